@@ -0,0 +1,211 @@
+//! Parsing of the `#[fadroma(...)]` attributes which control how types evolve.
+
+use syn::{
+    Attribute, Meta, NestedMeta, Lit, ExprPath,
+    ItemEnum, spanned::Spanned
+};
+use quote::quote;
+use proc_macro2::TokenStream;
+
+const ATTR: &str = "fadroma";
+
+/// `#[fadroma(version = N)]` on a struct or an enum.
+#[derive(Clone, Copy, Default)]
+pub struct ContainerAttrs {
+    pub version: Option<u8>
+}
+
+/// `#[fadroma(since = N, until = N, default = "path")]` on a field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// The version in which the field was added.
+    pub since: Option<u8>,
+    /// The version in which the field was removed.
+    pub until: Option<u8>,
+    pub default: Option<ExprPath>
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+
+        for meta in args(attrs)? {
+            match &meta {
+                Meta::NameValue(x) if x.path.is_ident("version") => {
+                    let version = parse_u8(&x.lit)?;
+
+                    if version == 0 {
+                        return Err(syn::Error::new_spanned(&x.lit, "The version must be at least 1."));
+                    }
+
+                    result.version = Some(version);
+                },
+                _ => return Err(syn::Error::new_spanned(meta, "Expected `version = N`."))
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute], container: ContainerAttrs) -> syn::Result<Self> {
+        let mut result = Self::default();
+        let mut span = None;
+
+        for meta in args(attrs)? {
+            span = Some(meta.span());
+
+            match &meta {
+                Meta::NameValue(x) if x.path.is_ident("since") => {
+                    result.since = Some(parse_u8(&x.lit)?);
+                },
+                Meta::NameValue(x) if x.path.is_ident("until") => {
+                    result.until = Some(parse_u8(&x.lit)?);
+                },
+                Meta::NameValue(x) if x.path.is_ident("default") => match &x.lit {
+                    Lit::Str(path) => result.default = Some(path.parse()?),
+                    lit => return Err(syn::Error::new_spanned(
+                        lit,
+                        "Expected the path to a function as a string."
+                    ))
+                },
+                Meta::Path(x) if x.is_ident("default") => {},
+                _ => return Err(syn::Error::new_spanned(
+                    meta,
+                    "Expected one of `since = N`, `until = N` or `default = \"path\"`."
+                ))
+            }
+        }
+
+        let Some(span) = span else {
+            return Ok(result);
+        };
+
+        let Some(version) = container.version else {
+            return Err(syn::Error::new(
+                span,
+                "Fields can only be versioned if the type has a `#[fadroma(version = N)]` attribute."
+            ));
+        };
+
+        if result.since.is_none() && result.until.is_none() {
+            return Err(syn::Error::new(
+                span,
+                "A default can only be set on fields with `since` or `until`."
+            ));
+        }
+
+        if let Some(since) = result.since {
+            if since == 0 || since > version {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`since` must be between 1 and the version of the type ({}).", version)
+                ));
+            }
+        }
+
+        if let Some(until) = result.until {
+            if until <= result.since.unwrap_or(0) || until > version {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`until` must be after `since` and at most the version of the type ({}).", version)
+                ));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns `false` for removed fields which are only ever read.
+    #[inline]
+    pub fn is_written(&self) -> bool {
+        self.until.is_none()
+    }
+
+    /// The condition on the `version` variable under which the
+    /// field is present or [`None`] if it is always present.
+    pub fn condition(&self) -> Option<TokenStream> {
+        match (self.since, self.until) {
+            (None, None) => None,
+            (Some(since), None) => Some(quote!(version >= #since)),
+            (None, Some(until)) => Some(quote!(version < #until)),
+            (Some(since), Some(until)) => Some(quote!(version >= #since && version < #until))
+        }
+    }
+
+    /// The value of the field when it's not present.
+    pub fn default_expr(&self) -> TokenStream {
+        match &self.default {
+            Some(path) => quote!(#path()),
+            None => quote!(::core::default::Default::default())
+        }
+    }
+}
+
+/// Returns the tag of each variant. Variants without a `#[fadroma(tag = N)]`
+/// attribute get the tag following the one of the previous variant.
+pub fn variant_tags(e: &ItemEnum) -> syn::Result<Vec<u8>> {
+    let mut result: Vec<u8> = Vec::with_capacity(e.variants.len());
+    let mut next = 0u16;
+
+    for variant in &e.variants {
+        let mut tag = None;
+
+        for meta in args(&variant.attrs)? {
+            match &meta {
+                Meta::NameValue(x) if x.path.is_ident("tag") => {
+                    tag = Some(parse_u8(&x.lit)?);
+                },
+                _ => return Err(syn::Error::new_spanned(meta, "Expected `tag = N`."))
+            }
+        }
+
+        let tag = match tag {
+            Some(tag) => tag,
+            None if next <= u8::MAX as u16 => next as u8,
+            None => return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "The tag of the variant exceeds 255."
+            ))
+        };
+
+        if result.contains(&tag) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("The tag {} is used by more than one variant.", tag)
+            ));
+        }
+
+        result.push(tag);
+        next = tag as u16 + 1;
+    }
+
+    Ok(result)
+}
+
+fn args(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+    let mut result = vec![];
+
+    for attr in attrs.iter().filter(|x| x.path.is_ident(ATTR)) {
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(syn::Error::new_spanned(attr, "Expected `#[fadroma(...)]`."));
+        };
+
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(meta) => result.push(meta),
+                NestedMeta::Lit(lit) => return Err(syn::Error::new_spanned(lit, "Unexpected literal."))
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_u8(lit: &Lit) -> syn::Result<u8> {
+    match lit {
+        Lit::Int(int) => int.base10_parse(),
+        _ => Err(syn::Error::new_spanned(lit, "Expected an integer between 0 and 255."))
+    }
+}
@@ -0,0 +1,164 @@
+use syn::{ItemStruct, Fields, Member, Index, Ident, Type, ext::IdentExt};
+use quote::{quote, format_ident};
+use proc_macro2::{Span, TokenStream};
+
+struct StoredField<'a> {
+    member: Member,
+    /// Used both as the suffix of the accessor names and as the storage key.
+    name: String,
+    ty: &'a Type
+}
+
+pub fn impl_field_storage(s: &ItemStruct) -> syn::Result<TokenStream> {
+    if !s.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &s.generics,
+            "FieldStorage cannot be derived for generic structs."
+        ));
+    }
+
+    let fields: Vec<StoredField> = match &s.fields {
+        Fields::Named(f) => f.named.iter().map(|x| {
+            let ident = x.ident.clone().unwrap();
+
+            StoredField {
+                name: ident.unraw().to_string(),
+                member: Member::Named(ident),
+                ty: &x.ty
+            }
+        }).collect(),
+        Fields::Unnamed(f) => f.unnamed.iter().enumerate().map(|(i, x)| {
+            StoredField {
+                name: i.to_string(),
+                member: Member::Unnamed(Index { index: i as u32, span: Span::call_site() }),
+                ty: &x.ty
+            }
+        }).collect(),
+        Fields::Unit => vec![]
+    };
+
+    if fields.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "FieldStorage can only be derived for structs with at least one field."
+        ));
+    }
+
+    let ident = &s.ident;
+    let vis = &s.vis;
+    let store = format_ident!("{}Store", ident);
+
+    let struct_doc = format!(
+        "Stores each field of [`{}`] under its own key in the namespace `N`.",
+        ident
+    );
+    let partial_err = format!("{}: only some of the fields are stored.", store);
+
+    let mut accessors = TokenStream::new();
+    let mut load_fields = TokenStream::new();
+    let mut save_fields = TokenStream::new();
+    let mut remove_fields = TokenStream::new();
+    let mut loaded = Vec::with_capacity(fields.len());
+
+    for (i, field) in fields.iter().enumerate() {
+        let StoredField { member, name, ty } = field;
+
+        let key = name.as_bytes();
+        let key = quote!([N::NAMESPACE, &[#(#key),*]].concat());
+
+        let load = format_ident!("load_{}", name);
+        let save = format_ident!("save_{}", name);
+        let var = Ident::new(&format!("x{}", i), Span::call_site());
+
+        let load_doc = format!("Loads the `{}` field.", name);
+        let save_doc = format!("Saves the `{}` field.", name);
+
+        accessors.extend(quote! {
+            #[doc = #load_doc]
+            #[inline]
+            pub fn #load(&self, storage: &dyn fadroma::cosmwasm_std::Storage) -> fadroma::cosmwasm_std::StdResult<Option<#ty>> {
+                fadroma::storage::load(storage, #key)
+            }
+
+            #[doc = #save_doc]
+            #[inline]
+            pub fn #save(&self, storage: &mut dyn fadroma::cosmwasm_std::Storage, value: &#ty) -> fadroma::cosmwasm_std::StdResult<()> {
+                fadroma::storage::save(storage, #key, value)
+            }
+        });
+
+        load_fields.extend(quote!(let #var = self.#load(storage)?;));
+        save_fields.extend(quote!(self.#save(storage, &value.#member)?;));
+        remove_fields.extend(quote!(fadroma::storage::remove(storage, #key);));
+        loaded.push((var, member));
+    }
+
+    let vars = loaded.iter().map(|(var, _)| var);
+    let somes = loaded.iter().map(|(var, _)| quote!(Some(#var)));
+    let nones = loaded.iter().map(|_| quote!(None));
+
+    // With a single field the two arms above are exhaustive.
+    let partial = (loaded.len() > 1).then(|| quote! {
+        _ => Err(fadroma::cosmwasm_std::StdError::generic_err(#partial_err))
+    });
+
+    let construct = match &s.fields {
+        Fields::Named(_) => {
+            let members = loaded.iter().map(|(var, member)| quote!(#member: #var));
+
+            quote!(#ident { #(#members),* })
+        },
+        _ => {
+            let vars = loaded.iter().map(|(var, _)| var);
+
+            quote!(#ident(#(#vars),*))
+        }
+    };
+
+    Ok(quote! {
+        #[doc = #struct_doc]
+        #vis struct #store<N: fadroma::storage::Namespace>(core::marker::PhantomData<N>);
+
+        #[automatically_derived]
+        impl<N: fadroma::storage::Namespace> #store<N> {
+            #[inline]
+            pub const fn new() -> Self {
+                Self(core::marker::PhantomData)
+            }
+
+            /// Loads all fields. Returns [`None`] if none of them are stored
+            /// and an error if only some of them are.
+            pub fn load(&self, storage: &dyn fadroma::cosmwasm_std::Storage) -> fadroma::cosmwasm_std::StdResult<Option<#ident>> {
+                #load_fields
+
+                match (#(#vars,)*) {
+                    (#(#somes,)*) => Ok(Some(#construct)),
+                    (#(#nones,)*) => Ok(None),
+                    #partial
+                }
+            }
+
+            /// Loads all fields, returning an error if they are not stored.
+            #[inline]
+            pub fn load_or_error(&self, storage: &dyn fadroma::cosmwasm_std::Storage) -> fadroma::cosmwasm_std::StdResult<#ident> {
+                self.load(storage)?.ok_or_else(|| fadroma::cosmwasm_std::StdError::not_found(
+                    format!("Storage load: {}", core::any::type_name::<#ident>())
+                ))
+            }
+
+            /// Saves all fields.
+            pub fn save(&self, storage: &mut dyn fadroma::cosmwasm_std::Storage, value: &#ident) -> fadroma::cosmwasm_std::StdResult<()> {
+                #save_fields
+
+                Ok(())
+            }
+
+            /// Removes all fields.
+            pub fn remove(&self, storage: &mut dyn fadroma::cosmwasm_std::Storage) {
+                #remove_fields
+            }
+
+            #accessors
+        }
+    })
+}
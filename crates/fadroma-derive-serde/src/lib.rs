@@ -12,6 +12,11 @@ use syn::{
 use quote::{quote, ToTokens};
 use proc_macro2::Span;
 
+use attrs::{ContainerAttrs, FieldAttrs};
+
+mod attrs;
+mod field_storage;
+
 #[cfg(test)]
 mod tests;
 
@@ -21,23 +26,20 @@ enum FieldsFor {
     Enum
 }
 
-#[proc_macro_derive(FadromaSerialize)]
+#[proc_macro_derive(FadromaSerialize, attributes(fadroma))]
 pub fn derive_serialize(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let item = parse_macro_input!(stream as Item);
 
     let result = match &item {
         Item::Struct(s) => {
-            let result = impl_struct_serialize(s);
-
-            quote!(#result)
+            match impl_struct_serialize(s) {
+                Ok(result) => quote!(#result),
+                Err(e) => e.to_compile_error()
+            }
         },
         Item::Enum(e) => {
-            match check_num_variants(&e) {
-                Ok(_) => {
-                    let result = impl_enum_serialize(e);
-
-                    quote!(#result)
-                },
+            match check_num_variants(&e).and_then(|_| impl_enum_serialize(e)) {
+                Ok(result) => quote!(#result),
                 Err(e) => e.to_compile_error()
             }
         },
@@ -52,23 +54,20 @@ pub fn derive_serialize(stream: proc_macro::TokenStream) -> proc_macro::TokenStr
     proc_macro::TokenStream::from(quote!(#result))
 }
 
-#[proc_macro_derive(FadromaDeserialize)]
+#[proc_macro_derive(FadromaDeserialize, attributes(fadroma))]
 pub fn derive_deserialize(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let item = parse_macro_input!(stream as Item);
 
     let result = match &item {
         Item::Struct(s) => {
-            let result = impl_struct_deserialize(s);
-
-            quote!(#result)
+            match impl_struct_deserialize(s) {
+                Ok(result) => quote!(#result),
+                Err(e) => e.to_compile_error()
+            }
         },
         Item::Enum(e) => {
-            match check_num_variants(&e) {
-                Ok(_) => {
-                    let result = impl_enum_deserialize(e);
-
-                    quote!(#result)
-                },
+            match check_num_variants(&e).and_then(|_| impl_enum_deserialize(e)) {
+                Ok(result) => quote!(#result),
                 Err(e) => e.to_compile_error()
             }
         },
@@ -83,6 +82,26 @@ pub fn derive_deserialize(stream: proc_macro::TokenStream) -> proc_macro::TokenS
     proc_macro::TokenStream::from(quote!(#result))
 }
 
+#[proc_macro_derive(FieldStorage)]
+pub fn derive_field_storage(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item = parse_macro_input!(stream as Item);
+
+    let result = match &item {
+        Item::Struct(s) => match field_storage::impl_field_storage(s) {
+            Ok(result) => result,
+            Err(e) => e.to_compile_error()
+        },
+        _ => {
+            syn::Error::new(
+                Span::call_site(),
+                "This macro can only be used on struct definitions."
+            ).to_compile_error()
+        }
+    };
+
+    proc_macro::TokenStream::from(result)
+}
+
 fn check_num_variants(item: &ItemEnum) -> syn::Result<()> {
     if item.variants.len() > u8::MAX as usize {
         syn::Error::new(Span::call_site(), "Enum variants cannot exceed 255.");
@@ -91,56 +110,81 @@ fn check_num_variants(item: &ItemEnum) -> syn::Result<()> {
     Ok(())
 }
 
-fn impl_struct_serialize(s: &ItemStruct) -> ItemImpl {    
+fn impl_struct_serialize(s: &ItemStruct) -> syn::Result<ItemImpl> {
+    let container = ContainerAttrs::parse(&s.attrs)?;
+
     let (size_hint, to_bytes) = match &s.fields {
         Fields::Named(f) => (
-            size_hint_fields(&f.named, FieldsFor::Struct),
-            to_bytes_fields(&f.named, FieldsFor::Struct)
+            size_hint_fields(&f.named, FieldsFor::Struct, container)?,
+            to_bytes_fields(&f.named, FieldsFor::Struct, container)?
         ),
         Fields::Unnamed(f) => (
-            size_hint_fields(&f.unnamed, FieldsFor::Struct),
-            to_bytes_fields(&f.unnamed, FieldsFor::Struct)
+            size_hint_fields(&f.unnamed, FieldsFor::Struct, container)?,
+            to_bytes_fields(&f.unnamed, FieldsFor::Struct, container)?
         ),
         Fields::Unit => (quote!(0), quote!(Ok(())))
     };
 
-    impl_ser(&s.ident, &s.generics, &size_hint, &to_bytes)
+    let (size_hint, to_bytes) = match container.version {
+        Some(version) => (
+            quote!(1 + #size_hint),
+            quote! {
+                ser.write_byte(#version);
+
+                #to_bytes
+            }
+        ),
+        None => (size_hint, to_bytes)
+    };
+
+    Ok(impl_ser(&s.ident, &s.generics, &size_hint, &to_bytes))
 }
 
-fn impl_enum_serialize(e: &ItemEnum) -> ItemImpl {
+fn impl_enum_serialize(e: &ItemEnum) -> syn::Result<ItemImpl> {
+    let container = ContainerAttrs::parse(&e.attrs)?;
+    let tags = attrs::variant_tags(e)?;
+
     let mut size_hint_arms = Punctuated::<Arm, Comma>::new();
     let mut to_bytes_arms = Punctuated::<Arm, Comma>::new();
 
-    for (i, variant) in e.variants.iter().enumerate() {
-        let tag = i as u8;
+    for (variant, tag) in e.variants.iter().zip(tags) {
         let ident = &variant.ident;
 
         let (size_hint, to_bytes) = match &variant.fields {
             Fields::Named(f) => {
-                let size_hint = size_hint_fields(&f.named, FieldsFor::Enum);
-                let to_bytes = to_bytes_fields(&f.named, FieldsFor::Enum);
+                let size_hint = size_hint_fields(&f.named, FieldsFor::Enum, container)?;
+                let to_bytes = to_bytes_fields(&f.named, FieldsFor::Enum, container)?;
+
+                // Removed fields are not written so they are left out of the pattern.
+                let mut fields = Punctuated::<FieldPat, Comma>::new();
 
-                let fields = Punctuated::<FieldPat, Comma>::from_iter(
-                    f.named.clone().into_iter().map(|x| {
-                        let ident = x.ident.unwrap();
-                        FieldPat {
+                for x in &f.named {
+                    if !FieldAttrs::parse(&x.attrs, container)?.is_written() {
+                        continue;
+                    }
+
+                    let ident = x.ident.clone().unwrap();
+                    fields.push(FieldPat {
+                        attrs: Vec::new(),
+                        member: Member::Named(ident.clone()),
+                        colon_token: None,
+                        pat: Box::new(Pat::Ident(PatIdent {
                             attrs: Vec::new(),
-                            member: Member::Named(ident.clone()),
-                            colon_token: None,
-                            pat: Box::new(Pat::Ident(PatIdent {
-                                attrs: Vec::new(),
-                                by_ref: None,
-                                mutability: None,
-                                ident,
-                                subpat: None
-                            }))
-                        }
-                    })
-                );
+                            by_ref: None,
+                            mutability: None,
+                            ident,
+                            subpat: None
+                        }))
+                    });
+                }
+
+                if !fields.empty_or_trailing() {
+                    fields.push_punct(Comma::default());
+                }
 
                 (
-                    parse_quote!(Self::#ident { #fields } => #size_hint),
-                    parse_quote!(Self::#ident { #fields } => {
+                    parse_quote!(Self::#ident { #fields .. } => #size_hint),
+                    parse_quote!(Self::#ident { #fields .. } => {
                         ser.write_byte(#tag);
 
                         #to_bytes
@@ -148,11 +192,11 @@ fn impl_enum_serialize(e: &ItemEnum) -> ItemImpl {
                 )
             },
             Fields::Unnamed(f) => {
-                let fields = f.unnamed.iter().enumerate().map(|(i, _)|  {
+                let fields = f.unnamed.iter().enumerate().map(|(i, x)|  {
                     let ident = Ident::new(&format!("x{}", i), Span::call_site());
 
                     Field {
-                        attrs: Vec::new(),
+                        attrs: x.attrs.clone(),
                         vis: Visibility::Inherited,
                         ident: Some(ident),
                         colon_token: None,
@@ -161,11 +205,13 @@ fn impl_enum_serialize(e: &ItemEnum) -> ItemImpl {
                 });
 
                 let fields = Punctuated::<Field, Comma>::from_iter(fields);
-                let size_hint = size_hint_fields(&fields, FieldsFor::Enum);
-                let to_bytes = to_bytes_fields(&fields, FieldsFor::Enum);
+                let size_hint = size_hint_fields(&fields, FieldsFor::Enum, container)?;
+                let to_bytes = to_bytes_fields(&fields, FieldsFor::Enum, container)?;
 
-                let pat_idents = Punctuated::<Pat, Comma>::from_iter(
-                    fields.clone().into_iter().map(|x|
+                let mut pat_idents = Punctuated::<Pat, Comma>::new();
+
+                for x in fields {
+                    let pat = if FieldAttrs::parse(&x.attrs, container)?.is_written() {
                         Pat::Ident(PatIdent {
                             attrs: Vec::new(),
                             by_ref: None,
@@ -173,8 +219,12 @@ fn impl_enum_serialize(e: &ItemEnum) -> ItemImpl {
                             ident: x.ident.unwrap(),
                             subpat: None
                         })
-                    )
-                );
+                    } else {
+                        parse_quote!(_)
+                    };
+
+                    pat_idents.push(pat);
+                }
                 
                 (
                     parse_quote!(Self::#ident(#pat_idents) => #size_hint),
@@ -201,46 +251,68 @@ fn impl_enum_serialize(e: &ItemEnum) -> ItemImpl {
         to_bytes_arms.push(to_bytes);
     }
 
-    let match_size_hint = quote!(1 + match self { #size_hint_arms });
-    let match_to_bytes = quote!(match self { #to_bytes_arms });
+    let (match_size_hint, match_to_bytes) = match container.version {
+        Some(version) => (
+            quote!(2 + match self { #size_hint_arms }),
+            quote! {
+                ser.write_byte(#version);
+
+                match self { #to_bytes_arms }
+            }
+        ),
+        None => (
+            quote!(1 + match self { #size_hint_arms }),
+            quote!(match self { #to_bytes_arms })
+        )
+    };
 
-    impl_ser(&e.ident, &e.generics, &match_size_hint, &match_to_bytes)
+    Ok(impl_ser(&e.ident, &e.generics, &match_size_hint, &match_to_bytes))
 }
 
-fn impl_struct_deserialize(s: &ItemStruct) -> ItemImpl {
+fn impl_struct_deserialize(s: &ItemStruct) -> syn::Result<ItemImpl> {
+    let container = ContainerAttrs::parse(&s.attrs)?;
+
     let from_bytes = match &s.fields {
         Fields::Named(f) => {
-            let from_bytes = from_bytes_struct(f);
+            let from_bytes = from_bytes_struct(f, container)?;
 
             quote!(Self { #from_bytes })
         },
         Fields::Unnamed(f) => {
-            let from_bytes = from_bytes_tuple(f);
+            let from_bytes = from_bytes_tuple(f, container)?;
 
             quote!(Self(#from_bytes))
         },
         Fields::Unit => quote!(Self)
     };
 
-    impl_de(&s.ident, &s.generics, &quote!(Ok(#from_bytes)))
+    let read_version = read_version(container);
+    let body = quote! {
+        #read_version
+        Ok(#from_bytes)
+    };
+
+    Ok(impl_de(&s.ident, &s.generics, &body))
 }
 
-fn impl_enum_deserialize(e: &ItemEnum) -> ItemImpl {
+fn impl_enum_deserialize(e: &ItemEnum) -> syn::Result<ItemImpl> {
+    let container = ContainerAttrs::parse(&e.attrs)?;
+    let tags = attrs::variant_tags(e)?;
+
     let tag_var = Ident::new("tag".into(), Span::call_site());
     let mut arms = Punctuated::<Arm, Comma>::new();
 
-    for (i, variant) in e.variants.iter().enumerate() {
-        let tag = i as u8;
+    for (variant, tag) in e.variants.iter().zip(tags) {
         let ident = &variant.ident;
 
         let arm = match &variant.fields {
             Fields::Named(f) => {
-                let from_bytes = from_bytes_struct(f);
+                let from_bytes = from_bytes_struct(f, container)?;
 
                 parse_quote!(#tag => Ok(Self::#ident { #from_bytes }))
             },
             Fields::Unnamed(f) => {
-                let from_bytes = from_bytes_tuple(f);
+                let from_bytes = from_bytes_tuple(f, container)?;
 
                 parse_quote!(#tag => Ok(Self::#ident(#from_bytes)))
             },
@@ -250,47 +322,76 @@ fn impl_enum_deserialize(e: &ItemEnum) -> ItemImpl {
         arms.push(arm);
     }
 
-    if e.variants.len() < u8::MAX as usize {
+    if e.variants.len() <= u8::MAX as usize {
         let arm = parse_quote!(_ => Err(fadroma::bin_serde::Error::InvalidType));
         arms.push(arm);
     }
 
+    let read_version = read_version(container);
     let tag_stmt: Stmt = parse_quote!(let #tag_var = de.read_byte()?;);
     let match_expr: ExprMatch = parse_quote!(match #tag_var { #arms });
     let body = quote!{
+        #read_version
         #tag_stmt
         #match_expr
     };
 
-    impl_de(&e.ident, &e.generics, &body)
+    Ok(impl_de(&e.ident, &e.generics, &body))
 }
 
-fn size_hint_fields(fields: &Punctuated<Field, Comma>, fields_for: FieldsFor) -> proc_macro2::TokenStream {
-    if fields.len() > 0 {
-        let mut result = Punctuated::<Expr, Add>::new();
+/// Reads the version of versioned types into a `version` variable,
+/// rejecting versions that are newer than the one of the type.
+fn read_version(container: ContainerAttrs) -> proc_macro2::TokenStream {
+    match container.version {
+        Some(version) => quote! {
+            let version = de.read_byte()?;
 
-        let receiver = match fields_for {
-            FieldsFor::Struct => quote!(&self.),
-            FieldsFor::Enum => proc_macro2::TokenStream::new(),
-        };
+            if version == 0 || version > #version {
+                return Err(fadroma::bin_serde::Error::InvalidType);
+            }
+        },
+        None => proc_macro2::TokenStream::new()
+    }
+}
 
-        for (i, f) in fields.iter().enumerate() {
-            let member = match f.ident.clone() {
-                Some(ident) => Member::Named(ident),
-                None => Member::Unnamed(Index { index: i as u32, span: Span::call_site() })
-            };
+fn size_hint_fields(
+    fields: &Punctuated<Field, Comma>,
+    fields_for: FieldsFor,
+    container: ContainerAttrs
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut result = Punctuated::<Expr, Add>::new();
+
+    let receiver = match fields_for {
+        FieldsFor::Struct => quote!(&self.),
+        FieldsFor::Enum => proc_macro2::TokenStream::new(),
+    };
 
-            let expr = parse_quote!(fadroma::bin_serde::FadromaSerialize::size_hint(#receiver #member));
-            result.push(expr);
+    for (i, f) in fields.iter().enumerate() {
+        if !FieldAttrs::parse(&f.attrs, container)?.is_written() {
+            continue;
         }
 
-        result.to_token_stream()
+        let member = match f.ident.clone() {
+            Some(ident) => Member::Named(ident),
+            None => Member::Unnamed(Index { index: i as u32, span: Span::call_site() })
+        };
+
+        let expr = parse_quote!(fadroma::bin_serde::FadromaSerialize::size_hint(#receiver #member));
+        result.push(expr);
+    }
+
+    if result.is_empty() {
+        Ok(quote!(0))
     } else {
-        quote!(0)
+        Ok(result.to_token_stream())
     }
 }
 
-fn to_bytes_fields(fields: &Punctuated<Field, Comma>, fields_for: FieldsFor) -> proc_macro2::TokenStream {
+fn to_bytes_fields(
+    fields: &Punctuated<Field, Comma>,
+    fields_for: FieldsFor,
+    container: ContainerAttrs
+) -> syn::Result<proc_macro2::TokenStream> {
     let mut result = proc_macro2::TokenStream::new();
 
     let receiver = match fields_for {
@@ -299,6 +400,10 @@ fn to_bytes_fields(fields: &Punctuated<Field, Comma>, fields_for: FieldsFor) ->
     };
 
     for (i, f) in fields.iter().enumerate() {
+        if !FieldAttrs::parse(&f.attrs, container)?.is_written() {
+            continue;
+        }
+
         let member = match f.ident.clone() {
             Some(ident) => Member::Named(ident),
             None => Member::Unnamed(Index { index: i as u32, span: Span::call_site() })
@@ -308,41 +413,71 @@ fn to_bytes_fields(fields: &Punctuated<Field, Comma>, fields_for: FieldsFor) ->
         result.extend(stmt);
     }
 
-    quote! {
+    Ok(quote! {
         #result
         
         Ok(())
-    }
+    })
 }
 
-fn from_bytes_struct(fields: &FieldsNamed) -> Punctuated::<FieldValue, Comma> {
+fn from_bytes_struct(
+    fields: &FieldsNamed,
+    container: ContainerAttrs
+) -> syn::Result<Punctuated::<FieldValue, Comma>> {
     let mut result = Punctuated::<FieldValue, Comma>::new();
 
     for f in &fields.named {
+        let expr = from_bytes_field(f, container)?;
+
         let field_expr: FieldValue = match &f.ident {
-            Some(ident) => parse_quote!(#ident: de.deserialize()?),
+            Some(ident) => parse_quote!(#ident: #expr),
             None => unreachable!()
         };
 
         result.push(field_expr);
     }
 
-    result
+    Ok(result)
 }
 
-fn from_bytes_tuple(fields: &FieldsUnnamed) -> Punctuated::<Expr, Comma> {
+fn from_bytes_tuple(
+    fields: &FieldsUnnamed,
+    container: ContainerAttrs
+) -> syn::Result<Punctuated::<Expr, Comma>> {
     let mut result = Punctuated::<Expr, Comma>::new();
 
     for f in &fields.unnamed {
         let field_expr: Expr = match f.ident.clone() {
             Some(_) => unreachable!(),
-            None => parse_quote!(de.deserialize()?)
+            None => from_bytes_field(f, container)?
         };
 
         result.push(field_expr);
     }
 
-    result
+    Ok(result)
+}
+
+/// Versioned fields are only read if they are present in the version being read.
+fn from_bytes_field(field: &Field, container: ContainerAttrs) -> syn::Result<Expr> {
+    let attrs = FieldAttrs::parse(&field.attrs, container)?;
+
+    let expr = match attrs.condition() {
+        Some(condition) => {
+            let default = attrs.default_expr();
+
+            parse_quote! {
+                if #condition {
+                    de.deserialize()?
+                } else {
+                    #default
+                }
+            }
+        },
+        None => parse_quote!(de.deserialize()?)
+    };
+
+    Ok(expr)
 }
 
 fn impl_ser(
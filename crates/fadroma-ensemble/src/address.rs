@@ -0,0 +1,86 @@
+use fadroma::crypto::sha_256;
+
+use super::{env::MockEnv, dump::hex};
+
+/// The prefix of every contract address that is generated by the ensemble.
+pub const CONTRACT_ADDRESS_PREFIX: &str = "contract";
+
+/// Number of hash bytes that make up a generated address.
+/// Hex encoded and prefixed with [`CONTRACT_ADDRESS_PREFIX`],
+/// this still fits within [`MockEnv::MAX_ADDRESS_LEN`].
+const ADDRESS_HASH_LEN: usize = 20;
+
+/// Derives the address of the `instance_id`-th contract instance
+/// created from `code_id`. This mirrors the classic CosmWasm scheme
+/// where the address only depends on the code id and a global
+/// instance sequence number.
+pub(crate) fn instance_address(code_id: u64, instance_id: u64) -> String {
+    let mut data = Vec::with_capacity(4 + 16);
+    data.extend_from_slice(b"wasm");
+    data.extend_from_slice(&code_id.to_be_bytes());
+    data.extend_from_slice(&instance_id.to_be_bytes());
+
+    encode(&sha_256(&data))
+}
+
+/// Derives a predictable address from the code hash, the creator and a
+/// user chosen salt, the same inputs that `instantiate2` uses on chains that support it.
+/// The same inputs will always produce the same address, though not the one a chain would.
+pub(crate) fn predictable_address(code_hash: &str, creator: &str, salt: &[u8]) -> String {
+    let mut data = Vec::with_capacity(5 + 24 + code_hash.len() + creator.len() + salt.len());
+    data.extend_from_slice(b"wasm2");
+
+    // Length prefix each component so that different
+    // splits of the same bytes can't produce collisions.
+    for part in [code_hash.as_bytes(), creator.as_bytes(), salt] {
+        data.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        data.extend_from_slice(part);
+    }
+
+    encode(&sha_256(&data))
+}
+
+fn encode(hash: &[u8; 32]) -> String {
+    let result = format!("{}{}", CONTRACT_ADDRESS_PREFIX, hex(&hash[..ADDRESS_HASH_LEN]));
+    debug_assert!(result.len() <= MockEnv::MAX_ADDRESS_LEN);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cosmwasm_std::{testing::MockApi, Api};
+    use super::*;
+
+    #[test]
+    fn generated_addresses_are_valid_and_deterministic() {
+        let api = MockApi::default();
+
+        let addr = instance_address(0, 1);
+        assert_eq!(addr, instance_address(0, 1));
+        assert!(addr.starts_with(CONTRACT_ADDRESS_PREFIX));
+        assert!(addr.len() <= MockEnv::MAX_ADDRESS_LEN);
+        assert_eq!(addr, addr.to_lowercase());
+
+        let canon = api.addr_canonicalize(&addr).unwrap();
+        assert_eq!(api.addr_humanize(&canon).unwrap().as_str(), addr);
+
+        assert_ne!(addr, instance_address(0, 2));
+        assert_ne!(addr, instance_address(1, 1));
+    }
+
+    #[test]
+    fn predictable_addresses_depend_on_all_inputs() {
+        let addr = predictable_address("hash", "creator", b"salt");
+        assert_eq!(addr, predictable_address("hash", "creator", b"salt"));
+        assert!(addr.len() <= MockEnv::MAX_ADDRESS_LEN);
+
+        assert_ne!(addr, predictable_address("hash2", "creator", b"salt"));
+        assert_ne!(addr, predictable_address("hash", "creator2", b"salt"));
+        assert_ne!(addr, predictable_address("hash", "creator", b"salt2"));
+        assert_ne!(
+            predictable_address("ab", "c", b""),
+            predictable_address("a", "bc", b"")
+        );
+    }
+}
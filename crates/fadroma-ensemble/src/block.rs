@@ -1,14 +1,25 @@
-use std::ops::Range;
+use std::{ops::Range, time::Duration};
 use oorandom::Rand64;
 
+use fadroma::cosmwasm_std::Timestamp;
+
 #[derive(Clone, Debug)]
 pub struct Block {
-    pub height: u64,
-    pub time: u64,
+    height: u64,
+    time: Timestamp,
     incr: BlockIncrement,
+    block_time: Option<Duration>,
     is_frozen: bool
 }
 
+/// The height and time of a [`Block`] at a given point.
+/// Created by [`Block::snapshot`] and restored with [`Block::rewind`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockSnapshot {
+    pub height: u64,
+    pub time: Timestamp
+}
+
 #[derive(Clone, Debug)]
 enum BlockIncrement {
     Random {
@@ -24,11 +35,66 @@ enum BlockIncrement {
 }
 
 impl Block {
+    /// The current block height.
+    #[inline]
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// The current block time, in nanosecond precision.
+    #[inline]
+    pub fn time(&self) -> Timestamp {
+        self.time
+    }
+
+    /// The time it takes to produce a single block. This is used to
+    /// derive the height when jumping to a point in time and vice versa.
+    ///
+    /// It is the duration set with [`Block::set_block_time`]. If none was
+    /// set, it is the `time` set in [`Block::exact_increments`] or the middle
+    /// of the `time` range set in [`Block::random_increments`].
+    pub fn block_time(&self) -> Duration {
+        if let Some(block_time) = self.block_time {
+            return block_time;
+        }
+
+        let seconds = match &self.incr {
+            BlockIncrement::Exact { time, .. } => *time,
+            BlockIncrement::Random { time, .. } => (time.start + time.end) / 2
+        };
+
+        Duration::from_secs(seconds)
+    }
+
+    /// Sets the time it takes to produce a single block, used when jumping
+    /// to a height or a point in time. Unlike the increments, it can be
+    /// set with sub-second precision and doesn't affect [`Block::next`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use fadroma_ensemble::Block;
+    ///
+    /// let mut block = Block::default();
+    /// block.set_block_time(Duration::from_millis(5500));
+    ///
+    /// let old_height = block.height();
+    /// block.advance(Duration::from_secs(55));
+    ///
+    /// assert_eq!(block.height() - old_height, 10);
+    /// ```
+    pub fn set_block_time(&mut self, block_time: Duration) {
+        assert!(!block_time.is_zero(), "Block time must be bigger than 0.");
+
+        self.block_time = Some(block_time);
+    }
+
     /// Will increase the block height by `height` and
     /// block time by `height` * `time` for each increment.
-    /// 
+    ///
     /// `time` is in seconds.
-    /// 
+    ///
     /// This is the default strategy.
     pub fn exact_increments(&mut self, height: u64, time: u64) {
         assert!(height > 0 && time > 0, "Height and time must be bigger than 0. Call \"freeze\" if you want to stop incrementing blocks.");
@@ -38,7 +104,7 @@ impl Block {
 
     /// Will increase the block height by a number within the range of `height` and
     /// block time by that same `height` * `time` for each increment.
-    /// 
+    ///
     /// `time` is in seconds.
     pub fn random_increments(&mut self, height: Range<u64>, time: Range<u64>) {
         assert!(height.start > 0 && time.start > 0, "Height and time range start must be bigger than 0.");
@@ -48,6 +114,8 @@ impl Block {
 
     /// Will stop incrementing blocks on each message execution
     /// and calling `next` and `increment` will have no effect.
+    ///
+    /// Explicit jumps such as [`Block::jump_to_height`] still apply.
     pub fn freeze(&mut self) {
         self.is_frozen = true;
     }
@@ -58,23 +126,23 @@ impl Block {
     }
 
     /// Increments the block height and time by the amount configured - once.
-    ///  
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
     /// use fadroma_ensemble::Block;
-    /// 
+    ///
     /// let mut block = Block::default();
     /// block.exact_increments(1, 5);
-    /// 
-    /// let old_height = block.height;
-    /// let old_time = block.time;
-    /// 
+    ///
+    /// let old_height = block.height();
+    /// let old_time = block.time();
+    ///
     /// block.next();
-    /// 
-    /// assert_eq!(block.height - old_height, 1);
-    /// assert_eq!(block.time - old_time, 5);
-    /// 
+    ///
+    /// assert_eq!(block.height() - old_height, 1);
+    /// assert_eq!(block.time().seconds() - old_time.seconds(), 5);
+    ///
     /// ```
     #[inline]
     pub fn next(&mut self) {
@@ -82,23 +150,23 @@ impl Block {
     }
 
     ///Increments the block height and time by the amount configured, multiplied by the `times` parameter.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
     /// use fadroma_ensemble::Block;
-    /// 
+    ///
     /// let mut block = Block::default();
     /// block.exact_increments(1, 5);
-    /// 
-    /// let old_height = block.height;
-    /// let old_time = block.time;
-    /// 
+    ///
+    /// let old_height = block.height();
+    /// let old_time = block.time();
+    ///
     /// block.increment(3);
-    /// 
-    /// assert_eq!(block.height - old_height, 3);
-    /// assert_eq!(block.time - old_time, 15);
-    /// 
+    ///
+    /// assert_eq!(block.height() - old_height, 3);
+    /// assert_eq!(block.time().seconds() - old_time.seconds(), 15);
+    ///
     /// ```
     pub fn increment(&mut self, times: u64) {
         if self.is_frozen {
@@ -110,7 +178,7 @@ impl Block {
                 let height = height * times;
 
                 self.height += height;
-                self.time += height * time;
+                self.time = self.time.plus_seconds(height * time);
             },
             BlockIncrement::Random { height, time } => {
                 // TODO: randomize this seed
@@ -122,10 +190,139 @@ impl Block {
                 let height = rand_height * times;
 
                 self.height += height;
-                self.time += height * rand_time;
+                self.time = self.time.plus_seconds(height * rand_time);
             }
         }
     }
+
+    /// Moves forward to the given `height`. The block time
+    /// advances by [`Block::block_time`] for every block skipped,
+    /// up to the maximum time that a [`Timestamp`] can represent.
+    ///
+    /// # Panics
+    ///
+    /// If `height` is lower than the current height. Use [`Block::rewind`] to go back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fadroma_ensemble::Block;
+    ///
+    /// let mut block = Block::default();
+    /// block.exact_increments(1, 6);
+    ///
+    /// let old_time = block.time();
+    /// block.jump_to_height(block.height() + 100);
+    ///
+    /// assert_eq!(block.time().seconds() - old_time.seconds(), 600);
+    /// ```
+    pub fn jump_to_height(&mut self, height: u64) {
+        assert!(
+            height >= self.height,
+            "Cannot jump to height {} which is before the current height {}.",
+            height,
+            self.height
+        );
+
+        let blocks = height - self.height;
+        let nanos = blocks.saturating_mul(self.block_time_nanos());
+
+        self.height = height;
+        self.time = Timestamp::from_nanos(self.time.nanos().saturating_add(nanos));
+    }
+
+    /// Moves forward to the given `time`. The height increases by the number
+    /// of blocks needed to cover the elapsed time, rounded up, so that any
+    /// change in time is always observed in a new block. The height saturates
+    /// at [`u64::MAX`].
+    ///
+    /// # Panics
+    ///
+    /// If `time` is before the current block time. Use [`Block::rewind`] to go back.
+    pub fn jump_to_time(&mut self, time: Timestamp) {
+        assert!(
+            time >= self.time,
+            "Cannot jump to time {} which is before the current block time {}.",
+            time,
+            self.time
+        );
+
+        let elapsed = time.nanos() - self.time.nanos();
+        let block_time = self.block_time_nanos();
+
+        // Rounds up, adding a block for any remainder. `div_ceil`
+        // isn't available in the toolchain that contracts are built with.
+        let blocks = elapsed / block_time + (elapsed % block_time).min(1);
+
+        self.height = self.height.saturating_add(blocks);
+        self.time = time;
+    }
+
+    /// Advances the block time by `duration` and the height
+    /// by the blocks produced in that time. See [`Block::jump_to_time`].
+    /// The time saturates at the maximum that a [`Timestamp`] can represent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use fadroma_ensemble::Block;
+    ///
+    /// let mut block = Block::default();
+    /// block.exact_increments(1, 5);
+    ///
+    /// let old_height = block.height();
+    /// block.advance(Duration::from_secs(60 * 60));
+    ///
+    /// assert_eq!(block.height() - old_height, 720);
+    /// ```
+    #[inline]
+    pub fn advance(&mut self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let time = Timestamp::from_nanos(self.time.nanos().saturating_add(nanos));
+
+        self.jump_to_time(time)
+    }
+
+    /// Captures the current height and time so that they
+    /// can be restored later with [`Block::rewind`].
+    #[inline]
+    pub fn snapshot(&self) -> BlockSnapshot {
+        BlockSnapshot {
+            height: self.height,
+            time: self.time
+        }
+    }
+
+    /// Sets the height and time to the ones in the given `snapshot`.
+    /// Unlike the jump methods, this can move the block backwards.
+    /// Only the block is affected - contract state is left as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use fadroma_ensemble::Block;
+    ///
+    /// let mut block = Block::default();
+    /// let snapshot = block.snapshot();
+    ///
+    /// block.advance(Duration::from_secs(1000));
+    /// assert_ne!(block.snapshot(), snapshot);
+    ///
+    /// block.rewind(snapshot);
+    /// assert_eq!(block.snapshot(), snapshot);
+    /// ```
+    #[inline]
+    pub fn rewind(&mut self, snapshot: BlockSnapshot) {
+        self.height = snapshot.height;
+        self.time = snapshot.time;
+    }
+
+    #[inline]
+    fn block_time_nanos(&self) -> u64 {
+        u64::try_from(self.block_time().as_nanos()).unwrap_or(u64::MAX)
+    }
 }
 
 impl Default for Block {
@@ -133,17 +330,93 @@ impl Default for Block {
         Self {
             height: 1,
             #[cfg(target_arch = "wasm32")]
-            time: 1600000000,
+            time: Timestamp::from_seconds(1600000000),
             #[cfg(not(target_arch = "wasm32"))]
-            time: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            time: Timestamp::from_nanos(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos() as u64
+            ),
             incr: BlockIncrement::Exact {
                 height: 1,
                 time: 10
             },
+            block_time: None,
             is_frozen: false
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+    #[test]
+    fn jump_to_time_rounds_height_up() {
+        let mut block = Block::default();
+        block.exact_increments(1, 10);
+        block.rewind(BlockSnapshot { height: 5, time: Timestamp::from_seconds(100) });
+
+        block.jump_to_time(Timestamp::from_seconds(100));
+        assert_eq!(block.height(), 5);
+
+        block.jump_to_time(Timestamp::from_nanos(100 * NANOS_PER_SECOND + 1));
+        assert_eq!(block.height(), 6);
+        assert_eq!(block.time().subsec_nanos(), 1);
+
+        block.advance(Duration::from_secs(25));
+        assert_eq!(block.height(), 9);
+
+        block.freeze();
+        block.jump_to_height(20);
+        assert_eq!(block.height(), 20);
+        assert_eq!(block.time().nanos(), 235 * NANOS_PER_SECOND + 1);
+    }
+
+    #[test]
+    fn explicit_block_time() {
+        let mut block = Block::default();
+        block.random_increments(1..5, 1..100);
+        block.set_block_time(Duration::from_millis(2500));
+        block.rewind(BlockSnapshot { height: 1, time: Timestamp::from_seconds(100) });
+
+        block.jump_to_height(5);
+        assert_eq!(block.time().nanos(), 110 * NANOS_PER_SECOND);
+
+        block.advance(Duration::from_secs(6));
+        assert_eq!(block.height(), 8);
+    }
+
+    #[test]
+    fn jumps_saturate() {
+        let mut block = Block::default();
+        block.set_block_time(Duration::from_secs(u64::MAX));
+
+        block.jump_to_height(u64::MAX);
+        assert_eq!(block.height(), u64::MAX);
+        assert_eq!(block.time().nanos(), u64::MAX);
+
+        let mut block = Block::default();
+        block.advance(Duration::MAX);
+        assert_eq!(block.time().nanos(), u64::MAX);
+
+        let mut block = Block::default();
+        block.set_block_time(Duration::from_nanos(1));
+        block.rewind(BlockSnapshot { height: 10, time: Timestamp::from_nanos(0) });
+
+        block.advance(Duration::MAX);
+        assert_eq!(block.height(), u64::MAX);
+        assert_eq!(block.time().nanos(), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot jump to height 1")]
+    fn cannot_jump_backwards() {
+        let mut block = Block::default();
+        block.jump_to_height(10);
+        block.jump_to_height(1);
+    }
+}
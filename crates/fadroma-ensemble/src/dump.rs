@@ -0,0 +1,294 @@
+use std::{
+    fmt::{self, Display},
+    collections::BTreeMap
+};
+
+use serde::Serialize;
+use fadroma::{
+    bin_serde::{FadromaDeserialize, Deserializer},
+    cosmwasm_std::{Uint128, Timestamp}
+};
+#[cfg(feature = "ensemble-staking")]
+use fadroma::cosmwasm_std::Delegation;
+
+use super::storage::TestStorage;
+
+pub(crate) type Decoder = Box<dyn Fn(&[u8]) -> Option<serde_json::Value>>;
+
+/// Namespaces that the contents of contract storage are grouped by
+/// when creating a [`StateDump`], optionally with a decoder for their values.
+#[derive(Default)]
+pub(crate) struct Decoders(BTreeMap<Vec<u8>, Option<Decoder>>);
+
+/// A snapshot of the entire ensemble state, created by [`crate::ContractEnsemble::dump`].
+/// Serialize it (or call [`StateDump::to_json`]) for a machine readable representation
+/// or use its [`Display`] implementation for a compact form that fits in test logs.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct StateDump {
+    pub chain_id: String,
+    pub block: BlockDump,
+    pub codes: Vec<CodeDump>,
+    pub instances: Vec<InstanceDump>,
+    /// Balances by address and then denomination.
+    pub balances: BTreeMap<String, BTreeMap<String, Uint128>>,
+    #[cfg(feature = "ensemble-staking")]
+    pub delegations: Vec<Delegation>
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct BlockDump {
+    pub height: u64,
+    pub time: Timestamp
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct CodeDump {
+    pub id: u64,
+    pub code_hash: String
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct InstanceDump {
+    pub address: String,
+    pub code_id: u64,
+    pub label: String,
+    pub creator: String,
+    pub storage: Vec<NamespaceDump>
+}
+
+/// All storage entries under a known namespace.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NamespaceDump {
+    /// The namespace as text if it's printable or hex encoded otherwise.
+    /// [`None`] for the entries that don't fall under any known namespace.
+    pub namespace: Option<String>,
+    pub entries: Vec<EntryDump>
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct EntryDump {
+    /// The key without the namespace, hex encoded.
+    pub key: String,
+    /// The value as decoded by the decoder registered for the namespace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoded: Option<serde_json::Value>,
+    /// The raw value, hex encoded. Only present if the value couldn't be decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>
+}
+
+impl Decoders {
+    #[inline]
+    pub fn register_namespace(&mut self, namespace: &[u8]) {
+        self.0.entry(namespace.to_vec()).or_insert(None);
+    }
+
+    pub fn register<T: FadromaDeserialize + Serialize>(&mut self, namespace: &[u8]) {
+        let decoder: Decoder = Box::new(|bytes| {
+            let mut de = Deserializer::from(&bytes);
+            let value = de.deserialize::<T>().ok()?;
+
+            // Reject values that only partially matched the type.
+            if !de.is_finished() {
+                return None;
+            }
+
+            serde_json::to_value(&value).ok()
+        });
+
+        self.0.insert(namespace.to_vec(), Some(decoder));
+    }
+
+    pub fn dump_storage(&self, storage: &TestStorage) -> Vec<NamespaceDump> {
+        let mut groups: BTreeMap<Option<&[u8]>, Vec<EntryDump>> = BTreeMap::new();
+
+        for (key, value) in &storage.backing {
+            // Keys that match multiple namespaces belong to the longest one.
+            let known = self.0.iter()
+                .filter(|(ns, _)| key.starts_with(ns))
+                .max_by_key(|(ns, _)| ns.len());
+
+            let (namespace, entry) = match known {
+                Some((ns, decoder)) => {
+                    let decoded = decoder.as_ref().and_then(|decode| decode(value));
+
+                    (Some(ns.as_slice()), EntryDump {
+                        key: hex(&key[ns.len()..]),
+                        raw: decoded.is_none().then(|| hex(value)),
+                        decoded
+                    })
+                },
+                None => (None, EntryDump {
+                    key: hex(key),
+                    decoded: None,
+                    raw: Some(hex(value))
+                })
+            };
+
+            groups.entry(namespace).or_default().push(entry);
+        }
+
+        groups.into_iter().map(|(namespace, entries)|
+            NamespaceDump {
+                namespace: namespace.map(display_bytes),
+                entries
+            }
+        ).collect()
+    }
+}
+
+impl StateDump {
+    /// Serializes the dump to pretty printed JSON.
+    #[inline]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl Display for StateDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "chain {} | height {} | time {}",
+            self.chain_id,
+            self.block.height,
+            self.block.time
+        )?;
+
+        for code in &self.codes {
+            writeln!(f, "code {}: {}", code.id, code.code_hash)?;
+        }
+
+        for instance in &self.instances {
+            writeln!(
+                f,
+                "contract {} (code {}, label \"{}\", creator {})",
+                instance.address,
+                instance.code_id,
+                instance.label,
+                instance.creator
+            )?;
+
+            for ns in &instance.storage {
+                writeln!(f, "  [{}]", ns.namespace.as_deref().unwrap_or("?"))?;
+
+                for entry in &ns.entries {
+                    let value = match &entry.decoded {
+                        Some(decoded) => decoded.to_string(),
+                        None => format!("0x{}", entry.raw.as_deref().unwrap_or_default())
+                    };
+
+                    if entry.key.is_empty() {
+                        writeln!(f, "    = {}", value)?;
+                    } else {
+                        writeln!(f, "    0x{} = {}", entry.key, value)?;
+                    }
+                }
+            }
+        }
+
+        for (address, balances) in &self.balances {
+            let coins = balances.iter()
+                .map(|(denom, amount)| format!("{}{}", amount, denom))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            writeln!(f, "balance {}: {}", address, coins)?;
+        }
+
+        #[cfg(feature = "ensemble-staking")]
+        for delegation in &self.delegations {
+            writeln!(
+                f,
+                "delegation {} -> {}: {}{}",
+                delegation.delegator,
+                delegation.validator,
+                delegation.amount.amount,
+                delegation.amount.denom
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        result.push_str(&format!("{:02x}", byte));
+    }
+
+    result
+}
+
+/// Renders namespaces such as `b"ltp5P6sFZT"` as text.
+fn display_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.chars().all(|x| x.is_ascii_graphic()) => text.to_string(),
+        _ => format!("0x{}", hex(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fadroma::cosmwasm_std::Storage;
+    use super::*;
+
+    #[test]
+    fn groups_by_longest_namespace_and_decodes() {
+        let mut storage = TestStorage::new("contract");
+        storage.set(b"num", &[1, 5]);
+        storage.set(b"numbers\x01", &[1, 7]);
+        storage.set(b"numbers\x02", &[]);
+        storage.set(b"\xff", b"x");
+
+        let mut decoders = Decoders::default();
+        decoders.register::<u64>(b"num");
+        decoders.register::<u64>(b"numbers");
+        decoders.register_namespace(b"unused");
+
+        let dump = decoders.dump_storage(&storage);
+
+        assert_eq!(dump, vec![
+            NamespaceDump {
+                namespace: None,
+                entries: vec![EntryDump {
+                    key: "ff".into(),
+                    decoded: None,
+                    raw: Some("78".into())
+                }]
+            },
+            NamespaceDump {
+                namespace: Some("num".into()),
+                entries: vec![EntryDump {
+                    key: "".into(),
+                    decoded: Some(5.into()),
+                    raw: None
+                }]
+            },
+            NamespaceDump {
+                namespace: Some("numbers".into()),
+                entries: vec![
+                    EntryDump {
+                        key: "01".into(),
+                        decoded: Some(7.into()),
+                        raw: None
+                    },
+                    EntryDump {
+                        key: "02".into(),
+                        decoded: None,
+                        raw: Some("".into())
+                    }
+                ]
+            }
+        ]);
+    }
+
+    #[test]
+    fn display_bytes_falls_back_to_hex() {
+        assert_eq!(display_bytes(b"ltp5P6sFZT"), "ltp5P6sFZT");
+        assert_eq!(display_bytes(b"with space"), "0x77697468207370616365");
+        assert_eq!(display_bytes(&[0, 1]), "0x0001");
+    }
+}
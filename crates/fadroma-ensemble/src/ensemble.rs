@@ -1,5 +1,6 @@
 use std::{
     fmt::Debug,
+    cell::Cell,
     convert::TryFrom
 };
 use serde::{
@@ -8,9 +9,10 @@ use serde::{
 };
 use fadroma::{
     prelude::{ContractCode, ContractLink},
+    bin_serde::FadromaDeserialize,
     cosmwasm_std::{
         SubMsg, Deps, DepsMut, Env, Response, MessageInfo, Binary, Coin, Empty,
-        CosmosMsg, WasmMsg, BlockInfo, ContractInfo, BankMsg, Timestamp, Addr,
+        CosmosMsg, WasmMsg, BlockInfo, ContractInfo, BankMsg, Addr,
         SubMsgResponse, SubMsgResult, Reply, Storage, Api, Querier, QuerierWrapper,
         from_binary, to_binary, testing::MockApi
     }
@@ -24,6 +26,7 @@ use fadroma::cosmwasm_std::{
 };
 
 use super::{
+    address::{instance_address, predictable_address},
     bank::Balances,
     block::Block,
     env::MockEnv,
@@ -32,9 +35,13 @@ use super::{
         ResponseVariants, ExecuteResponse,
         InstantiateResponse, ReplyResponse
     },
-    state::State,
-    execution_state::{ExecutionState, MessageType},
-    error::{EnsembleError, RegistryError},
+    state::{State, ContractInstance},
+    storage::{StorageStats, StorageBreakdown, StorageGrowth},
+    dump::{Decoders, StateDump, BlockDump, CodeDump, InstanceDump},
+    execution_state::{
+        ExecutionState, ExecutionLimits, MessageType
+    },
+    error::{EnsembleError, RegistryError, ExecutionError},
     event::ProcessedEvents
 };
 
@@ -63,6 +70,21 @@ pub trait ContractHarness {
     }
 }
 
+/// Metadata that the ensemble keeps for each contract instance.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ContractInstanceInfo {
+    /// The address of the instance.
+    pub address: Addr,
+    /// Code ID of the instantiated contract.
+    pub code_id: u64,
+    /// Code hash of the instantiated contract.
+    pub code_hash: String,
+    /// The label that the instance was created with.
+    pub label: String,
+    /// The address that instantiated the contract.
+    pub creator: Addr
+}
+
 /// This the main type in the system that takes care of registering and executing contracts,
 /// keeping the blockchain simulation state and allowing the manipulation of particular parameters
 /// such as account funds, blocks or contract state in order to efficiently simulate testing scenarios.
@@ -155,7 +177,12 @@ pub(crate) struct Context {
     pub delegations: Delegations,
     pub state: State,
     block: Block,
-    chain_id: String
+    chain_id: String,
+    /// Overrides the generated address of the next instantiated contract.
+    next_address: Option<String>,
+    limits: ExecutionLimits,
+    query_depth: Cell<usize>,
+    decoders: Decoders
 }
 
 pub(crate) struct ContractUpload {
@@ -230,6 +257,50 @@ impl ContractEnsemble {
         self.ctx.chain_id = id.into();
     }
 
+    /// Sets the maximum depth of nested queries i.e a contract querying
+    /// a contract that queries another contract and so on. Queries that
+    /// exceed it fail with [`ExecutionError::QueryDepthExceeded`].
+    /// Pass [`None`] to remove the limit.
+    /// 
+    /// Defaults to [`crate::DEFAULT_MAX_QUERY_DEPTH`].
+    #[inline]
+    pub fn set_max_query_depth(&mut self, depth: Option<usize>) {
+        self.ctx.limits.max_query_depth = depth;
+    }
+
+    /// Sets the maximum depth of nested sub-messages. The message sent
+    /// by the sender is at depth 0, any messages it returns are at depth 1
+    /// and so on. A transaction that exceeds it fails with
+    /// [`ExecutionError::MessageDepthExceeded`] and all of its state is reverted.
+    /// Pass [`None`] to remove the limit.
+    /// 
+    /// There is no limit by default.
+    #[inline]
+    pub fn set_max_message_depth(&mut self, depth: Option<usize>) {
+        self.ctx.limits.max_message_depth = depth;
+    }
+
+    /// When enabled, executing a contract that is still waiting for its own
+    /// sub-messages to finish (i.e A -> B -> A) fails the entire transaction
+    /// with [`ExecutionError::Reentrancy`]. Replies are not considered reentrant.
+    /// 
+    /// Disabled by default since this is allowed on chain.
+    #[inline]
+    pub fn detect_reentrancy(&mut self, enabled: bool) {
+        self.ctx.limits.detect_reentrancy = enabled;
+    }
+
+    /// Sets the maximum size in bytes (keys and values) that the storage
+    /// of any single contract can grow to. A transaction that grows the
+    /// storage of a contract past it fails with [`ExecutionError::StorageQuotaExceeded`]
+    /// and all of its state is reverted. Pass [`None`] to remove the quota.
+    /// 
+    /// There is no quota by default.
+    #[inline]
+    pub fn set_storage_quota(&mut self, bytes: Option<usize>) {
+        self.ctx.limits.storage_quota = bytes;
+    }
+
     /// Adds the given funds that will be associated with the
     /// provided account's address. Can either be a contract or
     /// a mock user's address. You need to use this method first
@@ -395,11 +466,198 @@ impl ContractEnsemble {
         result
     }
 
+    /// Returns the number of keys and their total size in the
+    /// storage of the contract with the given address.
+    pub fn storage_stats(&self, address: impl AsRef<str>) -> EnsembleResult<StorageStats> {
+        let instance = self.ctx.state.instance(address.as_ref())?;
+
+        Ok(instance.storage.stats())
+    }
+
+    /// Returns the storage statistics of the contract with the given address
+    /// grouped by the given namespaces, such as [`fadroma::storage::Namespace::NAMESPACE`].
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, Binary, to_binary};
+    /// # use fadroma_ensemble::{ContractEnsemble, ContractHarness, MockEnv, AnyResult};
+    /// use fadroma::storage::{Namespace, SingleItem};
+    /// 
+    /// fadroma::namespace!(ConfigNs, b"config");
+    /// const CONFIG: SingleItem<u64, ConfigNs> = SingleItem::new();
+    /// 
+    /// struct Contract;
+    /// 
+    /// impl ContractHarness for Contract {
+    ///     fn instantiate(&self, deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    ///         CONFIG.save(deps.storage, &1)?;
+    ///         deps.storage.set(b"other", b"value");
+    /// 
+    ///         Ok(Response::default())
+    ///     }
+    /// #   fn execute(&self, _deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    /// #       Ok(Response::default())
+    /// #   }
+    /// #   fn query(&self, _deps: Deps, _env: Env, _msg: Binary) -> AnyResult<Binary> {
+    /// #       Ok(to_binary(&())?)
+    /// #   }
+    /// }
+    /// 
+    /// let mut ensemble = ContractEnsemble::new();
+    /// let code = ensemble.register(Box::new(Contract));
+    /// ensemble.instantiate(code.id, &(), MockEnv::new("sender", "contract")).unwrap();
+    /// 
+    /// let breakdown = ensemble.storage_breakdown("contract", &[ConfigNs::NAMESPACE]).unwrap();
+    /// assert_eq!(breakdown.namespaces[ConfigNs::NAMESPACE].keys, 1);
+    /// assert_eq!(breakdown.other.keys, 1);
+    /// ```
+    pub fn storage_breakdown(
+        &self,
+        address: impl AsRef<str>,
+        namespaces: &[&[u8]]
+    ) -> EnsembleResult<StorageBreakdown> {
+        let instance = self.ctx.state.instance(address.as_ref())?;
+
+        Ok(instance.storage.breakdown(namespaces))
+    }
+
+    /// Returns how the storage of the contract with the given address changed
+    /// in each of the transactions that wrote to it, oldest first.
+    /// Changes made through [`ContractEnsemble::contract_storage_mut`] are not included.
+    pub fn storage_growth(&self, address: impl AsRef<str>) -> EnsembleResult<&[StorageGrowth]> {
+        let instance = self.ctx.state.instance(address.as_ref())?;
+
+        Ok(&instance.growth)
+    }
+
+    /// Registers a storage namespace that the storage of each contract
+    /// is grouped by when calling [`ContractEnsemble::dump`].
+    /// Its values are shown hex encoded.
+    #[inline]
+    pub fn register_namespace(&mut self, namespace: &[u8]) {
+        self.ctx.decoders.register_namespace(namespace);
+    }
+
+    /// Registers a storage namespace that the storage of each contract
+    /// is grouped by when calling [`ContractEnsemble::dump`] and
+    /// whose values are decoded as `T`. Values that fail to decode
+    /// (such as the internal keys of [`fadroma::storage::map::Map`])
+    /// are shown hex encoded.
+    #[inline]
+    pub fn register_decoder<T: FadromaDeserialize + Serialize + 'static>(&mut self, namespace: &[u8]) {
+        self.ctx.decoders.register::<T>(namespace);
+    }
+
+    /// Captures the entire state of the ensemble - the block, all registered
+    /// codes, contract instances and their storage and bank balances.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, Binary, coin, to_binary};
+    /// # use fadroma_ensemble::{ContractEnsemble, ContractHarness, MockEnv, AnyResult};
+    /// use fadroma::storage::{Namespace, SingleItem};
+    /// 
+    /// fadroma::namespace!(CountNs, b"count");
+    /// const COUNT: SingleItem<u64, CountNs> = SingleItem::new();
+    /// 
+    /// struct Counter;
+    /// 
+    /// impl ContractHarness for Counter {
+    ///     fn instantiate(&self, deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    ///         COUNT.save(deps.storage, &3)?;
+    /// 
+    ///         Ok(Response::default())
+    ///     }
+    /// #   fn execute(&self, _deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    /// #       Ok(Response::default())
+    /// #   }
+    /// #   fn query(&self, _deps: Deps, _env: Env, _msg: Binary) -> AnyResult<Binary> {
+    /// #       Ok(to_binary(&())?)
+    /// #   }
+    /// }
+    /// 
+    /// let mut ensemble = ContractEnsemble::new();
+    /// ensemble.register_decoder::<u64>(CountNs::NAMESPACE);
+    /// ensemble.add_funds("wallet", vec![coin(100, "uscrt")]);
+    /// 
+    /// let code = ensemble.register(Box::new(Counter));
+    /// ensemble.instantiate(code.id, &(), MockEnv::new("wallet", "counter")).unwrap();
+    /// 
+    /// let dump = ensemble.dump();
+    /// let text = dump.to_string();
+    /// 
+    /// assert!(text.contains("contract counter (code 0, label \"counter\", creator wallet)"));
+    /// assert!(text.contains("  [count]\n    = 3"));
+    /// assert!(text.contains("balance wallet: 100uscrt"));
+    /// 
+    /// assert!(dump.to_json().contains("\"decoded\": 3"));
+    /// ```
+    pub fn dump(&self) -> StateDump {
+        let ctx = &self.ctx;
+
+        let codes = ctx.contracts.iter()
+            .enumerate()
+            .map(|(id, code)| CodeDump {
+                id: id as u64,
+                code_hash: code.code_hash.clone()
+            })
+            .collect();
+
+        let mut instances: Vec<InstanceDump> = ctx.state.instances.iter()
+            .map(|(address, instance)| InstanceDump {
+                address: address.clone(),
+                code_id: instance.index as u64,
+                label: instance.label.clone(),
+                creator: instance.creator.clone(),
+                storage: ctx.decoders.dump_storage(&instance.storage)
+            })
+            .collect();
+
+        instances.sort_by(|a, b| a.address.cmp(&b.address));
+
+        let balances = ctx.state.bank.0.iter()
+            .map(|(address, balances)| (
+                address.clone(),
+                balances.iter()
+                    .map(|(denom, amount)| (denom.clone(), *amount))
+                    .collect()
+            ))
+            .collect();
+
+        #[cfg(feature = "ensemble-staking")]
+        let delegations = {
+            let mut delegators: Vec<&String> = ctx.delegations.delegators().collect();
+            delegators.sort();
+
+            delegators.into_iter()
+                .flat_map(|x| ctx.delegations.all_delegations(x))
+                .collect()
+        };
+
+        StateDump {
+            chain_id: ctx.chain_id.clone(),
+            block: BlockDump {
+                height: ctx.block.height(),
+                time: ctx.block.time()
+            },
+            codes,
+            instances,
+            balances,
+            #[cfg(feature = "ensemble-staking")]
+            delegations
+        }
+    }
+
     /// Creates a new contract instance using the given code id. The code id
     /// must be obtained by calling the [`ContractEnsemble::register`] method first.
     /// 
     /// The contract will be assigned the address the was provided with
-    /// the `env.contract` parameter.
+    /// the `env.contract` parameter which is also used as its label.
+    /// Contracts instantiated by other contracts are instead assigned
+    /// an address derived from their code id and the number of instances
+    /// created so far.
     /// 
     /// The `instance` field of the response will contain this address and
     /// the code hash associated with this instance.
@@ -409,25 +667,93 @@ impl ContractEnsemble {
         msg: &T,
         env: MockEnv
     ) -> EnsembleResult<InstantiateResponse> {
+        let address = env.contract.to_string();
+
+        self.instantiate_at(code_id, msg, address.clone(), address, env)
+    }
+
+    /// Creates a new contract instance using the given code id at a predictable
+    /// address, derived from the code hash, the sender in `env.sender` and the
+    /// given `salt`. The address can be known in advance by calling
+    /// [`ContractEnsemble::instantiate2_address`].
+    /// 
+    /// The `env.contract` parameter is used as the label of the instance.
+    /// 
+    /// This is only available to tests. The version of `cosmwasm_std` that
+    /// Fadroma uses has no `WasmMsg::Instantiate2`, so contracts can't send it
+    /// and the ensemble only handles `WasmMsg::Instantiate`. The address is
+    /// derived from the same inputs as on chains that support it but
+    /// doesn't match the address that a real chain would produce.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, Binary, to_binary};
+    /// # use fadroma_ensemble::{ContractEnsemble, ContractHarness, MockEnv, AnyResult};
+    /// # struct Contract;
+    /// # impl ContractHarness for Contract {
+    /// #     fn instantiate(&self, _deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    /// #         Ok(Response::default())
+    /// #     }
+    /// #     fn execute(&self, _deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+    /// #         Ok(Response::default())
+    /// #     }
+    /// #     fn query(&self, _deps: Deps, _env: Env, _msg: Binary) -> AnyResult<Binary> {
+    /// #         Ok(to_binary(&())?)
+    /// #     }
+    /// # }
+    /// let mut ensemble = ContractEnsemble::new();
+    /// let code = ensemble.register(Box::new(Contract));
+    /// 
+    /// let expected = ensemble.instantiate2_address(code.id, "sender", b"salt").unwrap();
+    /// let instance = ensemble.instantiate2(
+    ///     code.id,
+    ///     &(),
+    ///     b"salt",
+    ///     MockEnv::new("sender", "my contract")
+    /// )
+    /// .unwrap()
+    /// .instance;
+    /// 
+    /// assert_eq!(instance.address, expected);
+    /// assert_eq!(ensemble.contract_by_label("my contract").unwrap().address, expected);
+    /// ```
+    pub fn instantiate2<T: Serialize>(
+        &mut self,
+        code_id: u64,
+        msg: &T,
+        salt: impl AsRef<[u8]>,
+        env: MockEnv
+    ) -> EnsembleResult<InstantiateResponse> {
+        let address = self.instantiate2_address(
+            code_id,
+            env.sender(),
+            salt
+        )?.into_string();
+        let label = env.contract.to_string();
+
+        self.instantiate_at(code_id, msg, address, label, env)
+    }
+
+    /// Returns the address that [`ContractEnsemble::instantiate2`] will
+    /// assign to an instance of `code_id` created by `creator` with `salt`.
+    pub fn instantiate2_address(
+        &self,
+        code_id: u64,
+        creator: impl AsRef<str>,
+        salt: impl AsRef<[u8]>
+    ) -> EnsembleResult<Addr> {
         let contract = self
             .ctx
             .contracts
             .get(code_id as usize)
             .ok_or_else(|| EnsembleError::registry(RegistryError::IdNotFound(code_id)))?;
 
-        let sub_msg = SubMsg::new(WasmMsg::Instantiate {
-            code_id,
-            code_hash: contract.code_hash.clone(),
-            msg: to_binary(msg)?,
-            funds: env.sent_funds,
-            label: env.contract.into_string(),
-            admin: None
-        });
-
-        match self.ctx.execute_messages(sub_msg, env.sender.into_string())? {
-            ResponseVariants::Instantiate(resp) => Ok(resp),
-            _ => unreachable!()
-        }
+        Ok(Addr::unchecked(predictable_address(
+            &contract.code_hash,
+            creator.as_ref(),
+            salt.as_ref()
+        )))
     }
 
     /// Executes the contract with the address provided in `env.contract`.
@@ -454,6 +780,34 @@ impl ContractEnsemble {
         }
     }
 
+    /// Returns the metadata of the contract instance with the given address.
+    pub fn contract_info(&self, address: impl AsRef<str>) -> EnsembleResult<ContractInstanceInfo> {
+        let address = address.as_ref();
+        let instance = self.ctx.state.instance(address)?;
+
+        Ok(self.ctx.instance_info(address, instance))
+    }
+
+    /// Returns the metadata of the contract instance that was created with the given label.
+    pub fn contract_by_label(&self, label: impl AsRef<str>) -> EnsembleResult<ContractInstanceInfo> {
+        let (address, instance) = self.ctx.state.instance_by_label(label.as_ref())?;
+
+        Ok(self.ctx.instance_info(address, instance))
+    }
+
+    /// Returns the metadata of all instances of the given code id, ordered by address.
+    pub fn contracts_by_code(&self, code_id: u64) -> Vec<ContractInstanceInfo> {
+        let mut result: Vec<ContractInstanceInfo> = self.ctx.state.instances
+            .iter()
+            .filter(|(_, instance)| instance.index as u64 == code_id)
+            .map(|(address, instance)| self.ctx.instance_info(address, instance))
+            .collect();
+
+        result.sort_by(|a, b| a.address.cmp(&b.address));
+
+        result
+    }
+
     /// Queries the contract associated with the given address and
     /// attempts to deserialize its response to the given type parameter.
     #[inline]
@@ -478,6 +832,37 @@ impl ContractEnsemble {
     ) -> EnsembleResult<Binary> {
         self.ctx.query(address.as_ref(), to_binary(msg)?)
     }
+
+    fn instantiate_at<T: Serialize>(
+        &mut self,
+        code_id: u64,
+        msg: &T,
+        address: String,
+        label: String,
+        env: MockEnv
+    ) -> EnsembleResult<InstantiateResponse> {
+        let contract = self
+            .ctx
+            .contracts
+            .get(code_id as usize)
+            .ok_or_else(|| EnsembleError::registry(RegistryError::IdNotFound(code_id)))?;
+
+        let sub_msg = SubMsg::new(WasmMsg::Instantiate {
+            code_id,
+            code_hash: contract.code_hash.clone(),
+            msg: to_binary(msg)?,
+            funds: env.sent_funds,
+            label,
+            admin: None
+        });
+
+        self.ctx.next_address = Some(address);
+
+        match self.ctx.execute_messages(sub_msg, env.sender.into_string())? {
+            ResponseVariants::Instantiate(resp) => Ok(resp),
+            _ => unreachable!()
+        }
+    }
 }
 
 impl Context {
@@ -487,7 +872,11 @@ impl Context {
             contracts: vec![],
             state: State::new(),
             block: Block::default(),
-            chain_id: "fadroma-ensemble-testnet".into()
+            chain_id: "fadroma-ensemble-testnet".into(),
+            next_address: None,
+            limits: ExecutionLimits::default(),
+            query_depth: Cell::new(0),
+            decoders: Decoders::default()
         }
     }
 
@@ -498,7 +887,11 @@ impl Context {
             state: State::new(),
             delegations: Delegations::new(native_denom),
             block: Block::default(),
-            chain_id: "fadroma-ensemble-testnet".into()
+            chain_id: "fadroma-ensemble-testnet".into(),
+            next_address: None,
+            limits: ExecutionLimits::default(),
+            query_depth: Cell::new(0),
+            decoders: Decoders::default()
         }
     }
 
@@ -507,6 +900,7 @@ impl Context {
         id: u64,
         msg: Binary,
         env: MockEnv,
+        label: String
     ) -> EnsembleResult<InstantiateResponse> {
         // We check for validity in execute_sub_msg()
         let contract = &self.contracts[id as usize];
@@ -515,7 +909,12 @@ impl Context {
         let address = env.contract.to_string();
         let code_hash = contract.code_hash.clone();
 
-        self.state.create_contract_instance(address.clone(), id as usize)?;
+        self.state.create_contract_instance(
+            address.clone(),
+            id as usize,
+            label.clone(),
+            sender.clone()
+        )?;
 
         let (env, msg_info) = self.create_msg_deps(
             env,
@@ -543,6 +942,7 @@ impl Context {
                 code_hash
             },
             code_id: id,
+            label,
             msg,
             response
         })
@@ -585,6 +985,20 @@ impl Context {
     }
 
     pub(crate) fn query(&self, address: &str, msg: Binary) -> EnsembleResult<Binary> {
+        let depth = self.query_depth.get() + 1;
+
+        if matches!(self.limits.max_query_depth, Some(max) if depth > max) {
+            return Err(EnsembleError::Execution(ExecutionError::QueryDepthExceeded(depth)));
+        }
+
+        self.query_depth.set(depth);
+        let result = self.query_contract(address, msg);
+        self.query_depth.set(depth - 1);
+
+        result
+    }
+
+    fn query_contract(&self, address: &str, msg: Binary) -> EnsembleResult<Binary> {
         let instance = self.state.instance(address)?;
         let contract = &self.contracts[instance.index];
 
@@ -646,14 +1060,22 @@ impl Context {
         msg: SubMsg,
         initial_sender: String
     ) -> EnsembleResult<ResponseVariants> {
-        let mut state = ExecutionState::new(msg, initial_sender);
+        let mut state = ExecutionState::new(
+            msg,
+            initial_sender,
+            self.limits.max_message_depth
+        );
 
         while let Some(msg_ty) = state.next() {
             self.state.push_scope();
 
             let result = match msg_ty {
                 MessageType::SubMsg { msg, sender } => {
-                    self.execute_sub_msg(msg, sender)
+                    if let Some(address) = self.reentrant_address(&msg, &state) {
+                        Err(EnsembleError::Execution(ExecutionError::Reentrancy(address)))
+                    } else {
+                        self.execute_sub_msg(msg, sender)
+                    }
                 }
                 MessageType::Reply { id, error, target } => {
                     let result = match error {
@@ -704,6 +1126,12 @@ impl Context {
             }
         }
 
+        if let Err(err) = self.record_storage_growth() {
+            self.state.revert();
+
+            return Err(err);
+        }
+
         self.block.next();
         self.state.commit();
 
@@ -759,6 +1187,8 @@ impl Context {
                     code_hash,
                     ..
                 } => {
+                    let address = self.next_address.take();
+
                     let contract = self
                         .contracts
                         .get(code_id as usize)
@@ -768,9 +1198,13 @@ impl Context {
                         return Err(EnsembleError::registry(RegistryError::InvalidCodeHash(code_hash)));
                     }
 
-                    let env = MockEnv::new_sanitized(
+                    let address = address.unwrap_or_else(||
+                        instance_address(code_id, self.state.next_instance_id())
+                    );
+
+                    let env = MockEnv::new(
                         sender,
-                        label
+                        address
                     ).sent_funds(funds);
 
                     let mut events = if env.sent_funds.is_empty() {
@@ -788,7 +1222,8 @@ impl Context {
                     let instantiate_resp = self.instantiate(
                         code_id,
                         msg,
-                        env
+                        env,
+                        label
                     )?;
 
                     events.extend(&instantiate_resp)?;
@@ -880,6 +1315,58 @@ impl Context {
         }
     }
 
+    fn record_storage_growth(&mut self) -> EnsembleResult<()> {
+        let growth = self.state.storage_growth(self.block.height());
+
+        if let Some(quota) = self.limits.storage_quota {
+            for (address, growth) in &growth {
+                if growth.bytes <= 0 {
+                    continue;
+                }
+
+                let size = self.state.instance(address)?.storage.stats().total_bytes();
+
+                if size > quota {
+                    return Err(EnsembleError::Execution(ExecutionError::StorageQuotaExceeded {
+                        address: address.clone(),
+                        size,
+                        quota
+                    }));
+                }
+            }
+        }
+
+        for (address, growth) in growth {
+            if let Some(instance) = self.state.instances.get_mut(&address) {
+                instance.growth.push(growth);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reentrant_address(&self, msg: &SubMsg, state: &ExecutionState) -> Option<String> {
+        if !self.limits.detect_reentrancy {
+            return None;
+        }
+
+        match &msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. })
+                if state.is_executing(contract_addr) => Some(contract_addr.clone()),
+            _ => None
+        }
+    }
+
+    fn instance_info(&self, address: &str, instance: &ContractInstance) -> ContractInstanceInfo {
+        ContractInstanceInfo {
+            address: Addr::unchecked(address),
+            code_id: instance.index as u64,
+            code_hash: self.contracts[instance.index].code_hash.clone(),
+            label: instance.label.clone(),
+            creator: Addr::unchecked(instance.creator.clone())
+        }
+    }
+
     #[inline]
     fn create_msg_deps(&self, env: MockEnv, code_hash: String) -> (Env, MessageInfo) {
         (
@@ -896,15 +1383,15 @@ impl Context {
 
     #[inline]
     fn create_env(&self, contract: ContractLink<Addr>) -> Env {
-        let seed = 94759574359011638572u128.wrapping_mul(self.block.height as u128);
+        let seed = 94759574359011638572u128.wrapping_mul(self.block.height() as u128);
         
         let mut rng = Rand64::new(seed);
         let bytes = rng.rand_u64().to_le_bytes();
 
         Env {
             block: BlockInfo {
-                height: self.block.height,
-                time: Timestamp::from_seconds(self.block.time),
+                height: self.block.height(),
+                time: self.block.time(),
                 chain_id: self.chain_id.clone(),
                 random: Some(Binary::from(bytes))
             },
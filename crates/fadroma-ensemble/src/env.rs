@@ -72,23 +72,6 @@ impl MockEnv {
     pub fn contract(&self) -> &str {
         self.contract.as_str()
     }
-
-    pub(crate) fn new_sanitized(
-        sender: impl Into<String>,
-        contract: impl Into<String>
-    ) -> Self {
-        let sender = sender.into();
-        assert!(is_valid_address(&sender));
-
-        let mut contract = contract.into();
-        contract.truncate(Self::MAX_ADDRESS_LEN);
-
-        Self {
-            sender: Addr::unchecked(sender),
-            contract: Addr::unchecked(contract.to_lowercase()),
-            sent_funds: vec![]
-        }
-    }
 }
 
 #[inline]
@@ -128,19 +111,6 @@ mod tests {
         assert!(is_valid_address(human.as_str()));
     }
 
-    #[test]
-    fn mock_env_respects_mock_api() {
-        let addr = "A".repeat(MockEnv::MAX_ADDRESS_LEN + 1);
-        assert!(!is_valid_address(&addr));
-
-        let env = MockEnv::new_sanitized("sender", addr);
-        assert_eq!(env.sender.as_str(), "sender");
-        assert_eq!(env.contract.as_str(), "a".repeat(MockEnv::MAX_ADDRESS_LEN));
-
-        assert!(is_valid_address(env.sender.as_str()));
-        assert!(is_valid_address(env.contract.as_str()));
-    }
-
     #[test]
     fn addresses_can_contain_spaces_and_special_characters() {
         MockEnv::new("`~123!@#$%^&*()-=+\\/.,<>?[]{}", "this address has spaces");
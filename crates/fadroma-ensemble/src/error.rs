@@ -6,6 +6,7 @@ use fadroma::cosmwasm_std::StdError;
 pub enum EnsembleError {
     ContractError(anyhow::Error),
     ContractRegistry(RegistryError),
+    Execution(ExecutionError),
     AttributeValidation(String),
     Bank(String),
     Staking(String),
@@ -17,9 +18,23 @@ pub enum RegistryError {
     NotFound(String),
     IdNotFound(u64),
     DuplicateAddress(String),
+    DuplicateLabel(String),
+    LabelNotFound(String),
     InvalidCodeHash(String),
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExecutionError {
+    QueryDepthExceeded(usize),
+    MessageDepthExceeded(usize),
+    Reentrancy(String),
+    StorageQuotaExceeded {
+        address: String,
+        size: usize,
+        quota: usize
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum AttributeError {
     EventTypeTooShort(String),
@@ -70,6 +85,7 @@ impl Display for EnsembleError {
             Self::Bank(msg) => f.write_fmt(format_args!("Ensemble error - Bank: {}", msg)),
             Self::Staking(msg) => f.write_fmt(format_args!("Ensemble error - Staking: {}", msg)),
             Self::ContractRegistry(err) => f.write_fmt(format_args!("Ensemble error - Contract registry: {}", err.to_string())),
+            Self::Execution(err) => f.write_fmt(format_args!("Ensemble error - Execution: {}", err)),
             Self::AttributeValidation(msg) => f.write_fmt(format_args!("Ensemble error - Event attribute validation: {}", msg)),
             Self::Std(err) => Display::fmt(err, f),
             Self::ContractError(err) => Display::fmt(err, f)
@@ -77,11 +93,24 @@ impl Display for EnsembleError {
     }
 }
 
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::QueryDepthExceeded(depth) => f.write_fmt(format_args!("Query depth of {} exceeds the maximum allowed", depth)),
+            Self::MessageDepthExceeded(depth) => f.write_fmt(format_args!("Sub-message depth of {} exceeds the maximum allowed", depth)),
+            Self::Reentrancy(address) => f.write_fmt(format_args!("Contract {} was called again before its previous execution finished", address)),
+            Self::StorageQuotaExceeded { address, size, quota } => f.write_fmt(format_args!("Contract {} uses {} bytes of storage which exceeds the quota of {} bytes", address, size, quota)),
+        }
+    }
+}
+
 impl Display for RegistryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::NotFound(address) => f.write_fmt(format_args!("Contract address {} not found", address)),
             Self::DuplicateAddress(address) => f.write_fmt(format_args!("Contract instance with address {} already exists", address)),
+            Self::DuplicateLabel(label) => f.write_fmt(format_args!("Contract instance with label {} already exists", label)),
+            Self::LabelNotFound(label) => f.write_fmt(format_args!("Contract with label {} not found", label)),
             Self::IdNotFound(id) => f.write_fmt(format_args!("Contract with id {} not found", id)),
             Self::InvalidCodeHash(hash) => f.write_fmt(format_args!("Contract code hash {} is invalid", hash)),
         }
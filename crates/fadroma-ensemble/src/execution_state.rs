@@ -2,12 +2,36 @@ use fadroma::{
     cosmwasm_std::{SubMsg, ReplyOn, Event, Binary},
 };
 use crate::{
-    ResponseVariants, EnsembleResult, SubMsgExecuteResult
+    ResponseVariants, EnsembleResult, EnsembleError,
+    SubMsgExecuteResult, ExecutionError
 };
 
+/// The maximum depth of nested queries, same as the default in `wasmd`.
+pub const DEFAULT_MAX_QUERY_DEPTH: usize = 10;
+
 pub struct ExecutionState {
     states: Vec<ExecutionLevel>,
-    next: Option<MessageType>
+    next: Option<MessageType>,
+    max_depth: Option<usize>
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ExecutionLimits {
+    pub max_query_depth: Option<usize>,
+    pub max_message_depth: Option<usize>,
+    pub detect_reentrancy: bool,
+    pub storage_quota: Option<usize>
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_query_depth: Some(DEFAULT_MAX_QUERY_DEPTH),
+            max_message_depth: None,
+            detect_reentrancy: false,
+            storage_quota: None
+        }
+    }
 }
 
 pub enum MessageType {
@@ -45,7 +69,7 @@ enum SubMsgState {
 
 impl ExecutionState {
     #[inline]
-    pub fn new(initial: SubMsg, sender: String) -> Self {
+    pub fn new(initial: SubMsg, sender: String, max_depth: Option<usize>) -> Self {
         assert_eq!(initial.reply_on, ReplyOn::Never);
 
         let mut level = ExecutionLevel::new(vec![initial.clone()]);
@@ -56,7 +80,8 @@ impl ExecutionState {
             next: Some(MessageType::SubMsg {
                 msg: initial,
                 sender
-            })
+            }),
+            max_depth
         }
     }
 
@@ -84,6 +109,15 @@ impl ExecutionState {
                 level.responses.push(response);
         
                 if messages.len() > 0 {
+                    // The initial message is at depth 0.
+                    let depth = self.states.len();
+
+                    if matches!(self.max_depth, Some(max) if depth > max) {
+                        return Err(EnsembleError::Execution(
+                            ExecutionError::MessageDepthExceeded(depth)
+                        ));
+                    }
+
                     self.states.push(ExecutionLevel::new(messages));
                 }
 
@@ -128,6 +162,17 @@ impl ExecutionState {
         self.current_level_mut().data.as_ref()
     }
 
+    /// Returns `true` if the given contract is currently waiting for
+    /// its sub-messages to finish executing i.e if executing it again
+    /// at this point would be reentrant.
+    pub fn is_executing(&self, address: &str) -> bool {
+        let callers = &self.states[..self.states.len() - 1];
+
+        callers.iter().any(|level|
+            contract_address(level.responses.last().unwrap()) == address
+        )
+    }
+
     pub fn finalize(mut self) -> ResponseVariants {
         assert!(self.states.len() == 1 && self.next.is_none());
         assert_eq!(self.states[0].responses.len(), 1);
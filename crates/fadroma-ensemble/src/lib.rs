@@ -1,6 +1,7 @@
 //! Test multiple contract interactions using unit tests.
 //! *Feature flag: `ensemble`*
 
+mod address;
 mod bank;
 mod ensemble;
 mod env;
@@ -13,15 +14,20 @@ mod staking;
 mod state;
 mod execution_state;
 mod error;
+mod dump;
 mod event;
 
 #[cfg(test)]
 mod tests;
 
 pub use ensemble::*;
+pub use address::CONTRACT_ADDRESS_PREFIX;
 pub use env::*;
 pub use querier::*;
-pub use block::Block;
+pub use block::{Block, BlockSnapshot};
+pub use storage::{StorageStats, StorageBreakdown, StorageGrowth};
+pub use dump::{StateDump, BlockDump, CodeDump, InstanceDump, NamespaceDump, EntryDump};
+pub use execution_state::DEFAULT_MAX_QUERY_DEPTH;
 pub use response::*;
 pub use error::*;
 pub use anyhow;
@@ -128,8 +128,7 @@ impl Querier for EnsembleQuerier {
                         .delegations
                         .validators()
                         .iter()
-                        .filter(|validator| validator.address == address)
-                        .next()
+                        .find(|validator| validator.address == address)
                         .cloned();
 
                     querier_result!(to_binary(&ValidatorResponse { validator }))
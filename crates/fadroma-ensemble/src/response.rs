@@ -26,6 +26,8 @@ pub struct InstantiateResponse {
     pub instance: ContractLink<Addr>,
     /// Code ID of the instantiated contract.
     pub code_id: u64,
+    /// The label that the instance was created with.
+    pub label: String,
     /// The init message that was sent.
     pub msg: Binary,
     /// The init response returned by the contract.
@@ -536,6 +538,7 @@ mod tests {
                 code_hash: String::new()
             },
             code_id: 0,
+            label: String::new(),
             msg: Binary::from(format!("message_{}", index).as_bytes()),
             response: Response::default(),
             sent: vec![]
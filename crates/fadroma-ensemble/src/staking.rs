@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::prelude::*;
+use fadroma::cosmwasm_std::*;
 use super::{
     EnsembleResult, EnsembleError,
     response::{
@@ -31,24 +31,24 @@ pub(crate) struct Delegations {
     delegators: HashMap<String, Delegator>,
 }
 
-impl Into<Delegation> for DelegationWithUnbonding {
-    fn into(self) -> Delegation {
+impl From<DelegationWithUnbonding> for Delegation {
+    fn from(delegation: DelegationWithUnbonding) -> Self {
         Delegation {
-            delegator: Addr::unchecked(self.delegator),
-            validator: self.validator,
-            amount: self.amount,
+            delegator: Addr::unchecked(delegation.delegator),
+            validator: delegation.validator,
+            amount: delegation.amount,
         }
     }
 }
 
-impl Into<FullDelegation> for DelegationWithUnbonding {
-    fn into(self) -> FullDelegation {
+impl From<DelegationWithUnbonding> for FullDelegation {
+    fn from(delegation: DelegationWithUnbonding) -> Self {
         FullDelegation {
-            delegator: Addr::unchecked(self.delegator),
-            validator: self.validator,
-            amount: self.amount,
-            can_redelegate: self.can_redelegate,
-            accumulated_rewards: vec![self.accumulated_rewards],
+            delegator: Addr::unchecked(delegation.delegator),
+            validator: delegation.validator,
+            amount: delegation.amount,
+            can_redelegate: delegation.can_redelegate,
+            accumulated_rewards: vec![delegation.accumulated_rewards],
         }
     }
 }
@@ -102,6 +102,10 @@ impl Delegations {
         &self.bonded_denom
     }
 
+    pub fn delegators(&self) -> impl Iterator<Item = &String> {
+        self.delegators.keys()
+    }
+
     pub fn all_delegations(&self, delegator: &str) -> Vec<Delegation> {
         match self.delegators.get(delegator) {
             Some(delegations) => {
@@ -120,10 +124,7 @@ impl Delegations {
         delegator: &str, 
         validator: &str
     ) -> Option<FullDelegation> {
-        match self.get_delegation(delegator, validator) {
-            Some(delegation) => Some(delegation.into()),
-            None => None,
-        }
+        self.get_delegation(delegator, validator).map(|delegation| delegation.into())
     }
 
     pub fn validators(&self) -> &[Validator] {
@@ -160,26 +161,20 @@ impl Delegations {
         };
        
         // Check if delegation pair exists, add amounts if so
-        match self.delegators.get_mut(&delegator) {
-            Some(cur_delegator) => {
-                match cur_delegator.get(&validator) {
-                    Some(ref old_deleg) => {
-                        let old_delegation = (*old_deleg).clone();
-                        new_delegation.amount = Coin {
-                            denom: self.bonded_denom.clone(),
-                            amount: old_delegation.amount.amount + amount.amount,
-                        };
-                        new_delegation.unbonding_amount = old_delegation.unbonding_amount;
-                        new_delegation.can_redelegate = Coin {
-                            denom: self.bonded_denom.clone(),
-                            amount: old_delegation.can_redelegate.amount + amount.amount,
-                        };
-                        new_delegation.accumulated_rewards = old_delegation.accumulated_rewards;
-                    },
-                    None => { }
-                }
-            },
-            None => { }
+        if let Some(cur_delegator) = self.delegators.get_mut(&delegator) {
+            if let Some(old_deleg) = cur_delegator.get(&validator) {
+                let old_delegation = old_deleg.clone();
+                new_delegation.amount = Coin {
+                    denom: self.bonded_denom.clone(),
+                    amount: old_delegation.amount.amount + amount.amount,
+                };
+                new_delegation.unbonding_amount = old_delegation.unbonding_amount;
+                new_delegation.can_redelegate = Coin {
+                    denom: self.bonded_denom.clone(),
+                    amount: old_delegation.can_redelegate.amount + amount.amount,
+                };
+                new_delegation.accumulated_rewards = old_delegation.accumulated_rewards;
+            }
         };
 
         self.insert_delegation(delegator.clone(), validator.clone(), new_delegation);
@@ -369,10 +364,7 @@ impl Delegations {
     ) -> Option<DelegationWithUnbonding> {
         match self.delegators.get(delegator) {
             Some(cur_delegator) => {
-                match cur_delegator.get(validator).clone() {
-                    Some(ref delegation) => Some((*delegation).clone()),
-                    _ => None,
-                }
+                cur_delegator.get(validator).cloned()
             },
             _ => None,
         }
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap};
 
 use fadroma::cosmwasm_std::{Coin, Storage};
 
 use super::{
     EnsembleResult,
-    storage::TestStorage,
+    storage::{TestStorage, StorageGrowth},
     bank::Bank,
     response::BankResponse,
     error::{EnsembleError, RegistryError}
@@ -13,6 +13,8 @@ use super::{
 #[derive(Default, Debug)]
 pub(crate) struct State {
     pub instances: HashMap<String, ContractInstance>,
+    /// Maps instance labels to their contract address.
+    pub labels: HashMap<String, String>,
     pub bank: Bank,
     scopes: Vec<Scope>
 }
@@ -20,7 +22,10 @@ pub(crate) struct State {
 #[derive(Debug)]
 pub(crate) struct ContractInstance {
     pub storage: TestStorage,
-    pub index: usize
+    pub index: usize,
+    pub label: String,
+    pub creator: String,
+    pub growth: Vec<StorageGrowth>
 }
 
 #[derive(Clone, Debug)]
@@ -57,6 +62,7 @@ impl State {
     pub fn new() -> Self {
         Self {
             instances: HashMap::new(),
+            labels: HashMap::new(),
             bank: Bank::default(),
             scopes: vec![]
         }
@@ -65,19 +71,33 @@ impl State {
     pub fn create_contract_instance(
         &mut self,
         address: impl Into<String>,
-        index: usize
+        index: usize,
+        label: impl Into<String>,
+        creator: impl Into<String>
     ) -> EnsembleResult<()> {
         assert!(self.scopes.len() > 0);
         let address = address.into();
+        let label = label.into();
 
         if self.instances.contains_key(&address) {
             return Err(EnsembleError::registry(RegistryError::DuplicateAddress(address)));
         }
 
+        if self.labels.contains_key(&label) {
+            return Err(EnsembleError::registry(RegistryError::DuplicateLabel(label)));
+        }
+
         let storage = TestStorage::new(address.clone());
+        self.labels.insert(label.clone(), address.clone());
         self.instances.insert(
             address.clone(),
-            ContractInstance { index, storage }
+            ContractInstance {
+                index,
+                storage,
+                label,
+                creator: creator.into(),
+                growth: vec![]
+            }
         );
 
         let scope = self.current_scope_mut();
@@ -94,6 +114,20 @@ impl State {
         }
     }
 
+    #[inline]
+    pub fn instance_by_label(&self, label: &str) -> EnsembleResult<(&str, &ContractInstance)> {
+        match self.labels.get(label) {
+            Some(address) => Ok((address.as_str(), self.instance(address)?)),
+            None => Err(EnsembleError::registry(RegistryError::LabelNotFound(label.to_string())))
+        }
+    }
+
+    /// The sequence number that the next created instance will have.
+    #[inline]
+    pub fn next_instance_id(&self) -> u64 {
+        self.instances.len() as u64 + 1
+    }
+
     pub fn borrow_storage_mut<F, T>(&mut self, address: &str, borrow: F) -> EnsembleResult<T>
         where F: FnOnce(&mut dyn Storage) -> EnsembleResult<T>
     {
@@ -109,6 +143,38 @@ impl State {
         }
     }
 
+    /// Computes the net change in the storage of each contract
+    /// that was written to in the current uncommitted scopes.
+    pub fn storage_growth(&self, height: u64) -> BTreeMap<String, StorageGrowth> {
+        // The first write to a key holds its value prior to the scopes.
+        let mut original: BTreeMap<(&str, &[u8]), Option<&[u8]>> = BTreeMap::new();
+
+        for op in self.scopes.iter().flat_map(|x| x.0.iter()) {
+            if let Op::StorageWrite { address, key, old } = op {
+                original.entry((address, key)).or_insert(old.as_deref());
+            }
+        }
+
+        let mut result: BTreeMap<String, StorageGrowth> = BTreeMap::new();
+
+        for ((address, key), old) in original {
+            let Some(instance) = self.instances.get(address) else {
+                continue;
+            };
+
+            let new = instance.storage.backing.get(key);
+            let growth = result.entry(address.to_string())
+                .or_insert(StorageGrowth { height, keys: 0, bytes: 0 });
+
+            let size = |value: Option<&[u8]>| value.map_or(0, |x| (key.len() + x.len()) as i64);
+
+            growth.keys += new.is_some() as i64 - old.is_some() as i64;
+            growth.bytes += size(new.map(|x| x.as_slice())) - size(old);
+        }
+
+        result
+    }
+
     #[inline]
     pub fn commit(&mut self) {
         self.scopes.clear();
@@ -134,7 +200,9 @@ impl State {
         for op in scope.0 {
             match op {
                 Op::CreateInstance { address } => {
-                    self.instances.remove(&address);
+                    if let Some(instance) = self.instances.remove(&address) {
+                        self.labels.remove(&instance.label);
+                    }
                 }
                 Op::StorageWrite { address, key, old } => {
                     if let Some(instance) = self.instances.get_mut(&address) {
@@ -426,6 +494,56 @@ mod tests {
         assert_eq!(check_balance(&state, CONTRACTS[0]), 100);
     }
 
+    #[test]
+    fn labels_are_unique_and_reverted_with_instances() {
+        let mut state = setup_storage();
+
+        state.push_scope();
+        let err = state.create_contract_instance("D", 0, CONTRACTS[0], "creator").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Ensemble error - Contract registry: Contract instance with label A already exists"
+        );
+
+        state.create_contract_instance("D", 0, "label", "creator").unwrap();
+        let (address, instance) = state.instance_by_label("label").unwrap();
+        assert_eq!(address, "D");
+        assert_eq!(instance.creator, "creator");
+
+        state.revert();
+
+        assert!(state.instance("D").is_err());
+        assert!(state.instance_by_label("label").is_err());
+        assert_eq!(state.instance_by_label(CONTRACTS[0]).unwrap().0, CONTRACTS[0]);
+    }
+
+    #[test]
+    fn storage_growth_is_relative_to_committed_state() {
+        let mut state = setup_storage();
+
+        state.push_scope();
+        let store = storage_mut(&mut state, CONTRACTS[0]);
+        store.set(b"a", b"a");
+        store.set(b"a", b"abcde");
+        store.set(b"b", b"b");
+        store.set(b"c", b"c");
+        store.remove(b"c");
+
+        let ops = store.ops();
+        state.push_ops(ops);
+
+        let growth = state.storage_growth(5);
+        assert_eq!(growth.len(), 1);
+        assert_eq!(growth[CONTRACTS[0]], StorageGrowth {
+            height: 5,
+            keys: 1,
+            bytes: 4
+        });
+
+        state.revert();
+        assert!(state.storage_growth(5).is_empty());
+    }
+
     fn check_balance(state: &State, address: &str) -> u128 {
         let mut balances = state.bank.query_balances(address, Some("uscrt".into()));
         assert_eq!(balances.len(), 1);
@@ -442,9 +560,9 @@ mod tests {
 
         state.push_scope();
 
-        state.create_contract_instance(CONTRACTS[0], 0).unwrap();
-        state.create_contract_instance(CONTRACTS[1], 1).unwrap();
-        state.create_contract_instance(CONTRACTS[2], 2).unwrap();
+        state.create_contract_instance(CONTRACTS[0], 0, CONTRACTS[0], "creator").unwrap();
+        state.create_contract_instance(CONTRACTS[1], 1, CONTRACTS[1], "creator").unwrap();
+        state.create_contract_instance(CONTRACTS[2], 2, CONTRACTS[2], "creator").unwrap();
 
         state.commit();
 
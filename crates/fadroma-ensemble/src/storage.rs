@@ -11,6 +11,53 @@ use fadroma::cosmwasm_std::{
 
 use super::state::Op;
 
+/// Size statistics over a set of storage entries.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct StorageStats {
+    /// The number of keys.
+    pub keys: usize,
+    /// Total size of all keys in bytes.
+    pub key_bytes: usize,
+    /// Total size of all values in bytes.
+    pub value_bytes: usize
+}
+
+/// Storage statistics grouped by namespace prefix.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct StorageBreakdown {
+    /// Statistics for each of the requested namespaces. Entries that
+    /// match multiple namespaces count towards the longest one.
+    pub namespaces: BTreeMap<Vec<u8>, StorageStats>,
+    /// Statistics for the entries that didn't match any of the namespaces.
+    pub other: StorageStats
+}
+
+/// How much the storage of a contract changed during a single transaction.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct StorageGrowth {
+    /// The block height at which the transaction was executed.
+    pub height: u64,
+    /// The difference in the number of keys.
+    pub keys: i64,
+    /// The difference in the total size of keys and values in bytes.
+    pub bytes: i64
+}
+
+impl StorageStats {
+    /// The total size of all keys and values in bytes.
+    #[inline]
+    pub fn total_bytes(&self) -> usize {
+        self.key_bytes + self.value_bytes
+    }
+
+    #[inline]
+    fn add(&mut self, key: &[u8], value: &[u8]) {
+        self.keys += 1;
+        self.key_bytes += key.len();
+        self.value_bytes += value.len();
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TestStorage {
     pub backing: BTreeMap<Vec<u8>, Vec<u8>>,
@@ -31,6 +78,38 @@ impl TestStorage {
     pub fn ops(&mut self) -> Vec<Op> {
         mem::take(&mut self.ops)
     }
+
+    pub fn stats(&self) -> StorageStats {
+        let mut stats = StorageStats::default();
+
+        for (key, value) in &self.backing {
+            stats.add(key, value);
+        }
+
+        stats
+    }
+
+    pub fn breakdown(&self, namespaces: &[&[u8]]) -> StorageBreakdown {
+        let mut result = StorageBreakdown {
+            namespaces: namespaces.iter()
+                .map(|ns| (ns.to_vec(), StorageStats::default()))
+                .collect(),
+            other: StorageStats::default()
+        };
+
+        for (key, value) in &self.backing {
+            let ns = namespaces.iter()
+                .filter(|ns| key.starts_with(ns))
+                .max_by_key(|ns| ns.len());
+
+            match ns {
+                Some(ns) => result.namespaces.get_mut(*ns).unwrap().add(key, value),
+                None => result.other.add(key, value)
+            }
+        }
+
+        result
+    }
 }
 
 impl Storage for TestStorage {
@@ -95,3 +174,43 @@ fn clone_item<T: Clone>(item_ref: BTreeMapPairRef<T>) -> Record<T> {
     let (key, value) = item_ref;
     (key.clone(), value.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_and_breakdown() {
+        let mut storage = TestStorage::new("contract");
+        storage.set(b"ns_a", b"1");
+        storage.set(b"ns_ab", b"22");
+        storage.set(b"ns_b", b"333");
+        storage.set(b"x", b"4444");
+
+        assert_eq!(storage.stats(), StorageStats {
+            keys: 4,
+            key_bytes: 14,
+            value_bytes: 10
+        });
+        assert_eq!(storage.stats().total_bytes(), 24);
+
+        let breakdown = storage.breakdown(&[b"ns_", b"ns_a", b"unused"]);
+
+        assert_eq!(breakdown.namespaces[b"ns_".as_slice()], StorageStats {
+            keys: 1,
+            key_bytes: 4,
+            value_bytes: 3
+        });
+        assert_eq!(breakdown.namespaces[b"ns_a".as_slice()], StorageStats {
+            keys: 2,
+            key_bytes: 9,
+            value_bytes: 3
+        });
+        assert_eq!(breakdown.namespaces[b"unused".as_slice()], StorageStats::default());
+        assert_eq!(breakdown.other, StorageStats {
+            keys: 1,
+            key_bytes: 1,
+            value_bytes: 4
+        });
+    }
+}
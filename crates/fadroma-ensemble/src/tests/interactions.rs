@@ -4,7 +4,7 @@ use anyhow::{Result as AnyResult, bail};
 use crate::{
     ContractEnsemble, ContractHarness,
     MockEnv, EnsembleResult, EnsembleError,
-    ResponseVariants
+    ResponseVariants, BlockSnapshot, CONTRACT_ADDRESS_PREFIX
 };
 use fadroma::prelude::*;
 
@@ -275,7 +275,12 @@ fn init(
     };
 
     let multiplier = multiplier_init.instance.clone();
-    assert_eq!(multiplier.address.as_ref(), "a".repeat(MockEnv::MAX_ADDRESS_LEN));
+    let info = ensemble.contract_by_label("A".repeat(MockEnv::MAX_ADDRESS_LEN + 1))?;
+
+    assert_eq!(info.address, multiplier.address);
+    assert_eq!(info.code_id, multiplier_init.code_id);
+    assert_eq!(info.creator.as_str(), "counter");
+    assert!(multiplier.address.as_str().starts_with(CONTRACT_ADDRESS_PREFIX));
 
     Ok(InitResult {
         counter: msg.instance,
@@ -518,8 +523,10 @@ fn exact_increment() {
 
     let mut ensemble = ContractEnsemble::new();
     ensemble.block_mut().exact_increments(10, 7);
-    ensemble.block_mut().height = 0;
-    ensemble.block_mut().time = 0;
+    ensemble.block_mut().rewind(BlockSnapshot {
+        height: 0,
+        time: Timestamp::from_seconds(0)
+    });
 
     let block_height_contract = ensemble.register(Box::new(BlockHeight));
 
@@ -632,8 +639,8 @@ fn block_freeze() {
 
     let mut ensemble = ContractEnsemble::new();
 
-    let old_height = ensemble.block().height;
-    let old_time = ensemble.block().time;
+    let old_height = ensemble.block().height();
+    let old_time = ensemble.block().time().seconds();
 
     ensemble.block_mut().freeze();
 
@@ -684,6 +691,59 @@ fn block_freeze() {
     assert!(res.time > old_time);
 }
 
+#[test]
+fn block_time_travel() {
+    let admin = "admin";
+
+    let mut ensemble = ContractEnsemble::new();
+    ensemble.block_mut().freeze();
+
+    let block_height_contract = ensemble.register(Box::new(BlockHeight));
+    let block_height = ensemble
+        .instantiate(
+            block_height_contract.id,
+            &Empty {},
+            MockEnv::new(admin, "block_height")
+        )
+        .unwrap()
+        .instance;
+
+    let set_and_query = |ensemble: &mut ContractEnsemble| -> Block {
+        ensemble
+            .execute(
+                &BlockHeightHandle::Set,
+                MockEnv::new(admin, block_height.address.clone()),
+            )
+            .unwrap();
+
+        ensemble.query(&block_height.address, &Empty {}).unwrap()
+    };
+
+    let start = ensemble.block().snapshot();
+
+    ensemble.block_mut().jump_to_height(start.height + 10);
+    assert_eq!(
+        set_and_query(&mut ensemble),
+        Block {
+            height: start.height + 10,
+            time: start.time.seconds() + 100
+        }
+    );
+
+    let expiry = start.time.plus_seconds(60 * 60 * 24);
+    ensemble.block_mut().jump_to_time(expiry);
+    assert_eq!(set_and_query(&mut ensemble).time, expiry.seconds());
+
+    ensemble.block_mut().rewind(start);
+    assert_eq!(
+        set_and_query(&mut ensemble),
+        Block {
+            height: start.height,
+            time: start.time.seconds()
+        }
+    );
+}
+
 #[test]
 fn remove_funds() {
     let mut ensemble = ContractEnsemble::new();
@@ -747,3 +807,58 @@ fn remove_funds() {
         _ => panic!("No error message"),
     };
 }
+
+#[test]
+fn contract_metadata_and_instantiate2() {
+    let mut ensemble = ContractEnsemble::new();
+    let result = init(&mut ensemble, false, false).unwrap();
+
+    let counter = ensemble.contract_info(&result.counter.address).unwrap();
+    assert_eq!(counter.label, "counter");
+    assert_eq!(counter.creator.as_str(), "admin");
+    assert_eq!(counter.code_hash, result.counter.code_hash);
+
+    let multiplier = ensemble.contract_info(&result.multiplier.address).unwrap();
+    assert_eq!(ensemble.contracts_by_code(multiplier.code_id), vec![multiplier.clone()]);
+
+    let salt = b"salt";
+    let expected = ensemble.instantiate2_address(multiplier.code_id, "admin", salt).unwrap();
+
+    let resp = ensemble.instantiate2(
+        multiplier.code_id,
+        &MultiplierInit { fail: false },
+        salt,
+        MockEnv::new("admin", "predictable")
+    ).unwrap();
+
+    assert_eq!(resp.instance.address, expected);
+    assert_eq!(resp.label, "predictable");
+    assert_eq!(ensemble.contracts_by_code(multiplier.code_id).len(), 2);
+
+    // Same code, creator and salt results in the same address.
+    let err = ensemble.instantiate2(
+        multiplier.code_id,
+        &MultiplierInit { fail: false },
+        salt,
+        MockEnv::new("admin", "predictable2")
+    ).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!("Ensemble error - Contract registry: Contract instance with address {} already exists", expected)
+    );
+
+    let err = ensemble.instantiate2(
+        multiplier.code_id,
+        &MultiplierInit { fail: false },
+        b"other salt",
+        MockEnv::new("admin", "predictable")
+    ).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Ensemble error - Contract registry: Contract instance with label predictable already exists"
+    );
+
+    assert!(ensemble.contract_by_label("absent").is_err());
+}
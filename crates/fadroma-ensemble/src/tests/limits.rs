@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ContractEnsemble, ContractHarness, MockEnv, AnyResult,
+    EnsembleError, ExecutionError, StorageGrowth,
+    DEFAULT_MAX_QUERY_DEPTH
+};
+use fadroma::prelude::*;
+
+const SENDER: &str = "sender";
+const CODE_HASH: &str = "test_contract_0";
+
+struct Recursive;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ExecuteMsg {
+    /// Executes the first contract in `path` passing it the rest.
+    Call { path: Vec<String> }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QueryMsg {
+    /// Queries itself `remaining` more times.
+    Depth { remaining: u32 }
+}
+
+impl ContractHarness for Recursive {
+    fn instantiate(&self, _deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Binary) -> AnyResult<Response> {
+        Ok(Response::default())
+    }
+
+    fn execute(&self, deps: DepsMut, _env: Env, _info: MessageInfo, msg: Binary) -> AnyResult<Response> {
+        let ExecuteMsg::Call { mut path } = from_binary(&msg)?;
+
+        let count: u32 = storage::load(deps.storage, b"count")?.unwrap_or_default();
+        storage::save(deps.storage, b"count", &(count + 1))?;
+
+        if path.is_empty() {
+            return Ok(Response::default());
+        }
+
+        let next = path.remove(0);
+
+        Ok(Response::default().add_message(WasmMsg::Execute {
+            contract_addr: next,
+            code_hash: CODE_HASH.into(),
+            msg: to_binary(&ExecuteMsg::Call { path })?,
+            funds: vec![]
+        }))
+    }
+
+    fn query(&self, deps: Deps, env: Env, msg: Binary) -> AnyResult<Binary> {
+        let QueryMsg::Depth { remaining } = from_binary(&msg)?;
+
+        if remaining == 0 {
+            return Ok(to_binary(&0u32)?);
+        }
+
+        let depth: u32 = deps.querier.query_wasm_smart(
+            CODE_HASH,
+            env.contract.address,
+            &QueryMsg::Depth { remaining: remaining - 1 }
+        )?;
+
+        Ok(to_binary(&(depth + 1))?)
+    }
+}
+
+fn init(addresses: &[&str]) -> ContractEnsemble {
+    let mut ensemble = ContractEnsemble::new();
+    let code = ensemble.register(Box::new(Recursive));
+
+    for address in addresses {
+        ensemble.instantiate(code.id, &(), MockEnv::new(SENDER, *address)).unwrap();
+    }
+
+    ensemble
+}
+
+fn call(ensemble: &mut ContractEnsemble, path: &[&str]) -> Result<(), EnsembleError> {
+    let msg = ExecuteMsg::Call {
+        path: path[1..].iter().map(|x| x.to_string()).collect()
+    };
+
+    ensemble.execute(&msg, MockEnv::new(SENDER, path[0])).map(|_| ())
+}
+
+fn count(ensemble: &ContractEnsemble, address: &str) -> u32 {
+    let mut result = 0;
+    ensemble.contract_storage(address, |storage| {
+        result = storage::load(storage, b"count").unwrap().unwrap_or_default();
+    }).unwrap();
+
+    result
+}
+
+#[test]
+fn query_depth_is_limited() {
+    let mut ensemble = init(&["a"]);
+
+    let max = DEFAULT_MAX_QUERY_DEPTH as u32;
+
+    // The query made by the test itself is at depth 1.
+    let depth: u32 = ensemble.query("a", &QueryMsg::Depth { remaining: max - 1 }).unwrap();
+    assert_eq!(depth, max - 1);
+
+    let err = ensemble.query::<_, u32>("a", &QueryMsg::Depth { remaining: max }).unwrap_err();
+    assert!(err.to_string().contains(
+        &ExecutionError::QueryDepthExceeded(DEFAULT_MAX_QUERY_DEPTH + 1).to_string()
+    ));
+
+    ensemble.set_max_query_depth(Some(2));
+    ensemble.query::<_, u32>("a", &QueryMsg::Depth { remaining: 1 }).unwrap();
+    ensemble.query::<_, u32>("a", &QueryMsg::Depth { remaining: 2 }).unwrap_err();
+
+    ensemble.set_max_query_depth(None);
+    let depth: u32 = ensemble.query("a", &QueryMsg::Depth { remaining: 50 }).unwrap();
+    assert_eq!(depth, 50);
+
+    // The depth is reset after an error.
+    ensemble.set_max_query_depth(Some(1));
+    ensemble.query::<_, u32>("a", &QueryMsg::Depth { remaining: 1 }).unwrap_err();
+    ensemble.query::<_, u32>("a", &QueryMsg::Depth { remaining: 0 }).unwrap();
+}
+
+#[test]
+fn message_depth_is_limited_and_reverts_state() {
+    let mut ensemble = init(&["a", "b", "c"]);
+
+    call(&mut ensemble, &["a", "b", "c", "a", "b"]).unwrap();
+    assert_eq!(count(&ensemble, "a"), 2);
+
+    ensemble.set_max_message_depth(Some(3));
+    call(&mut ensemble, &["a", "b", "c", "a"]).unwrap();
+    assert_eq!(count(&ensemble, "a"), 4);
+
+    let err = call(&mut ensemble, &["a", "b", "c", "a", "b"]).unwrap_err();
+    assert!(matches!(
+        err,
+        EnsembleError::Execution(ExecutionError::MessageDepthExceeded(4))
+    ));
+
+    assert_eq!(count(&ensemble, "a"), 4);
+    assert_eq!(count(&ensemble, "b"), 3);
+}
+
+#[test]
+fn detects_reentrancy() {
+    let mut ensemble = init(&["a", "b", "c"]);
+    ensemble.detect_reentrancy(true);
+
+    // Calling the same contracts in sequence is fine.
+    call(&mut ensemble, &["a", "b", "c"]).unwrap();
+    call(&mut ensemble, &["a", "b", "c"]).unwrap();
+
+    let err = call(&mut ensemble, &["a", "b", "c", "a"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Ensemble error - Execution: Contract a was called again before its previous execution finished"
+    );
+
+    let err = call(&mut ensemble, &["a", "b", "b"]).unwrap_err();
+    assert!(matches!(
+        err,
+        EnsembleError::Execution(ExecutionError::Reentrancy(address)) if address == "b"
+    ));
+
+    assert_eq!(count(&ensemble, "a"), 2);
+    assert_eq!(count(&ensemble, "b"), 2);
+
+    ensemble.detect_reentrancy(false);
+    call(&mut ensemble, &["a", "b", "c", "a"]).unwrap();
+    assert_eq!(count(&ensemble, "a"), 4);
+}
+
+#[test]
+fn storage_growth_and_quota() {
+    let mut ensemble = init(&["a", "b"]);
+    assert!(ensemble.storage_growth("a").unwrap().is_empty());
+
+    let height = ensemble.block().height();
+    call(&mut ensemble, &["a", "b"]).unwrap();
+
+    let size = ensemble.storage_stats("a").unwrap().total_bytes();
+    let growth = ensemble.storage_growth("a").unwrap();
+    assert_eq!(growth, &[StorageGrowth { height, keys: 1, bytes: size as i64 }]);
+
+    call(&mut ensemble, &["a"]).unwrap();
+    assert_eq!(ensemble.storage_growth("a").unwrap()[1].bytes, 0);
+    assert_eq!(ensemble.storage_growth("b").unwrap().len(), 1);
+
+    ensemble.set_storage_quota(Some(size - 1));
+
+    // Doesn't grow so the quota isn't checked.
+    call(&mut ensemble, &["a"]).unwrap();
+
+    let code = ensemble.contract_info("a").unwrap().code_id;
+    ensemble.instantiate(code, &(), MockEnv::new(SENDER, "c")).unwrap();
+
+    let err = call(&mut ensemble, &["a", "c"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Ensemble error - Execution: Contract c uses {} bytes of storage which exceeds the quota of {} bytes", size, size - 1)
+    );
+
+    assert_eq!(count(&ensemble, "a"), 3);
+    assert_eq!(count(&ensemble, "c"), 0);
+    assert!(ensemble.storage_growth("c").unwrap().is_empty());
+
+    ensemble.set_storage_quota(None);
+    call(&mut ensemble, &["a", "c"]).unwrap();
+}
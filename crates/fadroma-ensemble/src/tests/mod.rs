@@ -1,4 +1,5 @@
 mod interactions;
+mod limits;
 #[cfg(feature = "ensemble-staking")]
 mod staking;
 mod submsg;
@@ -9,7 +9,7 @@ fn staking() {
         "something".to_string()
     );
 
-    let mut ensemble = ContractEnsemble::new();
+    let mut ensemble = ContractEnsemble::new_with_denom("uscrt");
     assert_eq!(ensemble.ctx.delegations.bonded_denom(), "uscrt");
 
     let addr1 = "addr1";
@@ -32,8 +32,8 @@ fn staking() {
         max_change_rate: Decimal::percent(5),
     };
 
-    ensemble.add_funds(addr1.clone(), vec![Coin::new(1100u128, "uscrt")]);
-    ensemble.add_funds(addr1.clone(), vec![Coin::new(314159u128, "notscrt")]);
+    ensemble.add_funds(addr1, vec![Coin::new(1100u128, "uscrt")]);
+    ensemble.add_funds(addr1, vec![Coin::new(314159u128, "notscrt")]);
     ensemble.add_validator(validator1.clone());
     ensemble.add_validator(validator2.clone());
 
@@ -50,7 +50,7 @@ fn staking() {
         .ctx
         .state
         .bank
-        .remove_funds(&addr1, Coin::new(1000u128, "uscrt"))
+        .remove_funds(addr1, Coin::new(1000u128, "uscrt"))
         .unwrap();
 
     match ensemble.ctx.delegations.delegate(
@@ -72,7 +72,7 @@ fn staking() {
         .ctx
         .state
         .bank
-        .remove_funds(&addr1, Coin::new(314159u128, "notscrt"))
+        .remove_funds(addr1, Coin::new(314159u128, "notscrt"))
         .unwrap();
 
     match ensemble.ctx.delegations.delegate(
@@ -97,7 +97,7 @@ fn staking() {
         .ctx
         .state
         .bank
-        .remove_funds(&addr1, Coin::new(100u128, "uscrt"))
+        .remove_funds(addr1, Coin::new(100u128, "uscrt"))
         .unwrap();
         
     match ensemble
@@ -116,7 +116,7 @@ fn staking() {
     };
     ensemble.ctx.state.commit();
 
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -130,11 +130,11 @@ fn staking() {
         _ => panic!("Incorrect response from delegation query"),
     };
     assert_eq!(
-        ensemble.ctx.delegations.delegation(&addr1, &val_addr_2),
+        ensemble.ctx.delegations.delegation(addr1, val_addr_2),
         None
     );
     assert_eq!(
-        ensemble.ctx.delegations.delegation(&addr2, &val_addr_1),
+        ensemble.ctx.delegations.delegation(addr2, val_addr_1),
         None
     );
 
@@ -148,7 +148,7 @@ fn staking() {
             Coin::new(500u128, "uscrt"),
         )
         .unwrap();
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -166,10 +166,7 @@ fn staking() {
         val_addr_2.to_string(),
         Coin::new(300u128, "uscrt"),
     ) {
-        Err(error) => match error {
-            EnsembleError::Staking(msg) => assert_eq!("Delegation not found", msg),
-            _ => panic!("Invalid undelegation error improperly caught"),
-        },
+        Err(EnsembleError::Staking(msg)) => assert_eq!("Delegation not found", msg),
         _ => panic!("Invalid undelegation error improperly caught"),
     };
     match ensemble.ctx.delegations.undelegate(
@@ -177,10 +174,7 @@ fn staking() {
         val_addr_1.to_string(),
         Coin::new(600u128, "uscrt"),
     ) {
-        Err(error) => match error {
-            EnsembleError::Staking(msg) => assert_eq!("Insufficient funds", msg),
-            _ => panic!("Undelegate too much error improperly caught"),
-        },
+        Err(EnsembleError::Staking(msg)) => assert_eq!("Insufficient funds", msg),
         _ => panic!("Undelegate too much error improperly caught"),
     };
 
@@ -195,7 +189,7 @@ fn staking() {
             Coin::new(300u128, "uscrt"),
         )
         .unwrap();
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -208,11 +202,11 @@ fn staking() {
         ),
         None => panic!("Original delegation not found"),
     };
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_2) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_2) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
-                delegator: Addr::unchecked(addr1.clone()),
+                delegator: Addr::unchecked(addr1),
                 validator: val_addr_2.to_string(),
                 amount: Coin::new(300u128, "uscrt"),
                 can_redelegate: Coin::new(0u128, "uscrt"),
@@ -226,7 +220,7 @@ fn staking() {
         .ctx
         .state
         .bank
-        .remove_funds(&addr1, Coin::new(100u128, "uscrt"))
+        .remove_funds(addr1, Coin::new(100u128, "uscrt"))
         .unwrap_err();
 
     ensemble
@@ -261,7 +255,7 @@ fn staking() {
             Coin::new(325u128, "uscrt"),
         )
         .unwrap();
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -274,7 +268,7 @@ fn staking() {
         ),
         None => panic!("Validator 1 delegation not found"),
     };
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_2) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_2) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -290,7 +284,7 @@ fn staking() {
 
     // Rewards
     ensemble.add_rewards(Uint128::from(50u64));
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -303,7 +297,7 @@ fn staking() {
         ),
         None => panic!("Validator 1 delegation not found"),
     };
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_2) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_2) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -322,11 +316,11 @@ fn staking() {
     let withdraw_amount = ensemble
         .ctx
         .delegations
-        .delegation(&addr1.clone(), &val_addr_1.clone())
+        .delegation(addr1, val_addr_1)
         .unwrap()
         .accumulated_rewards;
 
-    ensemble.add_funds(&addr1, withdraw_amount);
+    ensemble.add_funds(addr1, withdraw_amount);
 
     ensemble
         .ctx
@@ -334,7 +328,7 @@ fn staking() {
         .withdraw(addr1.to_string(), val_addr_1.to_string())
         .unwrap();
 
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -352,13 +346,13 @@ fn staking() {
             .ctx
             .state
             .bank
-            .query_balances(&addr1, Some("uscrt".to_string())),
+            .query_balances(addr1, Some("uscrt".to_string())),
         vec![Coin::new(50u128, "uscrt")],
     );
 
     // Fast forward
     ensemble.fast_forward_delegation_waits();
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_1) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_1) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -371,7 +365,7 @@ fn staking() {
         ),
         None => panic!("Validator 1 delegation not found"),
     };
-    match ensemble.ctx.delegations.delegation(&addr1, &val_addr_2) {
+    match ensemble.ctx.delegations.delegation(addr1, val_addr_2) {
         Some(delegation) => assert_eq!(
             delegation,
             FullDelegation {
@@ -390,7 +384,32 @@ fn staking() {
             .ctx
             .state
             .bank
-            .query_balances(&addr1, Some("uscrt".to_string())),
+            .query_balances(addr1, Some("uscrt".to_string())),
         vec![Coin::new(875u128, "uscrt")], // 500 undelegate, 325 undelegate, 50 rewards
     );
 }
+
+#[test]
+fn dump_delegations() {
+    let mut ensemble = ContractEnsemble::new_with_denom("uscrt");
+
+    ensemble.add_validator(Validator {
+        address: "validator".into(),
+        commission: Decimal::percent(5),
+        max_commission: Decimal::percent(10),
+        max_change_rate: Decimal::percent(1),
+    });
+
+    ensemble
+        .ctx
+        .delegations
+        .delegate("delegator".into(), "validator".into(), Coin::new(100u128, "uscrt"))
+        .unwrap();
+
+    let dump = ensemble.dump();
+
+    assert_eq!(dump.delegations.len(), 1);
+    assert_eq!(dump.delegations[0].delegator, Addr::unchecked("delegator"));
+    assert_eq!(dump.delegations[0].amount, Coin::new(100u128, "uscrt"));
+    assert!(dump.to_string().contains("delegation delegator -> validator: 100uscrt"));
+}
@@ -0,0 +1,180 @@
+//! Decodes a dump of a contract's storage whose keys and values are built-in types.
+//! See [`fadroma::storage::inspect`] for using it with any other types.
+
+use std::{env, fs, io::{self, Read}, process};
+
+use fadroma::{
+    cosmwasm_std::{
+        Addr, CanonicalAddr, Binary, Coin, Timestamp, Decimal,
+        Decimal256, Uint64, Uint128, Uint256, Uint512
+    },
+    storage::{
+        TypedKey,
+        inspect::{Inspector, StructureKind, Decoder, parse_dump, decode_key, decode_value, raw_key}
+    }
+};
+
+const USAGE: &str = "\
+Usage: fadroma-inspect [OPTIONS] <DUMP>
+
+Decodes a dump of a contract's storage, as returned by the `contract-state all`
+query, and prints the contents of the given storage types. Reads the dump from
+standard input if DUMP is \"-\". Exits with status 2 if any issues were found.
+
+Options:
+  --json                          Print the report as JSON.
+  --single-item <NS>=<VALUE>      A SingleItem.
+  --item-space <NS>=<KEY>:<VALUE> An ItemSpace.
+  --iterable <NS>=<VALUE>         An IterableStorage.
+  --map <NS>=<KEY>:<VALUE>        A Map.
+  --insert-only-map <NS>=<KEY>:<VALUE>
+                                  An InsertOnlyMap.
+
+NS is the namespace as text or hex encoded if it starts with \"0x\".
+
+Keys: raw, string, addr, canonical_addr, u8, u16, u32, u64, u128,
+      uint64, uint128, uint256, uint512
+Values: bool, u8, u16, u32, u64, u128, uint64, uint128, uint256, uint512,
+        decimal, decimal256, string, addr, canonical_addr, binary, coin, timestamp";
+
+fn main() {
+    match run(env::args().skip(1).collect()) {
+        Ok(true) => {},
+        Ok(false) => process::exit(2),
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+/// Returns `false` if the report has any issues.
+fn run(args: Vec<String>) -> Result<bool, String> {
+    let mut inspector = Inspector::new();
+    let mut json = false;
+    let mut path = None;
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let kind = match arg.as_str() {
+            "--json" => {
+                json = true;
+
+                continue;
+            },
+            "--single-item" => StructureKind::SingleItem,
+            "--item-space" => StructureKind::ItemSpace,
+            "--iterable" => StructureKind::Iterable,
+            "--map" => StructureKind::Map,
+            "--insert-only-map" => StructureKind::InsertOnlyMap,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+
+                return Ok(true);
+            },
+            _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            _ => {
+                if path.replace(arg).is_some() {
+                    return Err("Only a single dump can be inspected.".into());
+                }
+
+                continue;
+            }
+        };
+
+        let spec = args.next().ok_or_else(|| format!("Missing a value for {}.", arg))?;
+        register(&mut inspector, kind, &spec)?;
+    }
+
+    let path = path.ok_or("Missing the path to the dump.")?;
+
+    let dump = if path == "-" {
+        let mut dump = vec![];
+        io::stdin().read_to_end(&mut dump).map_err(|e| e.to_string())?;
+
+        dump
+    } else {
+        fs::read(&path).map_err(|e| format!("Couldn't read {}: {}", path, e))?
+    };
+
+    let entries = parse_dump(&dump).map_err(|e| e.to_string())?;
+    let report = inspector.inspect(entries);
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report);
+    }
+
+    Ok(!report.has_issues())
+}
+
+fn register(inspector: &mut Inspector, kind: StructureKind, spec: &str) -> Result<(), String> {
+    let (ns, types) = spec.rsplit_once('=')
+        .ok_or_else(|| format!("Expected <NS>=<TYPES>, got: {}", spec))?;
+
+    let (key, value) = match kind {
+        StructureKind::SingleItem | StructureKind::Iterable => (raw_key as Decoder, types),
+        _ => {
+            let (key, value) = types.split_once(':')
+                .ok_or_else(|| format!("Expected <KEY>:<VALUE>, got: {}", types))?;
+
+            (key_decoder(key)?, value)
+        }
+    };
+
+    let bytes = match ns.strip_prefix("0x") {
+        Some(hex) => fadroma::cosmwasm_std::HexBinary::from_hex(hex)
+            .map_err(|e| e.to_string())?
+            .to_vec(),
+        None => ns.as_bytes().to_vec()
+    };
+
+    inspector.register(ns, &bytes, kind, key, value_decoder(value)?);
+
+    Ok(())
+}
+
+fn key_decoder(name: &str) -> Result<Decoder, String> {
+    Ok(match name {
+        "raw" => raw_key,
+        "string" => decode_key::<TypedKey<String>>,
+        "addr" => decode_key::<TypedKey<Addr>>,
+        "canonical_addr" => decode_key::<TypedKey<CanonicalAddr>>,
+        "u8" => decode_key::<TypedKey<u8>>,
+        "u16" => decode_key::<TypedKey<u16>>,
+        "u32" => decode_key::<TypedKey<u32>>,
+        "u64" => decode_key::<TypedKey<u64>>,
+        "u128" => decode_key::<TypedKey<u128>>,
+        "uint64" => decode_key::<TypedKey<Uint64>>,
+        "uint128" => decode_key::<TypedKey<Uint128>>,
+        "uint256" => decode_key::<TypedKey<Uint256>>,
+        "uint512" => decode_key::<TypedKey<Uint512>>,
+        _ => return Err(format!("Unknown key type: {}", name))
+    })
+}
+
+fn value_decoder(name: &str) -> Result<Decoder, String> {
+    Ok(match name {
+        "bool" => decode_value::<bool>,
+        "u8" => decode_value::<u8>,
+        "u16" => decode_value::<u16>,
+        "u32" => decode_value::<u32>,
+        "u64" => decode_value::<u64>,
+        "u128" => decode_value::<u128>,
+        "uint64" => decode_value::<Uint64>,
+        "uint128" => decode_value::<Uint128>,
+        "uint256" => decode_value::<Uint256>,
+        "uint512" => decode_value::<Uint512>,
+        "decimal" => decode_value::<Decimal>,
+        "decimal256" => decode_value::<Decimal256>,
+        "string" => decode_value::<String>,
+        "addr" => decode_value::<Addr>,
+        "canonical_addr" => decode_value::<CanonicalAddr>,
+        "binary" => decode_value::<Binary>,
+        "coin" => decode_value::<Coin>,
+        "timestamp" => decode_value::<Timestamp>,
+        _ => return Err(format!("Unknown value type: {}", name))
+    })
+}
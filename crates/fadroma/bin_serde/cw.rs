@@ -1,4 +1,4 @@
-use crate::cosmwasm_std::{Binary, CanonicalAddr, Addr, Coin, Empty};
+use crate::cosmwasm_std::{Binary, CanonicalAddr, Addr, Coin, Empty, Timestamp};
 
 use super::{
     FadromaSerialize, FadromaDeserialize,
@@ -117,6 +117,25 @@ impl FadromaDeserialize for Empty {
     }
 }
 
+impl FadromaSerialize for Timestamp {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        FadromaSerialize::size_hint(&self.nanos())
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        FadromaSerialize::to_bytes(&self.nanos(), ser)
+    }
+}
+
+impl FadromaDeserialize for Timestamp {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        Ok(Self::from_nanos(de.deserialize()?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::{
@@ -183,5 +202,10 @@ mod tests {
 
             proptest_serde(&coin)?;
         }
+
+        #[test]
+        fn proptest_serde_timestamp(nanos in num::u64::ANY) {
+            proptest_serde(&Timestamp::from_nanos(nanos))?;
+        }
     }
 }
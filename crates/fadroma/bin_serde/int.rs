@@ -0,0 +1,162 @@
+use std::mem;
+
+use super::{
+    FadromaSerialize, FadromaDeserialize,
+    Serializer, Deserializer, Result
+};
+
+impl FadromaSerialize for i8 {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        ser.write_byte(*self as u8);
+
+        Ok(())
+    }
+}
+
+impl FadromaDeserialize for i8 {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        Ok(de.read_byte()? as i8)
+    }
+}
+
+impl FadromaSerialize for i16 {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        2
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        ser.write(&self.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+impl FadromaDeserialize for i16 {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        const SIZE: usize = mem::size_of::<i16>();
+        let le_bytes = de.read(SIZE)?;
+
+        Ok(Self::from_le_bytes([le_bytes[0], le_bytes[1]]))
+    }
+}
+
+// The wider integers are mapped to their unsigned counterpart using zigzag
+// encoding (0, -1, 1, -2, 2...) so that numbers with a small magnitude take
+// up few bytes regardless of their sign, as they would with two's complement.
+macro_rules! impl_int {
+    ($int:ty, $uint:ty) => {
+        impl FadromaSerialize for $int {
+            #[inline]
+            fn size_hint(&self) -> usize {
+                1 + mem::size_of::<Self>()
+            }
+
+            #[inline]
+            fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+                let zigzag = ((*self << 1) ^ (*self >> (<$int>::BITS - 1))) as $uint;
+
+                zigzag.to_bytes(ser)
+            }
+        }
+
+        impl FadromaDeserialize for $int {
+            #[inline]
+            fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+                let zigzag = de.deserialize::<$uint>()?;
+
+                Ok(((zigzag >> 1) as $int) ^ -((zigzag & 1) as $int))
+            }
+        }
+    };
+}
+
+impl_int!(i32, u32);
+impl_int!(i64, u64);
+impl_int!(i128, u128);
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::bin_serde::testing::{serde_len, proptest_serde, proptest_serde_len};
+
+    #[test]
+    fn serde_i8() {
+        serde_len(&0i8, 1);
+        serde_len(&-1i8, 1);
+        serde_len(&i8::MIN, 1);
+        serde_len(&i8::MAX, 1);
+    }
+
+    #[test]
+    fn serde_i16() {
+        serde_len(&0i16, 2);
+        serde_len(&-1i16, 2);
+        serde_len(&i16::MIN, 2);
+        serde_len(&i16::MAX, 2);
+    }
+
+    #[test]
+    fn serde_i32() {
+        serde_len(&0i32, 1);
+        serde_len(&-1i32, 2);
+        serde_len(&1i32, 2);
+        serde_len(&-128i32, 2);
+        serde_len(&128i32, 3);
+        serde_len(&i32::MIN, 5);
+        serde_len(&i32::MAX, 5);
+    }
+
+    #[test]
+    fn serde_i64() {
+        serde_len(&0i64, 1);
+        serde_len(&-1i64, 2);
+        serde_len(&i64::MIN, 9);
+        serde_len(&i64::MAX, 9);
+    }
+
+    #[test]
+    fn serde_i128() {
+        serde_len(&0i128, 1);
+        serde_len(&-1i128, 2);
+        serde_len(&i128::MIN, 17);
+        serde_len(&i128::MAX, 17);
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_serde_i8(num in any::<i8>()) {
+            proptest_serde_len(&num, 1)?;
+        }
+
+        #[test]
+        fn proptest_serde_i16(num in any::<i16>()) {
+            proptest_serde_len(&num, 2)?;
+        }
+
+        #[test]
+        fn proptest_serde_i32(num in any::<i32>()) {
+            proptest_serde(&num)?;
+        }
+
+        #[test]
+        fn proptest_serde_i64(num in any::<i64>()) {
+            proptest_serde(&num)?;
+        }
+
+        #[test]
+        fn proptest_serde_i128(num in any::<i128>()) {
+            proptest_serde(&num)?;
+        }
+    }
+}
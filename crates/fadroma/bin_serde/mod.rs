@@ -1,18 +1,133 @@
 //! Time and space efficient binary serialization for types that are stored in a contract's storage.
 //! Supports both structs and enums, with or without generics.
 //! This is Fadroma's *default* mode for serializing data for storage.
+//!
+//! # Evolving types
+//!
+//! The binary format doesn't include field names or tags, so changing the fields of
+//! a type changes how every stored value of it is read. Types that are expected to
+//! change can be versioned with the `#[fadroma(version = N)]` attribute, which makes
+//! the derives write the version before the fields. Values written by any earlier
+//! version can still be read afterwards, so existing data doesn't need to be migrated:
+//!  * Fields added in a later version are marked with `#[fadroma(since = N)]`. When reading
+//!    older versions, they are set to [`Default::default`] or to the value returned by the
+//!    function given with `#[fadroma(since = N, default = "path::to::function")]`.
+//!  * Fields removed in a later version are marked with `#[fadroma(until = N)]` instead
+//!    of being deleted. They are only read from older versions and are never written.
+//!    Use [`Skip`] as their type to discard their value.
+//!
+//! Enum variants are identified by their position in the enum by default. Give them
+//! a fixed tag with `#[fadroma(tag = N)]` so that variants can be reordered or removed.
+//! Variants without one get the tag that follows the one of the previous variant.
+//!
+//! Adding a version to a type that is already stored changes its representation and
+//! values written before the version was added **can't be read** by the versioned type,
+//! since nothing in the stored bytes tells them apart from versioned ones. The attribute
+//! should therefore be present from the first version that is stored. Otherwise, existing
+//! values need a one-time migration which reads them using a copy of the type without the
+//! attribute and saves them again, for example with a [`crate::storage::migration::Migrator`]
+//! step:
+//!
+//! ```
+//! use fadroma::{
+//!     bin_serde::{FadromaSerialize, FadromaDeserialize},
+//!     cosmwasm_std::{Storage, StdResult, testing::mock_dependencies},
+//!     storage::{self, Namespace, SingleItem, migration::{Migrator, Migration, migrate_value}}
+//! };
+//!
+//! // The type as it was stored before it was versioned.
+//! #[derive(FadromaSerialize, FadromaDeserialize)]
+//! struct UnversionedConfig {
+//!     admin: String
+//! }
+//!
+//! #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+//! #[fadroma(version = 1)]
+//! struct Config {
+//!     admin: String
+//! }
+//!
+//! fadroma::namespace!(ConfigNs, b"config");
+//! const CONFIG: SingleItem<Config, ConfigNs> = SingleItem::new();
+//!
+//! fn version_config(storage: &mut dyn Storage) -> StdResult<()> {
+//!     migrate_value(storage, ConfigNs::NAMESPACE, |old: UnversionedConfig| {
+//!         Ok(Config { admin: old.admin })
+//!     })?;
+//!
+//!     Ok(())
+//! }
+//!
+//! const MIGRATOR: Migrator = Migrator::new(&[
+//!     Migration::once(0, "add a version to the config", version_config)
+//! ]);
+//!
+//! # fn main() -> StdResult<()> {
+//! # let mut deps = mock_dependencies();
+//! # let storage = deps.as_mut().storage;
+//! storage::save(storage, ConfigNs::NAMESPACE, &UnversionedConfig { admin: "admin".into() })?;
+//!
+//! MIGRATOR.run(storage)?;
+//! assert_eq!(CONFIG.load(storage)?, Some(Config { admin: "admin".into() }));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```
+//! use fadroma::bin_serde::{
+//!     FadromaSerialize, FadromaDeserialize, FadromaSerializeExt, Deserializer, Skip
+//! };
+//!
+//! #[derive(FadromaSerialize, FadromaDeserialize)]
+//! #[fadroma(version = 1)]
+//! struct ConfigV1 {
+//!     fee: u64,
+//!     admin: String
+//! }
+//!
+//! #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+//! #[fadroma(version = 2)]
+//! struct Config {
+//!     #[fadroma(until = 2)]
+//!     fee: Skip<u64>,
+//!     admin: String,
+//!     #[fadroma(since = 2, default = "default_fee")]
+//!     fee_bps: u16
+//! }
+//!
+//! fn default_fee() -> u16 {
+//!     30
+//! }
+//!
+//! let bytes = ConfigV1 { fee: 3, admin: "admin".into() }.serialize().unwrap();
+//! let config: Config = Deserializer::from(&bytes).deserialize().unwrap();
+//!
+//! assert_eq!(config, Config { fee: Skip::default(), admin: "admin".into(), fee_bps: 30 });
+//!
+//! #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+//! enum Status {
+//!     #[fadroma(tag = 1)]
+//!     Active,
+//!     // Tag 2 was used by a variant that was removed.
+//!     #[fadroma(tag = 3)]
+//!     Closed { reason: String }
+//! }
+//!
+//! assert_eq!(Status::Active.serialize().unwrap(), vec![1]);
+//! ```
 
 pub mod adapter;
 
 mod byte_len;
 mod uint;
+mod int;
 mod stdlib;
 mod cw;
 
 pub use fadroma_derive_serde::{FadromaSerialize, FadromaDeserialize};
 pub use byte_len::ByteLen;
 
-use std::fmt::Display;
+use std::{fmt::{self, Display}, marker::PhantomData};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -67,6 +182,11 @@ pub trait FadromaSerializeExt: FadromaSerialize {
     fn serialize(&self) -> Result<Vec<u8>>;
 }
 
+/// Reads a value of type `T` and discards it. Meant to be used as
+/// the type of fields that have been removed from a versioned type.
+/// See the [module level documentation](self).
+pub struct Skip<T>(PhantomData<T>);
+
 pub struct Serializer {
     buf: Vec<u8>
 }
@@ -184,6 +304,47 @@ impl<'a> Deserializer<'a> {
     }
 }
 
+impl<T: FadromaDeserialize> FadromaDeserialize for Skip<T> {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        de.deserialize::<T>()?;
+
+        Ok(Self::default())
+    }
+}
+
+// Implemented manually since the derives would require T to implement them as well.
+impl<T> Default for Skip<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Clone for Skip<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Skip<T> { }
+
+impl<T> PartialEq for Skip<T> {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for Skip<T> { }
+
+impl<T> fmt::Debug for Skip<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Skip")
+    }
+}
+
 impl<'a, T: AsRef<[u8]>> From<&'a T> for Deserializer<'a> {
     fn from(bytes: &'a T) -> Self {
         Self {
@@ -318,6 +479,119 @@ pub(crate) mod testing {
         }
     }
 
+    #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+    #[fadroma(version = 1)]
+    struct RecordV1 {
+        id: u64,
+        name: String
+    }
+
+    #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+    #[fadroma(version = 2)]
+    struct RecordV2 {
+        id: u64,
+        #[fadroma(until = 2)]
+        name: Skip<String>,
+        #[fadroma(since = 2, default = "default_label")]
+        label: String,
+        #[fadroma(since = 2)]
+        tags: Vec<u8>
+    }
+
+    #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+    #[fadroma(version = 1)]
+    enum MessageV1 {
+        Ping,
+        Data(u64, String)
+    }
+
+    #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+    #[fadroma(version = 2)]
+    enum MessageV2 {
+        #[fadroma(tag = 1)]
+        Data(
+            u64,
+            #[fadroma(until = 2)]
+            Skip<String>,
+            #[fadroma(since = 2)]
+            Option<u8>
+        ),
+        #[fadroma(tag = 5)]
+        Close { code: u16 }
+    }
+
+    fn default_label() -> String {
+        "unnamed".into()
+    }
+
+    #[test]
+    fn versioned_struct() {
+        let v1 = RecordV1 { id: 3, name: "record".into() };
+        let bytes = v1.serialize().unwrap();
+
+        assert_eq!(bytes[0], 1);
+        serde(&v1);
+
+        let mut de = Deserializer::from(&bytes);
+        let v2: RecordV2 = de.deserialize().unwrap();
+
+        assert!(de.is_finished());
+        assert_eq!(v2, RecordV2 {
+            id: 3,
+            name: Skip::default(),
+            label: "unnamed".into(),
+            tags: vec![]
+        });
+
+        let v2 = RecordV2 {
+            label: "label".into(),
+            tags: vec![1, 2],
+            ..v2
+        };
+        let bytes = v2.serialize().unwrap();
+
+        assert_eq!(bytes[0], 2);
+
+        let mut de = Deserializer::from(&bytes);
+        assert_eq!(de.deserialize::<RecordV2>().unwrap(), v2);
+        assert!(de.is_finished());
+
+        // Versions which are newer than the type can't be read.
+        let mut de = Deserializer::from(&bytes);
+        assert_eq!(de.deserialize::<RecordV1>(), Err(Error::InvalidType));
+
+        let mut de = Deserializer::from(&[0u8, 3]);
+        assert_eq!(de.deserialize::<RecordV1>(), Err(Error::InvalidType));
+    }
+
+    #[test]
+    fn versioned_enum() {
+        serde(&MessageV1::Ping);
+        serde(&MessageV1::Data(1, "data".into()));
+
+        let bytes = MessageV1::Data(7, "data".into()).serialize().unwrap();
+        assert_eq!(&bytes[..2], &[1, 1]);
+
+        let mut de = Deserializer::from(&bytes);
+        assert_eq!(de.deserialize::<MessageV2>().unwrap(), MessageV2::Data(7, Skip::default(), None));
+        assert!(de.is_finished());
+
+        // The removed variant can't be read.
+        let bytes = MessageV1::Ping.serialize().unwrap();
+        let mut de = Deserializer::from(&bytes);
+        assert_eq!(de.deserialize::<MessageV2>(), Err(Error::InvalidType));
+
+        let msg = MessageV2::Data(7, Skip::default(), Some(4));
+        let bytes = msg.serialize().unwrap();
+
+        assert_eq!(bytes, vec![2, 1, 1, 7, 1, 4]);
+        serde(&msg);
+
+        let msg = MessageV2::Close { code: 3 };
+        assert_eq!(msg.serialize().unwrap(), vec![2, 5, 3, 0]);
+        serde(&msg);
+    }
+
     fn coin_strategy() -> impl Strategy<Value = Coin> {
         (any::<u128>(), "\\PC*").prop_map(|x| coin(x.0, x.1))
     }
@@ -1,4 +1,8 @@
-use std::{mem, ptr};
+use std::{
+    mem,
+    hash::{Hash, BuildHasher},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet}
+};
 
 use super::{
     FadromaSerialize, FadromaDeserialize,
@@ -149,56 +153,333 @@ impl FadromaDeserialize for String {
     }
 }
 
-impl<const N: usize> FadromaSerialize for [u8; N] {
+impl<T: FadromaSerialize, const N: usize> FadromaSerialize for [T; N] {
     #[inline]
     fn size_hint(&self) -> usize {
-        ByteLen::MAX_SIZE + N
+        ByteLen::MAX_SIZE + self.iter().map(|x| x.size_hint()).sum::<usize>()
     }
 
     #[inline]
     fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
-        let len = ByteLen::encode(self.len())?;
+        // The length is redundant but it is kept so that arrays
+        // have the same representation as the equivalent Vec.
+        let len = ByteLen::encode(N)?;
         ser.write(len.as_bytes());
-        ser.write(self.as_slice());
+
+        for item in self {
+            item.to_bytes(ser)?;
+        }
 
         Ok(())
     }
 }
 
-impl<const N: usize> FadromaDeserialize for [u8; N] {
+impl<T: FadromaDeserialize, const N: usize> FadromaDeserialize for [T; N] {
     fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
         let len = ByteLen::decode(de)?;
-        let bytes = de.read(len)?;
 
-        let mut result = [0; N];
-
-        unsafe {
-            // SAFETY: `read` will either return the requested amount of bytes
-            // or an error. The slices cannot overlap because the read bytes come from
-            // heap memory.
-            ptr::copy_nonoverlapping(
-                bytes.as_ptr(),
-                result.as_mut_ptr(),
-                N
-            )
+        if len != N {
+            return Err(Error::InvalidType);
+        }
+
+        let mut result = Vec::with_capacity(N);
+
+        for _ in 0..N {
+            result.push(T::from_bytes(de)?);
+        }
+
+        // The length was checked above.
+        Ok(result.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<T: FadromaSerialize + ?Sized> FadromaSerialize for Box<T> {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        self.as_ref().size_hint()
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        self.as_ref().to_bytes(ser)
+    }
+}
+
+impl<T: FadromaDeserialize> FadromaDeserialize for Box<T> {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        Ok(Box::new(T::from_bytes(de)?))
+    }
+}
+
+impl FadromaSerialize for char {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        FadromaSerialize::size_hint(&(*self as u32))
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        (*self as u32).to_bytes(ser)
+    }
+}
+
+impl FadromaDeserialize for char {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        let value = de.deserialize::<u32>()?;
+
+        char::from_u32(value).ok_or(Error::InvalidType)
+    }
+}
+
+impl FadromaSerialize for () {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn to_bytes(&self, _ser: &mut Serializer) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl FadromaDeserialize for () {
+    #[inline]
+    fn from_bytes<'a>(_de: &mut Deserializer<'a>) -> Result<Self> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($index:tt $name:ident),+) => {
+        impl<$($name: FadromaSerialize),+> FadromaSerialize for ($($name,)+) {
+            #[inline]
+            fn size_hint(&self) -> usize {
+                0 $(+ self.$index.size_hint())+
+            }
+
+            #[inline]
+            fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+                $(self.$index.to_bytes(ser)?;)+
+
+                Ok(())
+            }
+        }
+
+        impl<$($name: FadromaDeserialize),+> FadromaDeserialize for ($($name,)+) {
+            #[inline]
+            fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+                Ok(($($name::from_bytes(de)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(0 A);
+impl_tuple!(0 A, 1 B);
+impl_tuple!(0 A, 1 B, 2 C);
+impl_tuple!(0 A, 1 B, 2 C, 3 D);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K);
+impl_tuple!(0 A, 1 B, 2 C, 3 D, 4 E, 5 F, 6 G, 7 H, 8 I, 9 J, 10 K, 11 L);
+
+// Maps and sets are encoded just like a Vec of their entries. Since the
+// iteration order of the hash based collections is not deterministic, their
+// entries are sorted first so that equal collections always produce the
+// same bytes. Duplicate entries are rejected when deserializing, which
+// guarantees that every collection has a single valid representation.
+
+impl<K: FadromaSerialize, V: FadromaSerialize> FadromaSerialize for BTreeMap<K, V> {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        ByteLen::MAX_SIZE + self.iter()
+            .map(|(k, v)| k.size_hint() + v.size_hint())
+            .sum::<usize>()
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        write_entries(ser, self.len(), self.iter())
+    }
+}
+
+impl<K: FadromaDeserialize + Ord, V: FadromaDeserialize> FadromaDeserialize for BTreeMap<K, V> {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        let len = ByteLen::decode(de)?;
+        let mut result = Self::new();
+
+        for _ in 0..len {
+            let (key, value) = de.deserialize()?;
+
+            if result.insert(key, value).is_some() {
+                return Err(Error::InvalidType);
+            }
         }
 
         Ok(result)
     }
 }
 
+impl<K, V, S> FadromaSerialize for HashMap<K, V, S>
+    where
+        K: FadromaSerialize + Ord,
+        V: FadromaSerialize
+{
+    #[inline]
+    fn size_hint(&self) -> usize {
+        ByteLen::MAX_SIZE + self.iter()
+            .map(|(k, v)| k.size_hint() + v.size_hint())
+            .sum::<usize>()
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        write_entries(ser, self.len(), entries.into_iter())
+    }
+}
+
+impl<K, V, S> FadromaDeserialize for HashMap<K, V, S>
+    where
+        K: FadromaDeserialize + Eq + Hash,
+        V: FadromaDeserialize,
+        S: BuildHasher + Default
+{
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        let len = ByteLen::decode(de)?;
+        let mut result = Self::with_capacity_and_hasher(len, S::default());
+
+        for _ in 0..len {
+            let (key, value) = de.deserialize()?;
+
+            if result.insert(key, value).is_some() {
+                return Err(Error::InvalidType);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl<T: FadromaSerialize> FadromaSerialize for BTreeSet<T> {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        ByteLen::MAX_SIZE + self.iter().map(|x| x.size_hint()).sum::<usize>()
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        write_items(ser, self.len(), self.iter())
+    }
+}
+
+impl<T: FadromaDeserialize + Ord> FadromaDeserialize for BTreeSet<T> {
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        let len = ByteLen::decode(de)?;
+        let mut result = Self::new();
+
+        for _ in 0..len {
+            if !result.insert(T::from_bytes(de)?) {
+                return Err(Error::InvalidType);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl<T: FadromaSerialize + Ord, S> FadromaSerialize for HashSet<T, S> {
+    #[inline]
+    fn size_hint(&self) -> usize {
+        ByteLen::MAX_SIZE + self.iter().map(|x| x.size_hint()).sum::<usize>()
+    }
+
+    #[inline]
+    fn to_bytes(&self, ser: &mut Serializer) -> Result<()> {
+        let mut items = self.iter().collect::<Vec<_>>();
+        items.sort_unstable();
+
+        write_items(ser, self.len(), items.into_iter())
+    }
+}
+
+impl<T, S> FadromaDeserialize for HashSet<T, S>
+    where
+        T: FadromaDeserialize + Eq + Hash,
+        S: BuildHasher + Default
+{
+    #[inline]
+    fn from_bytes<'a>(de: &mut Deserializer<'a>) -> Result<Self> {
+        let len = ByteLen::decode(de)?;
+        let mut result = Self::with_capacity_and_hasher(len, S::default());
+
+        for _ in 0..len {
+            if !result.insert(T::from_bytes(de)?) {
+                return Err(Error::InvalidType);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[inline]
+fn write_entries<'a, K: FadromaSerialize + 'a, V: FadromaSerialize + 'a>(
+    ser: &mut Serializer,
+    len: usize,
+    entries: impl Iterator<Item = (&'a K, &'a V)>
+) -> Result<()> {
+    let len = ByteLen::encode(len)?;
+    ser.write(len.as_bytes());
+
+    for (key, value) in entries {
+        key.to_bytes(ser)?;
+        value.to_bytes(ser)?;
+    }
+
+    Ok(())
+}
+
+#[inline]
+fn write_items<'a, T: FadromaSerialize + 'a>(
+    ser: &mut Serializer,
+    len: usize,
+    items: impl Iterator<Item = &'a T>
+) -> Result<()> {
+    let len = ByteLen::encode(len)?;
+    ser.write(len.as_bytes());
+
+    for item in items {
+        item.to_bytes(ser)?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::{
         prelude::*,
         num,
-        collection::vec,
+        collection::{vec, btree_map, btree_set},
         array::{uniform7, uniform13, uniform27}
     };
 
     use crate::bin_serde::{
-        FadromaSerialize, Serializer, Deserializer,
-        testing::{serde_len, proptest_serde_len}
+        FadromaSerialize, FadromaSerializeExt, Serializer, Deserializer,
+        testing::{serde, serde_len, proptest_serde, proptest_serde_len}
     };
     use super::*;
 
@@ -287,6 +568,77 @@ mod tests {
         serde_len(&string, 130);
     }
 
+    #[test]
+    fn serde_array() {
+        serde_len(&[0u8; 0], 1);
+        serde_len(&[1u64, 256, 0], 1 + 2 + 3 + 1);
+        serde_len(&[String::from("a"), String::from("bc")], 1 + 2 + 3);
+
+        // Arrays have the same representation as the equivalent Vec.
+        assert_eq!([1u64, 2].serialize().unwrap(), vec![1u64, 2].serialize().unwrap());
+
+        let bytes = vec![1u64, 2, 3].serialize().unwrap();
+        let err = Deserializer::from(&bytes).deserialize::<[u64; 2]>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+
+        let bytes = [1u8, 2].serialize().unwrap();
+        let err = Deserializer::from(&bytes).deserialize::<[u8; 3]>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+    }
+
+    #[test]
+    fn serde_box_char_unit() {
+        serde_len(&Box::new(String::from("box")), 4);
+        serde_len(&(), 0);
+        serde_len(&'a', 2);
+        serde_len(&'\u{10FFFF}', 4);
+
+        let bytes = 0xD800u32.serialize().unwrap();
+        let err = Deserializer::from(&bytes).deserialize::<char>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+    }
+
+    #[test]
+    fn serde_tuple() {
+        serde_len(&(1u8,), 1);
+        serde_len(&(1u8, String::from("ab"), true), 5);
+        serde(&(1u8, 2u16, 3u32, 4u64, 5u128, 6i8, 7i16, 8i32, 9i64, 10i128, 'c', ()));
+    }
+
+    #[test]
+    fn serde_maps_and_sets() {
+        let btree: BTreeMap<String, u64> = [
+            ("b".into(), 2),
+            ("a".into(), 1),
+            ("c".into(), 3)
+        ].into_iter().collect();
+        serde_len(&btree, 1 + 3 * (2 + 2));
+
+        // Hash maps are written in the same order as the equivalent BTreeMap.
+        let hash: HashMap<String, u64> = btree.clone().into_iter().collect();
+        assert_eq!(hash.serialize().unwrap(), btree.serialize().unwrap());
+        serde(&hash);
+
+        let set: BTreeSet<u64> = [3, 1, 2].into_iter().collect();
+        let hash_set: HashSet<u64> = set.iter().copied().collect();
+        assert_eq!(hash_set.serialize().unwrap(), set.serialize().unwrap());
+        serde(&set);
+        serde(&hash_set);
+
+        // Duplicate keys are rejected.
+        let bytes = vec![(1u8, 2u8), (1, 3)].serialize().unwrap();
+        let err = Deserializer::from(&bytes).deserialize::<BTreeMap<u8, u8>>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+        let err = Deserializer::from(&bytes).deserialize::<HashMap<u8, u8>>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+
+        let bytes = vec![1u8, 1].serialize().unwrap();
+        let err = Deserializer::from(&bytes).deserialize::<BTreeSet<u8>>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+        let err = Deserializer::from(&bytes).deserialize::<HashSet<u8>>().unwrap_err();
+        assert_eq!(err, Error::InvalidType);
+    }
+
     proptest! {
         #[test]
         fn proptest_serde_byte_slice(bytes in vec(num::u8::ANY, 0..=1024)) {
@@ -355,5 +707,39 @@ mod tests {
         fn proptest_serde_array27(bytes in uniform27(0..u8::MAX)) {
             proptest_serde_len(&bytes, 1 + 27)?;
         }
+
+        #[test]
+        fn proptest_serde_tuple(a in any::<i64>(), b in "\\PC*", c in any::<Option<u32>>()) {
+            proptest_serde(&(a, b, c))?;
+        }
+
+        #[test]
+        fn proptest_serde_char(c in any::<char>()) {
+            proptest_serde(&c)?;
+        }
+
+        #[test]
+        fn proptest_serde_array_of_strings(array in uniform7("\\PC*")) {
+            proptest_serde(&array)?;
+            proptest_serde(&Box::new(array))?;
+        }
+
+        #[test]
+        fn proptest_serde_maps(map in btree_map("\\PC*", any::<i128>(), 0..=32)) {
+            let hash_map = map.clone().into_iter().collect::<HashMap<_, _>>();
+            prop_assert_eq!(hash_map.serialize().unwrap(), map.serialize().unwrap());
+
+            proptest_serde(&map)?;
+            proptest_serde(&hash_map)?;
+        }
+
+        #[test]
+        fn proptest_serde_sets(set in btree_set(any::<u64>(), 0..=32)) {
+            let hash_set = set.iter().copied().collect::<HashSet<_>>();
+            prop_assert_eq!(hash_set.serialize().unwrap(), set.serialize().unwrap());
+
+            proptest_serde(&set)?;
+            proptest_serde(&hash_set)?;
+        }
     }
 }
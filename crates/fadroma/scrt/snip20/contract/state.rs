@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     self as fadroma,
-    storage::{Segment, iterable::IterableStorage},
+    storage::{Segment, iterable::IterableStorage, counter::Counter},
     scrt::snip20::client::{TokenConfig, GivenAllowance, ReceivedAllowance},
     cosmwasm_std::{self, BlockInfo, CanonicalAddr, StdResult, Storage, Uint128, Deps},
     prelude::{
@@ -73,6 +73,12 @@ crate::namespace!(AllowedNs, b"YjRo4tM6pC");
 crate::namespace!(ViewingKeyNs, b"MLRCoHCV8x");
 crate::namespace!(ReceierHashNs, b"V1SJqXtGju");
 
+crate::assert_namespaces!(
+    ConstantsNs, PrngSeedNs, TotalSupplyNs, MintersNs, SupportedDenomsNs,
+    BalancesNs, AllowancesNs, AllowancesIndicesNs, AllowedNs, ViewingKeyNs,
+    ReceierHashNs, crate::admin::AdminNs, crate::admin::PendingAdminNs
+);
+
 #[derive(PartialEq, Debug)]
 pub struct Account {
     addr: CanonicalAddr
@@ -111,20 +117,14 @@ impl TotalSupplyStore {
     /// Saturates at [`Uint128::MAX`] and thus the return value is the actual amount added.
     #[inline]
     pub fn increase(&self, storage: &mut dyn Storage, amount: Uint128) -> StdResult<Uint128> {
-        let mut total_supply = self.load_or_default(storage)?;
-        let amount_added = safe_add(&mut total_supply, amount);
-
-        self.save(storage, &total_supply)?;
-
-        Ok(amount_added)
+        Counter::<Uint128, TotalSupplyNs>::new().saturating_add(storage, amount)
     }
 
     #[inline]
     pub fn decrease(&self, storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
-        let total_supply = self.load_or_default(storage)?;
-        let new_total = total_supply.checked_sub(amount)?;
+        Counter::<Uint128, TotalSupplyNs>::new().sub(storage, amount)?;
 
-        self.save(storage, &new_total)
+        Ok(())
     }
 }
 
@@ -521,7 +521,9 @@ impl From<TokenConfig> for TokenSettings {
 
 #[cfg(test)]
 mod tests {
-    use super::{TokenSettings, TokenPermission};
+    use crate::cosmwasm_std::{BlockInfo, Timestamp};
+
+    use super::{TokenSettings, TokenPermission, Allowance};
 
     #[test]
     fn token_settings() {
@@ -559,4 +561,22 @@ mod tests {
 
         test(s, [false, true, false, true, false, true]);
     }
+
+    #[test]
+    fn allowance_expiration() {
+        let block = BlockInfo {
+            height: 1,
+            time: Timestamp::from_seconds(1000),
+            chain_id: "test".into(),
+            random: None
+        };
+
+        let allowance = |expiration| Allowance { amount: 1u128.into(), expiration };
+
+        assert!(!allowance(None).is_expired_at(&block));
+        assert!(!allowance(Some(1001)).is_expired_at(&block));
+        assert!(allowance(Some(1000)).is_expired_at(&block));
+        assert!(allowance(Some(999)).is_expired_at(&block));
+        assert!(!allowance(Some(u64::MAX)).is_expired_at(&block));
+    }
 }
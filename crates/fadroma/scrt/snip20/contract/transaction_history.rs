@@ -2,7 +2,7 @@ use std::ops::Deref;
 
 use crate::{
     prelude::*,
-    storage::iterable::{self, IterableStorage},
+    storage::{iterable::{self, IterableStorage}, counter::Counter},
     scrt::snip20::client::{Tx, RichTx, RichTxCanon, TxActionCanon, TxCode}
 };
 use super::{
@@ -365,10 +365,7 @@ impl RichTxCanon {
 impl TxCountStore {
     #[inline]
     pub fn increment(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let count = self.load_or_default(storage)? + 1;
-        self.save(storage, &count)?;
-
-        Ok(count)
+        Counter::<u64, TxCountNs>::new().increment(storage)
     }
 }
 
@@ -0,0 +1,413 @@
+use std::{marker::PhantomData, ops::{RangeBounds, Bound}};
+
+use crate::cosmwasm_std::{Storage, StdResult, StdError};
+use super::Namespace;
+
+const KEY_WORD: &[u8] = b"word";
+const KEY_COUNT: &[u8] = b"count";
+
+const WORD_BYTES: usize = 32;
+const WORD_BITS: u64 = WORD_BYTES as u64 * 8;
+
+type Word = [u8; WORD_BYTES];
+
+/// A set of bits indexed by `u64` which packs them into words of 256 bits,
+/// each stored under its own key. Words with no bits set are not stored at all.
+/// Use it to track which indices have been used, for example airdrop claims or
+/// one-time actions, with a single storage key for every 256 consecutive indices.
+/// It also keeps count of the bits that are set.
+///
+/// Operations over a range of bits read one word for every 256 bits
+/// in the range, so avoid ranges that are much wider than needed.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::mock_dependencies},
+///     storage::bitmap::Bitmap
+/// };
+///
+/// fadroma::namespace!(ClaimedNs, b"claimed");
+/// const CLAIMED: Bitmap<ClaimedNs> = Bitmap::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// assert!(CLAIMED.set(storage, 7).unwrap());
+/// assert!(CLAIMED.set(storage, 300).unwrap());
+/// // Already claimed.
+/// assert!(!CLAIMED.set(storage, 7).unwrap());
+///
+/// assert!(CLAIMED.get(storage, 300).unwrap());
+/// assert_eq!(CLAIMED.count(storage).unwrap(), 2);
+/// assert_eq!(CLAIMED.count_ones(storage, 0..256).unwrap(), 1);
+///
+/// let claimed = CLAIMED.ones(storage, ..1000).unwrap().collect::<StdResult<Vec<_>>>().unwrap();
+/// assert_eq!(claimed, [7, 300]);
+/// ```
+pub struct Bitmap<N: Namespace> {
+    ns_data: PhantomData<N>
+}
+
+/// Iterator over the indices of the bits that are set in a [`Bitmap`], in ascending order.
+/// Stops after returning an error if a word can't be loaded.
+pub struct Ones<'storage> {
+    storage: &'storage dyn Storage,
+    ns: &'static [u8],
+    word_index: u64,
+    word: Word,
+    /// The next bit to check, relative to the start of the current word.
+    bit: u64,
+    /// The last bit to check, inclusive.
+    last: u64,
+    done: bool
+}
+
+impl<N: Namespace> Bitmap<N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `Bitmap`:
+    ///  * N + "count"
+    ///  * N + "word" + n - where n is a number
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            ns_data: PhantomData
+        }
+    }
+
+    /// Sets the bit at the given index. Returns `true` if it wasn't already set.
+    pub fn set(&self, storage: &mut dyn Storage, index: u64) -> StdResult<bool> {
+        let (word_index, byte, mask) = position(index);
+        let mut word = load_word(storage, N::NAMESPACE, word_index)?;
+
+        if word[byte] & mask != 0 {
+            return Ok(false);
+        }
+
+        word[byte] |= mask;
+        save_word(storage, N::NAMESPACE, word_index, &word);
+
+        let count = self.count(storage)?;
+        super::save(storage, Self::count_key(), &(count + 1))?;
+
+        Ok(true)
+    }
+
+    /// Unsets the bit at the given index. Returns `true` if it was set.
+    pub fn unset(&self, storage: &mut dyn Storage, index: u64) -> StdResult<bool> {
+        let (word_index, byte, mask) = position(index);
+        let mut word = load_word(storage, N::NAMESPACE, word_index)?;
+
+        if word[byte] & mask == 0 {
+            return Ok(false);
+        }
+
+        word[byte] &= !mask;
+        save_word(storage, N::NAMESPACE, word_index, &word);
+
+        let count = self.count(storage)?;
+        super::save(storage, Self::count_key(), &(count - 1))?;
+
+        Ok(true)
+    }
+
+    /// Returns `true` if the bit at the given index is set.
+    #[inline]
+    pub fn get(&self, storage: &dyn Storage, index: u64) -> StdResult<bool> {
+        let (word_index, byte, mask) = position(index);
+        let word = load_word(storage, N::NAMESPACE, word_index)?;
+
+        Ok(word[byte] & mask != 0)
+    }
+
+    /// Returns the total number of bits that are set.
+    #[inline]
+    pub fn count(&self, storage: &dyn Storage) -> StdResult<u64> {
+        let count: Option<u64> = super::load(storage, Self::count_key())?;
+
+        Ok(count.unwrap_or_default())
+    }
+
+    /// Returns the number of bits that are set within the given range of indices.
+    pub fn count_ones(
+        &self,
+        storage: &dyn Storage,
+        range: impl RangeBounds<u64>
+    ) -> StdResult<u64> {
+        let Some((first, last)) = resolve_range(range)? else {
+            return Ok(0);
+        };
+
+        let mut count = 0;
+
+        for word_index in (first / WORD_BITS)..=(last / WORD_BITS) {
+            let word = load_word(storage, N::NAMESPACE, word_index)?;
+            let start = word_index * WORD_BITS;
+
+            let from = first.saturating_sub(start);
+            let to = (last - start).min(WORD_BITS - 1);
+
+            count += if from == 0 && to == WORD_BITS - 1 {
+                word.iter().map(|x| x.count_ones() as u64).sum()
+            } else {
+                (from..=to).filter(|bit| is_set(&word, *bit)).count() as u64
+            };
+        }
+
+        Ok(count)
+    }
+
+    /// Returns an iterator over the indices of the bits that are set within the given range.
+    pub fn ones<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        range: impl RangeBounds<u64>
+    ) -> StdResult<Ones<'storage>> {
+        let (first, last, done) = match resolve_range(range)? {
+            Some((first, last)) => (first, last, false),
+            None => (0, 0, true)
+        };
+
+        let word_index = first / WORD_BITS;
+        let word = if done { [0; WORD_BYTES] } else { load_word(storage, N::NAMESPACE, word_index)? };
+
+        Ok(Ones {
+            storage,
+            ns: N::NAMESPACE,
+            word_index,
+            word,
+            bit: first % WORD_BITS,
+            last,
+            done
+        })
+    }
+
+    #[inline]
+    fn count_key() -> Vec<u8> {
+        [N::NAMESPACE, KEY_COUNT].concat()
+    }
+}
+
+impl<'storage> Iterator for Ones<'storage> {
+    type Item = StdResult<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let start = self.word_index * WORD_BITS;
+
+            if self.bit == WORD_BITS {
+                // Moving past the last word that can be indexed.
+                if self.word_index == u64::MAX / WORD_BITS {
+                    self.done = true;
+
+                    break;
+                }
+
+                self.word_index += 1;
+                self.bit = 0;
+
+                match load_word(self.storage, self.ns, self.word_index) {
+                    Ok(word) => self.word = word,
+                    Err(err) => {
+                        self.done = true;
+
+                        return Some(Err(err));
+                    }
+                }
+
+                continue;
+            }
+
+            let index = start + self.bit;
+
+            if index > self.last {
+                self.done = true;
+
+                break;
+            }
+
+            // Skip over bytes with no bits set.
+            if self.bit % 8 == 0 && self.word[(self.bit / 8) as usize] == 0 {
+                self.bit += 8;
+
+                continue;
+            }
+
+            self.bit += 1;
+
+            if is_set(&self.word, index - start) {
+                return Some(Ok(index));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the first and last index in the range or
+/// [`None`] if the range is empty.
+#[inline]
+fn resolve_range(range: impl RangeBounds<u64>) -> StdResult<Option<(u64, u64)>> {
+    let first = match range.start_bound() {
+        Bound::Included(x) => *x,
+        Bound::Excluded(x) => match x.checked_add(1) {
+            Some(x) => x,
+            None => return Ok(None)
+        },
+        Bound::Unbounded => 0
+    };
+
+    let last = match range.end_bound() {
+        Bound::Included(x) => *x,
+        Bound::Excluded(x) => match x.checked_sub(1) {
+            Some(x) => x,
+            None => return Ok(None)
+        },
+        Bound::Unbounded => return Err(StdError::generic_err(
+            "Bitmap: the range must have an end."
+        ))
+    };
+
+    if first > last {
+        return Ok(None);
+    }
+
+    Ok(Some((first, last)))
+}
+
+/// Returns the index of the word, the byte within the word and the mask for the bit.
+#[inline]
+fn position(index: u64) -> (u64, usize, u8) {
+    let bit = index % WORD_BITS;
+
+    (index / WORD_BITS, (bit / 8) as usize, 1 << (bit % 8))
+}
+
+#[inline]
+fn is_set(word: &Word, bit: u64) -> bool {
+    word[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+}
+
+#[inline]
+fn word_key(ns: &[u8], word_index: u64) -> Vec<u8> {
+    [ns, KEY_WORD, &word_index.to_be_bytes()].concat()
+}
+
+#[inline]
+fn load_word(storage: &dyn Storage, ns: &[u8], word_index: u64) -> StdResult<Word> {
+    let mut word = [0; WORD_BYTES];
+
+    if let Some(bytes) = storage.get(&word_key(ns, word_index)) {
+        if bytes.len() != WORD_BYTES {
+            return Err(StdError::generic_err(format!(
+                "Bitmap: expected a word of {} bytes but found {}.",
+                WORD_BYTES,
+                bytes.len()
+            )));
+        }
+
+        word.copy_from_slice(&bytes);
+    }
+
+    Ok(word)
+}
+
+#[inline]
+fn save_word(storage: &mut dyn Storage, ns: &[u8], word_index: u64, word: &Word) {
+    let key = word_key(ns, word_index);
+
+    if word.iter().all(|x| *x == 0) {
+        storage.remove(&key);
+    } else {
+        storage.set(&key, word);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    const BITMAP: Bitmap<TestNs> = Bitmap::new();
+
+    proptest! {
+        #[test]
+        fn proptest_bitmap(
+            ops in prop::collection::vec((0u64..2000, any::<bool>()), 1..200),
+            start in 0u64..2000,
+            len in 0u64..1000
+        ) {
+            let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+            let mut model = BTreeSet::new();
+
+            for (index, set) in ops {
+                if set {
+                    prop_assert_eq!(BITMAP.set(storage, index).unwrap(), model.insert(index));
+                } else {
+                    prop_assert_eq!(BITMAP.unset(storage, index).unwrap(), model.remove(&index));
+                }
+
+                prop_assert_eq!(BITMAP.get(storage, index).unwrap(), set);
+            }
+
+            prop_assert_eq!(BITMAP.count(storage).unwrap(), model.len() as u64);
+
+            let expected = model.range(start..start + len).copied().collect::<Vec<_>>();
+            let ones = BITMAP.ones(storage, start..start + len).unwrap().collect::<StdResult<Vec<_>>>().unwrap();
+
+            prop_assert_eq!(&ones, &expected);
+            prop_assert_eq!(BITMAP.count_ones(storage, start..start + len).unwrap(), expected.len() as u64);
+        }
+    }
+
+    #[test]
+    fn bounds() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        BITMAP.set(storage, 0).unwrap();
+        BITMAP.set(storage, u64::MAX).unwrap();
+
+        let ones = BITMAP.ones(storage, ..=10).unwrap().collect::<StdResult<Vec<_>>>().unwrap();
+        assert_eq!(ones, [0]);
+
+        let ones = BITMAP.ones(storage, u64::MAX - 300..=u64::MAX).unwrap().collect::<StdResult<Vec<_>>>().unwrap();
+        assert_eq!(ones, [u64::MAX]);
+        assert_eq!(BITMAP.count_ones(storage, u64::MAX - 1000..=u64::MAX).unwrap(), 1);
+        assert_eq!(BITMAP.count_ones(storage, 0..0).unwrap(), 0);
+        assert!(BITMAP.count_ones(storage, 0..).is_err());
+
+        // Empty words are removed from storage.
+        BITMAP.unset(storage, 0).unwrap();
+        assert_eq!(storage.get(&word_key(TestNs::NAMESPACE, 0)), None);
+        assert_eq!(BITMAP.count(storage).unwrap(), 1);
+    }
+
+    #[test]
+    fn invalid_word() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        BITMAP.set(storage, 3).unwrap();
+        storage.set(&word_key(TestNs::NAMESPACE, 1), &[1; 5]);
+
+        let err = StdError::generic_err("Bitmap: expected a word of 32 bytes but found 5.");
+
+        assert_eq!(BITMAP.get(storage, 300).unwrap_err(), err);
+        assert_eq!(BITMAP.set(storage, 300).unwrap_err(), err);
+        assert_eq!(BITMAP.count_ones(storage, 0..1000).unwrap_err(), err);
+
+        let mut ones = BITMAP.ones(storage, 0..1000).unwrap();
+        assert_eq!(ones.next(), Some(Ok(3)));
+        assert_eq!(ones.next(), Some(Err(err)));
+        assert_eq!(ones.next(), None);
+    }
+}
@@ -0,0 +1,811 @@
+//! A B+ tree of byte keys laid out across storage keys. It keeps keys
+//! sorted without relying on `Storage::range` which is unavailable on
+//! Secret Network. Only keys are stored in the tree - the storage types
+//! built on top of it store their values separately so that point
+//! lookups don't have to go through the tree at all.
+
+use std::mem;
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, Binary, StdResult, StdError}
+};
+
+/// The maximum number of keys in a single node. A node is split in
+/// two once it exceeds this. Bigger nodes mean fewer storage reads
+/// per operation but more bytes read and written each time.
+const MAX_KEYS: usize = 32;
+
+const KEY_META: &[u8] = b"meta";
+const KEY_NODE: &[u8] = b"node";
+
+/// The direction of iteration.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Order {
+    Ascending,
+    Descending
+}
+
+/// A bound of a range of keys.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Bound {
+    Inclusive(Vec<u8>),
+    Exclusive(Vec<u8>),
+    Unbounded
+}
+
+/// The internal nodes on the way to a leaf together
+/// with the index of the child that was taken in each one.
+type Path = Vec<(u64, Node, usize)>;
+
+pub(crate) struct BTree<'a> {
+    ns: &'a [u8]
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Default)]
+struct Meta {
+    /// Zero if the tree is empty.
+    root: u64,
+    next_id: u64,
+    len: u64
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize)]
+enum Node {
+    Leaf {
+        keys: Vec<Binary>,
+        /// Zero if this is the first leaf.
+        prev: u64,
+        /// Zero if this is the last leaf.
+        next: u64
+    },
+    Internal {
+        /// The first key of `children[i + 1]` is bigger or equal to `keys[i]`.
+        keys: Vec<Binary>,
+        children: Vec<u64>,
+        /// The number of keys in the subtree of each child.
+        counts: Vec<u64>
+    }
+}
+
+/// Iterates over the keys of a [`BTree`] within the given bounds,
+/// reading a single node each time it moves on to the next leaf.
+pub(crate) struct Keys<'storage> {
+    storage: &'storage dyn Storage,
+    ns: Vec<u8>,
+    order: Order,
+    /// The bound at which iteration stops.
+    end: Bound,
+    keys: Vec<Binary>,
+    /// The index of the next key for ascending iteration and
+    /// one past the index of the next key for descending.
+    pos: usize,
+    /// The leaf to move to once `keys` are exhausted.
+    sibling: u64,
+    is_done: bool
+}
+
+impl Bound {
+    /// Creates an inclusive bound with the bytes that `segment` writes.
+    #[inline]
+    pub fn inclusive(segment: &(impl super::Segment + ?Sized)) -> Self {
+        Self::Inclusive(segment_bytes(segment))
+    }
+
+    /// Creates an exclusive bound with the bytes that `segment` writes.
+    #[inline]
+    pub fn exclusive(segment: &(impl super::Segment + ?Sized)) -> Self {
+        Self::Exclusive(segment_bytes(segment))
+    }
+
+    /// Returns the pair of bounds that match all keys starting with `prefix`.
+    pub(crate) fn prefix(prefix: Vec<u8>) -> (Self, Self) {
+        let mut end = prefix.clone();
+
+        // The smallest key that is bigger than all keys starting with the prefix.
+        while let Some(last) = end.pop() {
+            if last < u8::MAX {
+                end.push(last + 1);
+
+                return (Self::Inclusive(prefix), Self::Exclusive(end));
+            }
+        }
+
+        (Self::Inclusive(prefix), Self::Unbounded)
+    }
+
+    /// Whether `key` is past this bound when it is used as
+    /// the end of a range iterated in the given order.
+    fn is_past(&self, key: &[u8], order: Order) -> bool {
+        match (self, order) {
+            (Self::Unbounded, _) => false,
+            (Self::Inclusive(bound), Order::Ascending) => key > bound.as_slice(),
+            (Self::Exclusive(bound), Order::Ascending) => key >= bound.as_slice(),
+            (Self::Inclusive(bound), Order::Descending) => key < bound.as_slice(),
+            (Self::Exclusive(bound), Order::Descending) => key <= bound.as_slice()
+        }
+    }
+}
+
+impl<'a> BTree<'a> {
+    /// The following keys are reserved under `ns`:
+    ///  * `ns` + "meta"
+    ///  * `ns` + "node" + n - where n is a number
+    #[inline]
+    pub fn new(ns: &'a [u8]) -> Self {
+        Self { ns }
+    }
+
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.load_meta(storage)?.len)
+    }
+
+    /// Returns `false` if the key was already present.
+    pub fn insert(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        let mut meta = self.load_meta(storage)?;
+
+        if meta.root == 0 {
+            meta.root = self.alloc(&mut meta);
+            meta.len = 1;
+
+            self.save_node(storage, meta.root, &Node::Leaf {
+                keys: vec![Binary(key.to_vec())],
+                prev: 0,
+                next: 0
+            })?;
+
+            return self.save_meta(storage, &meta).map(|_| true);
+        }
+
+        let (path, leaf_id, mut leaf) = self.find_leaf(storage, meta.root, Some(key))?;
+        let Node::Leaf { keys, next, .. } = &mut leaf else {
+            unreachable!()
+        };
+
+        let pos = match search(keys, key) {
+            Ok(_) => return Ok(false),
+            Err(pos) => pos
+        };
+
+        keys.insert(pos, Binary(key.to_vec()));
+        meta.len += 1;
+
+        // The separator, id and key count of a node split off from the current one.
+        let mut split: Option<(Binary, u64, u64)> = None;
+
+        if keys.len() > MAX_KEYS {
+            let right_id = self.alloc(&mut meta);
+            let right_keys = keys.split_off(keys.len() / 2);
+            let right_next = mem::replace(next, right_id);
+
+            if right_next != 0 {
+                self.update_leaf_link(storage, right_next, |prev, _| *prev = right_id)?;
+            }
+
+            split = Some((right_keys[0].clone(), right_id, right_keys.len() as u64));
+
+            self.save_node(storage, right_id, &Node::Leaf {
+                keys: right_keys,
+                prev: leaf_id,
+                next: right_next
+            })?;
+        }
+
+        self.save_node(storage, leaf_id, &leaf)?;
+
+        for (id, mut node, index) in path.into_iter().rev() {
+            let Node::Internal { keys, children, counts } = &mut node else {
+                unreachable!()
+            };
+
+            counts[index] += 1;
+
+            if let Some((separator, right_id, right_count)) = split.take() {
+                counts[index] -= right_count;
+                keys.insert(index, separator);
+                children.insert(index + 1, right_id);
+                counts.insert(index + 1, right_count);
+
+                if keys.len() > MAX_KEYS {
+                    let mid = keys.len() / 2;
+
+                    let right_keys = keys.split_off(mid + 1);
+                    let separator = keys.pop().unwrap();
+                    let right_children = children.split_off(mid + 1);
+                    let right_counts = counts.split_off(mid + 1);
+
+                    let right_id = self.alloc(&mut meta);
+                    split = Some((separator, right_id, right_counts.iter().sum()));
+
+                    self.save_node(storage, right_id, &Node::Internal {
+                        keys: right_keys,
+                        children: right_children,
+                        counts: right_counts
+                    })?;
+                }
+            }
+
+            self.save_node(storage, id, &node)?;
+        }
+
+        if let Some((separator, right_id, right_count)) = split {
+            let root = self.alloc(&mut meta);
+
+            self.save_node(storage, root, &Node::Internal {
+                keys: vec![separator],
+                children: vec![meta.root, right_id],
+                counts: vec![meta.len - right_count, right_count]
+            })?;
+
+            meta.root = root;
+        }
+
+        self.save_meta(storage, &meta).map(|_| true)
+    }
+
+    /// Returns `false` if the key wasn't present.
+    pub fn remove(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        let mut meta = self.load_meta(storage)?;
+
+        if meta.root == 0 {
+            return Ok(false);
+        }
+
+        let (path, leaf_id, mut leaf) = self.find_leaf(storage, meta.root, Some(key))?;
+        let Node::Leaf { keys, prev, next } = &mut leaf else {
+            unreachable!()
+        };
+
+        match search(keys, key) {
+            Ok(pos) => keys.remove(pos),
+            Err(_) => return Ok(false)
+        };
+
+        meta.len -= 1;
+
+        // Empty nodes are dropped instead of being merged with their siblings.
+        // This keeps removal cheap at the cost of potentially having some
+        // sparsely populated nodes.
+        let mut is_removed = keys.is_empty();
+
+        if is_removed {
+            let (prev, next) = (*prev, *next);
+
+            if prev != 0 {
+                self.update_leaf_link(storage, prev, |_, x| *x = next)?;
+            }
+
+            if next != 0 {
+                self.update_leaf_link(storage, next, |x, _| *x = prev)?;
+            }
+
+            self.remove_node(storage, leaf_id);
+        } else {
+            self.save_node(storage, leaf_id, &leaf)?;
+        }
+
+        for (id, mut node, index) in path.into_iter().rev() {
+            let Node::Internal { keys, children, counts } = &mut node else {
+                unreachable!()
+            };
+
+            if is_removed {
+                children.remove(index);
+                counts.remove(index);
+
+                if index > 0 {
+                    keys.remove(index - 1);
+                } else if !keys.is_empty() {
+                    keys.remove(0);
+                }
+            } else {
+                counts[index] -= 1;
+            }
+
+            is_removed = children.is_empty();
+
+            if is_removed {
+                self.remove_node(storage, id);
+            } else {
+                self.save_node(storage, id, &node)?;
+            }
+        }
+
+        if is_removed {
+            meta.root = 0;
+        }
+
+        // Shrink the tree while the root only has a single child.
+        while meta.root != 0 {
+            match self.load_node(storage, meta.root)? {
+                Node::Internal { children, .. } if children.len() == 1 => {
+                    self.remove_node(storage, meta.root);
+                    meta.root = children[0];
+                },
+                _ => break
+            }
+        }
+
+        self.save_meta(storage, &meta).map(|_| true)
+    }
+
+    /// Returns the number of keys that are smaller than `key`.
+    pub fn rank(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<u64> {
+        let meta = self.load_meta(storage)?;
+
+        if meta.root == 0 {
+            return Ok(0);
+        }
+
+        let mut rank = 0;
+        let mut id = meta.root;
+
+        loop {
+            match self.load_node(storage, id)? {
+                Node::Internal { keys, children, counts } => {
+                    let index = child_index(&keys, key);
+                    rank += counts[..index].iter().sum::<u64>();
+                    id = children[index];
+                },
+                Node::Leaf { keys, .. } => {
+                    rank += keys.partition_point(|x| x.as_slice() < key) as u64;
+
+                    return Ok(rank);
+                }
+            }
+        }
+    }
+
+    /// Returns the `n`-th smallest key.
+    pub fn nth(&self, storage: &dyn Storage, mut n: u64) -> StdResult<Option<Vec<u8>>> {
+        let meta = self.load_meta(storage)?;
+
+        if n >= meta.len {
+            return Ok(None);
+        }
+
+        let mut id = meta.root;
+
+        loop {
+            match self.load_node(storage, id)? {
+                Node::Internal { children, counts, .. } => {
+                    let mut index = 0;
+
+                    while n >= counts[index] {
+                        n -= counts[index];
+                        index += 1;
+                    }
+
+                    id = children[index];
+                },
+                Node::Leaf { mut keys, .. } => {
+                    return Ok(Some(keys.swap_remove(n as usize).0));
+                }
+            }
+        }
+    }
+
+    pub fn keys<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        min: Bound,
+        max: Bound,
+        order: Order
+    ) -> StdResult<Keys<'storage>> {
+        let (start, end) = match order {
+            Order::Ascending => (min, max),
+            Order::Descending => (max, min)
+        };
+
+        let mut iter = Keys {
+            storage,
+            ns: self.ns.to_vec(),
+            order,
+            end,
+            keys: vec![],
+            pos: 0,
+            sibling: 0,
+            is_done: true
+        };
+
+        let root = self.load_meta(storage)?.root;
+
+        if root == 0 {
+            return Ok(iter);
+        }
+
+        let start_key = match &start {
+            Bound::Inclusive(key) | Bound::Exclusive(key) => Some(key.as_slice()),
+            Bound::Unbounded => None
+        };
+
+        let (_, _, leaf) = match (start_key, order) {
+            (None, Order::Descending) => self.find_last_leaf(storage, root)?,
+            _ => self.find_leaf(storage, root, start_key)?
+        };
+
+        let Node::Leaf { keys, prev, next } = leaf else {
+            unreachable!()
+        };
+
+        iter.pos = match (&start, order) {
+            (Bound::Unbounded, Order::Ascending) => 0,
+            (Bound::Unbounded, Order::Descending) => keys.len(),
+            (Bound::Inclusive(key), Order::Ascending) => keys.partition_point(|x| x.as_slice() < key.as_slice()),
+            (Bound::Exclusive(key), Order::Ascending) => keys.partition_point(|x| x.as_slice() <= key.as_slice()),
+            (Bound::Inclusive(key), Order::Descending) => keys.partition_point(|x| x.as_slice() <= key.as_slice()),
+            (Bound::Exclusive(key), Order::Descending) => keys.partition_point(|x| x.as_slice() < key.as_slice())
+        };
+
+        iter.sibling = match order {
+            Order::Ascending => next,
+            Order::Descending => prev
+        };
+        iter.keys = keys;
+        iter.is_done = false;
+
+        Ok(iter)
+    }
+
+    /// Descends to the leaf that `key` belongs to or the first leaf if [`None`].
+    fn find_leaf(
+        &self,
+        storage: &dyn Storage,
+        root: u64,
+        key: Option<&[u8]>
+    ) -> StdResult<(Path, u64, Node)> {
+        let mut path = vec![];
+        let mut id = root;
+
+        loop {
+            let node = self.load_node(storage, id)?;
+
+            match &node {
+                Node::Internal { keys, children, .. } => {
+                    let index = key.map(|x| child_index(keys, x)).unwrap_or(0);
+                    let child = children[index];
+
+                    path.push((id, node, index));
+                    id = child;
+                },
+                Node::Leaf { .. } => return Ok((path, id, node))
+            }
+        }
+    }
+
+    fn find_last_leaf(
+        &self,
+        storage: &dyn Storage,
+        root: u64
+    ) -> StdResult<(Path, u64, Node)> {
+        let mut id = root;
+
+        loop {
+            match self.load_node(storage, id)? {
+                Node::Internal { children, .. } => id = *children.last().unwrap(),
+                leaf => return Ok((vec![], id, leaf))
+            }
+        }
+    }
+
+    fn update_leaf_link(
+        &self,
+        storage: &mut dyn Storage,
+        id: u64,
+        update: impl FnOnce(&mut u64, &mut u64)
+    ) -> StdResult<()> {
+        let mut node = self.load_node(storage, id)?;
+
+        if let Node::Leaf { prev, next, .. } = &mut node {
+            update(prev, next);
+        }
+
+        self.save_node(storage, id, &node)
+    }
+
+    #[inline]
+    fn alloc(&self, meta: &mut Meta) -> u64 {
+        meta.next_id += 1;
+
+        meta.next_id
+    }
+
+    #[inline]
+    fn load_meta(&self, storage: &dyn Storage) -> StdResult<Meta> {
+        let meta: Option<Meta> = super::load(storage, self.key_meta())?;
+
+        Ok(meta.unwrap_or_default())
+    }
+
+    #[inline]
+    fn save_meta(&self, storage: &mut dyn Storage, meta: &Meta) -> StdResult<()> {
+        super::save(storage, self.key_meta(), meta)
+    }
+
+    #[inline]
+    fn load_node(&self, storage: &dyn Storage, id: u64) -> StdResult<Node> {
+        load_node(storage, &node_key(self.ns, id))
+    }
+
+    #[inline]
+    fn save_node(&self, storage: &mut dyn Storage, id: u64, node: &Node) -> StdResult<()> {
+        super::save(storage, node_key(self.ns, id), node)
+    }
+
+    #[inline]
+    fn remove_node(&self, storage: &mut dyn Storage, id: u64) {
+        super::remove(storage, node_key(self.ns, id))
+    }
+
+    #[inline]
+    fn key_meta(&self) -> Vec<u8> {
+        [self.ns, KEY_META].concat()
+    }
+}
+
+impl<'storage> Iterator for Keys<'storage> {
+    type Item = StdResult<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        loop {
+            let has_key = match self.order {
+                Order::Ascending => self.pos < self.keys.len(),
+                Order::Descending => self.pos > 0
+            };
+
+            if has_key {
+                break;
+            }
+
+            if self.sibling == 0 {
+                self.is_done = true;
+
+                return None;
+            }
+
+            let node = load_node(self.storage, &node_key(&self.ns, self.sibling));
+            let Ok(Node::Leaf { keys, prev, next }) = node else {
+                self.is_done = true;
+
+                return Some(Err(node.err().unwrap_or_else(||
+                    StdError::generic_err("BTree: expected a leaf node.")
+                )));
+            };
+
+            (self.pos, self.sibling) = match self.order {
+                Order::Ascending => (0, next),
+                Order::Descending => (keys.len(), prev)
+            };
+            self.keys = keys;
+        }
+
+        let index = match self.order {
+            Order::Ascending => {
+                self.pos += 1;
+
+                self.pos - 1
+            },
+            Order::Descending => {
+                self.pos -= 1;
+
+                self.pos
+            }
+        };
+
+        let key = mem::take(&mut self.keys[index].0);
+
+        if self.end.is_past(&key, self.order) {
+            self.is_done = true;
+
+            return None;
+        }
+
+        Some(Ok(key))
+    }
+}
+
+/// The index of the child that `key` belongs to.
+#[inline]
+fn child_index(keys: &[Binary], key: &[u8]) -> usize {
+    keys.partition_point(|x| x.as_slice() <= key)
+}
+
+#[inline]
+fn search(keys: &[Binary], key: &[u8]) -> Result<usize, usize> {
+    keys.binary_search_by(|x| x.as_slice().cmp(key))
+}
+
+#[inline]
+fn load_node(storage: &dyn Storage, key: &[u8]) -> StdResult<Node> {
+    let node: Option<Node> = super::load(storage, key)?;
+
+    node.ok_or_else(|| StdError::generic_err("BTree: node not found."))
+}
+
+#[inline]
+fn node_key(ns: &[u8], id: u64) -> Vec<u8> {
+    [ns, KEY_NODE, &id.to_be_bytes()].concat()
+}
+
+#[inline]
+pub(crate) fn segment_bytes(segment: &(impl super::Segment + ?Sized)) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(segment.size());
+    segment.write_segment(&mut bytes);
+
+    bytes
+}
+
+/// Encodes the key so that it is terminated by two zero bytes and any
+/// zero bytes within it are escaped as `[0, 1]`. This keeps the order of
+/// the keys and ensures that no encoded key is a prefix of another, so
+/// that more bytes can be appended after it.
+pub(crate) fn encode(key: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(key.len() + 2);
+
+    for byte in key {
+        result.push(*byte);
+
+        if *byte == 0 {
+            result.push(1);
+        }
+    }
+
+    result.extend_from_slice(&[0, 0]);
+
+    result
+}
+
+/// The smallest encoded key that comes after all entries with the given encoded key.
+#[inline]
+pub(crate) fn after(mut encoded: Vec<u8>) -> Vec<u8> {
+    *encoded.last_mut().unwrap() = 1;
+
+    encoded
+}
+
+/// Splits bytes that start with an encoded key into
+/// the decoded key and the bytes that come after it.
+pub(crate) fn split_encoded(bytes: &[u8]) -> Option<(Vec<u8>, &[u8])> {
+    let mut key = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i + 1 < bytes.len() {
+        match (bytes[i], bytes[i + 1]) {
+            (0, 0) => return Some((key, &bytes[i + 2..])),
+            (0, _) => {
+                key.push(0);
+                i += 2;
+            },
+            (byte, _) => {
+                key.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::{prelude::*, collection::vec};
+
+    use super::*;
+    use crate::cosmwasm_std::testing::MockStorage;
+
+    fn collect(iter: Keys) -> Vec<Vec<u8>> {
+        iter.map(|x| x.unwrap()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_btree_matches_btree_set(
+            ops in vec((any::<bool>(), 0u16..600), 0..1500),
+            min in 0u16..600,
+            max in 0u16..600
+        ) {
+            let mut storage = MockStorage::new();
+            let tree = BTree::new(b"tree");
+            let mut model = BTreeSet::new();
+
+            for (insert, key) in ops {
+                let key = key.to_be_bytes().to_vec();
+
+                if insert {
+                    prop_assert_eq!(tree.insert(&mut storage, &key)?, model.insert(key));
+                } else {
+                    prop_assert_eq!(tree.remove(&mut storage, &key)?, model.remove(&key));
+                }
+            }
+
+            prop_assert_eq!(tree.len(&storage)?, model.len() as u64);
+
+            let all = model.iter().cloned().collect::<Vec<_>>();
+            let keys = tree.keys(&storage, Bound::Unbounded, Bound::Unbounded, Order::Ascending)?;
+            prop_assert_eq!(collect(keys), all.clone());
+
+            let keys = tree.keys(&storage, Bound::Unbounded, Bound::Unbounded, Order::Descending)?;
+            prop_assert_eq!(collect(keys), all.iter().rev().cloned().collect::<Vec<_>>());
+
+            let (min, max) = (min.to_be_bytes().to_vec(), max.to_be_bytes().to_vec());
+            let expected = model.iter()
+                .filter(|x| **x >= min && **x < max)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let keys = tree.keys(
+                &storage,
+                Bound::Inclusive(min.clone()),
+                Bound::Exclusive(max.clone()),
+                Order::Ascending
+            )?;
+            prop_assert_eq!(collect(keys), expected.clone());
+
+            let keys = tree.keys(
+                &storage,
+                Bound::Inclusive(min.clone()),
+                Bound::Exclusive(max),
+                Order::Descending
+            )?;
+            prop_assert_eq!(collect(keys), expected.into_iter().rev().collect::<Vec<_>>());
+
+            prop_assert_eq!(
+                tree.rank(&storage, &min)?,
+                model.iter().filter(|x| **x < min).count() as u64
+            );
+
+            for (i, key) in all.iter().enumerate() {
+                prop_assert_eq!(tree.nth(&storage, i as u64)?, Some(key.clone()));
+            }
+
+            prop_assert_eq!(tree.nth(&storage, all.len() as u64)?, None);
+        }
+    }
+
+    #[test]
+    fn removing_everything_clears_storage() {
+        let mut storage = MockStorage::new();
+        let tree = BTree::new(b"tree");
+
+        for i in 0..200u32 {
+            tree.insert(&mut storage, &i.to_be_bytes()).unwrap();
+        }
+
+        for i in 0..200u32 {
+            assert!(tree.remove(&mut storage, &i.to_be_bytes()).unwrap());
+        }
+
+        assert_eq!(tree.len(&storage).unwrap(), 0);
+
+        let keys = storage.range(None, None, crate::cosmwasm_std::Order::Ascending)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+
+        // Only the metadata is left.
+        assert_eq!(keys, vec![b"treemeta".to_vec()]);
+    }
+
+    #[test]
+    fn prefix_bounds() {
+        assert_eq!(
+            Bound::prefix(vec![1, 2]),
+            (Bound::Inclusive(vec![1, 2]), Bound::Exclusive(vec![1, 3]))
+        );
+        assert_eq!(
+            Bound::prefix(vec![1, 255]),
+            (Bound::Inclusive(vec![1, 255]), Bound::Exclusive(vec![2]))
+        );
+        assert_eq!(
+            Bound::prefix(vec![255]),
+            (Bound::Inclusive(vec![255]), Bound::Unbounded)
+        );
+    }
+}
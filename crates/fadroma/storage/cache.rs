@@ -0,0 +1,235 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap
+};
+
+use crate::cosmwasm_std::Storage;
+
+/// A [`Storage`] wrapper that caches reads and buffers writes and removals
+/// in memory. Repeated accesses to the same key only hit the underlying
+/// storage once and all changes are written to it in a single pass when
+/// calling [`CachedStorage::flush`]. Since all storage types in this module
+/// take `&mut dyn Storage` it can be passed to them in place of `deps.storage`.
+///
+/// Dropping the wrapper without flushing discards all buffered changes.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{Storage, testing::mock_dependencies},
+///     storage::{SingleItem, cache::{CachedStorage, CacheStats}}
+/// };
+///
+/// fadroma::namespace!(CounterNs, b"counter");
+/// const COUNTER: SingleItem<u64, CounterNs> = SingleItem::new();
+///
+/// let mut deps = mock_dependencies();
+/// let mut storage = CachedStorage::new(deps.as_mut().storage);
+///
+/// for _ in 0..10 {
+///     let count = COUNTER.load_or_default(&storage).unwrap();
+///     COUNTER.save(&mut storage, &(count + 1)).unwrap();
+/// }
+///
+/// let stats = storage.flush();
+/// assert_eq!(stats, CacheStats { hits: 9, misses: 1, writes: 10, flushed: 1 });
+/// assert_eq!(COUNTER.load(&deps.storage).unwrap(), Some(10));
+/// ```
+pub struct CachedStorage<'a> {
+    inner: &'a mut dyn Storage,
+    cache: RefCell<BTreeMap<Vec<u8>, Entry>>,
+    stats: Cell<CacheStats>
+}
+
+/// Statistics about the usage of [`CachedStorage`].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CacheStats {
+    /// Reads that were served from memory.
+    pub hits: u64,
+    /// Reads that had to go to the underlying storage.
+    pub misses: u64,
+    /// Writes and removals that were buffered.
+    pub writes: u64,
+    /// Keys that were written to or removed from the underlying storage when flushing.
+    pub flushed: u64
+}
+
+struct Entry {
+    value: Option<Vec<u8>>,
+    dirty: bool
+}
+
+impl<'a> CachedStorage<'a> {
+    #[inline]
+    pub fn new(inner: &'a mut dyn Storage) -> Self {
+        Self {
+            inner,
+            cache: RefCell::new(BTreeMap::new()),
+            stats: Cell::new(CacheStats::default())
+        }
+    }
+
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// Writes all buffered changes to the underlying storage.
+    /// Returns the final statistics.
+    pub fn flush(self) -> CacheStats {
+        let mut stats = self.stats.get();
+
+        for (key, entry) in self.cache.into_inner() {
+            if !entry.dirty {
+                continue;
+            }
+
+            match entry.value {
+                Some(value) => self.inner.set(&key, &value),
+                None => self.inner.remove(&key)
+            }
+
+            stats.flushed += 1;
+        }
+
+        stats
+    }
+
+    #[inline]
+    fn write(&mut self, key: &[u8], value: Option<Vec<u8>>) {
+        self.cache.get_mut().insert(key.to_vec(), Entry { value, dirty: true });
+
+        let mut stats = self.stats.get();
+        stats.writes += 1;
+        self.stats.set(stats);
+    }
+}
+
+impl<'a> Storage for CachedStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut stats = self.stats.get();
+        let mut cache = self.cache.borrow_mut();
+
+        let value = match cache.get(key) {
+            Some(entry) => {
+                stats.hits += 1;
+
+                entry.value.clone()
+            },
+            None => {
+                stats.misses += 1;
+
+                let value = self.inner.get(key);
+                cache.insert(key.to_vec(), Entry { value: value.clone(), dirty: false });
+
+                value
+            }
+        };
+
+        self.stats.set(stats);
+
+        value
+    }
+
+    #[inline]
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.write(key, Some(value.to_vec()));
+    }
+
+    #[inline]
+    fn remove(&mut self, key: &[u8]) {
+        self.write(key, None);
+    }
+
+    /// Merges the buffered changes with the range of the underlying storage.
+    /// Only meant for tests as iteration is not available on chain.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: crate::cosmwasm_std::Order
+    ) -> Box<dyn Iterator<Item = crate::cosmwasm_std::Record> + 'b> {
+        let mut records: BTreeMap<Vec<u8>, Vec<u8>> = self.inner
+            .range(start, end, crate::cosmwasm_std::Order::Ascending)
+            .collect();
+
+        let in_range = |key: &[u8]| start.map_or(true, |x| key >= x) && end.map_or(true, |x| key < x);
+
+        for (key, entry) in self.cache.borrow().iter() {
+            if !entry.dirty || !in_range(key) {
+                continue;
+            }
+
+            match &entry.value {
+                Some(value) => records.insert(key.clone(), value.clone()),
+                None => records.remove(key)
+            };
+        }
+
+        match order {
+            crate::cosmwasm_std::Order::Ascending => Box::new(records.into_iter()),
+            crate::cosmwasm_std::Order::Descending => Box::new(records.into_iter().rev())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::{Order, testing::MockStorage},
+        storage::{StaticKey, iterable::IterableStorage}
+    };
+
+    #[test]
+    fn buffers_until_flushed() {
+        let mut inner = MockStorage::new();
+        inner.set(b"a", b"1");
+        inner.set(b"b", b"2");
+
+        let mut storage = CachedStorage::new(&mut inner);
+        let mut items = IterableStorage::<u64, _>::new(StaticKey(b"items"));
+
+        for i in 0..5 {
+            items.push(&mut storage, &i).unwrap();
+        }
+
+        assert_eq!(items.get(&storage, 4).unwrap(), Some(4));
+
+        storage.set(b"a", b"3");
+        storage.remove(b"b");
+        storage.remove(b"c");
+        assert_eq!(storage.get(b"a"), Some(b"3".to_vec()));
+        assert_eq!(storage.get(b"b"), None);
+
+        let range = storage.range(Some(b"a"), Some(b"c"), Order::Descending).collect::<Vec<_>>();
+        assert_eq!(range, [(b"a".to_vec(), b"3".to_vec())]);
+
+        let stats = storage.flush();
+        // Only the length was read from the underlying storage.
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        // The 5 items, the length that was written 5 times and "a", "b" and "c".
+        assert_eq!(stats.writes, 13);
+        assert_eq!(stats.flushed, 9);
+
+        assert_eq!(inner.get(b"a"), Some(b"3".to_vec()));
+        assert_eq!(inner.get(b"b"), None);
+        let items = IterableStorage::<u64, _>::new(StaticKey(b"items"));
+        assert_eq!(items.len(&inner).unwrap(), 5);
+        assert_eq!(items.get(&inner, 2).unwrap(), Some(2));
+    }
+
+    #[test]
+    fn discards_when_dropped() {
+        let mut inner = MockStorage::new();
+
+        let mut storage = CachedStorage::new(&mut inner);
+        storage.set(b"a", b"1");
+        drop(storage);
+
+        assert_eq!(inner.get(b"a"), None);
+    }
+}
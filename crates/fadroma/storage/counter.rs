@@ -0,0 +1,684 @@
+//! Storage types for numbers which are only ever changed by adding to or
+//! subtracting from them, such as total supplies, transaction counters and
+//! fee pools, as well as [`RewardPerShare`] for distributing rewards
+//! proportionally to shares.
+//!
+//! Values which have not been stored yet are treated as zero.
+
+use std::marker::PhantomData;
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{
+        Storage, StdResult, StdError, OverflowError, OverflowOperation,
+        Uint64, Uint128, Uint256, Uint512
+    }
+};
+use super::{Namespace, Key};
+
+/// Implemented by the unsigned number types that can be stored in a [`Counter`].
+pub trait CounterValue: FadromaSerialize + FadromaDeserialize + Copy + Default + ToString {
+    fn one() -> Self;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+}
+
+/// A number stored under the given [`Namespace`] that is
+/// changed using checked or saturating arithmetic.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{Uint128, StdError, testing::mock_dependencies},
+///     storage::counter::Counter
+/// };
+///
+/// fadroma::namespace!(TotalSupplyNs, b"total_supply");
+/// const TOTAL_SUPPLY: Counter<Uint128, TotalSupplyNs> = Counter::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// assert_eq!(TOTAL_SUPPLY.add(storage, Uint128::new(100)).unwrap(), Uint128::new(100));
+/// assert_eq!(TOTAL_SUPPLY.sub(storage, Uint128::new(40)).unwrap(), Uint128::new(60));
+///
+/// // Checked operations fail and leave the value unchanged.
+/// let err = TOTAL_SUPPLY.sub(storage, Uint128::new(61)).unwrap_err();
+/// assert!(matches!(err, StdError::Overflow { .. }));
+///
+/// // Saturating operations return the amount that was actually added or subtracted.
+/// assert_eq!(TOTAL_SUPPLY.saturating_sub(storage, Uint128::new(61)).unwrap(), Uint128::new(60));
+/// assert_eq!(TOTAL_SUPPLY.load(storage).unwrap(), Uint128::zero());
+/// ```
+pub struct Counter<T: CounterValue, N: Namespace> {
+    value_data: PhantomData<T>,
+    ns_data: PhantomData<N>
+}
+
+/// Many counters stored under the given [`Namespace`], one per key.
+/// Supports the same operations as [`Counter`].
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::testing::mock_dependencies,
+///     storage::{TypedKey, counter::CounterSpace}
+/// };
+///
+/// fadroma::namespace!(VisitsNs, b"visits");
+/// const VISITS: CounterSpace<u32, VisitsNs, TypedKey<&str>> = CounterSpace::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// VISITS.increment(storage, &"home").unwrap();
+/// VISITS.increment(storage, &"home").unwrap();
+/// VISITS.increment(storage, &"about").unwrap();
+///
+/// assert_eq!(VISITS.load(storage, &"home").unwrap(), 2);
+/// assert_eq!(VISITS.load(storage, &"about").unwrap(), 1);
+/// assert_eq!(VISITS.load(storage, &"blog").unwrap(), 0);
+/// ```
+pub struct CounterSpace<T: CounterValue, N: Namespace, K: Key> {
+    value_data: PhantomData<T>,
+    ns_data: PhantomData<N>,
+    key_data: PhantomData<K>
+}
+
+/// Distributes rewards between accounts proportionally to the shares that they
+/// hold at the time of each distribution, e.g. staking rewards between stakers.
+/// Distributing, depositing, withdrawing and claiming all take constant time
+/// regardless of the number of accounts.
+///
+/// It keeps a global index of the rewards per share, scaled by 10^18, which
+/// grows with each distribution. Each account records the index at the time
+/// it was last updated, so the rewards it has earned since then are its shares
+/// multiplied by the difference. Amounts smaller than the precision of the index
+/// are lost to rounding. Rewards distributed while there are no shares are kept
+/// and added to the next distribution.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{Uint128, testing::mock_dependencies},
+///     storage::{TypedKey, counter::RewardPerShare}
+/// };
+///
+/// fadroma::namespace!(RewardsNs, b"rewards");
+/// const REWARDS: RewardPerShare<TypedKey<&str>, RewardsNs> = RewardPerShare::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// REWARDS.deposit(storage, &"alice", Uint128::new(100)).unwrap();
+/// REWARDS.deposit(storage, &"bob", Uint128::new(300)).unwrap();
+/// REWARDS.distribute(storage, Uint128::new(1000)).unwrap();
+///
+/// REWARDS.withdraw(storage, &"bob", Uint128::new(200)).unwrap();
+/// REWARDS.distribute(storage, Uint128::new(1000)).unwrap();
+///
+/// assert_eq!(REWARDS.claim(storage, &"alice").unwrap(), Uint128::new(750));
+/// assert_eq!(REWARDS.claim(storage, &"bob").unwrap(), Uint128::new(1250));
+/// assert_eq!(REWARDS.pending(storage, &"bob").unwrap(), Uint128::zero());
+/// ```
+pub struct RewardPerShare<K: Key, N: Namespace> {
+    key_data: PhantomData<K>,
+    ns_data: PhantomData<N>
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Default)]
+struct RewardPool {
+    total_shares: Uint128,
+    index: Uint256,
+    undistributed: Uint128
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Default)]
+struct RewardAccount {
+    shares: Uint128,
+    index: Uint256,
+    pending: Uint128
+}
+
+const KEY_POOL: &[u8] = b"pool";
+const KEY_ACCOUNT: &[u8] = b"account";
+const INDEX_SCALE: Uint256 = Uint256::from_u128(1_000_000_000_000_000_000);
+
+impl<T: CounterValue, N: Namespace> Counter<T, N> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            value_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Returns the stored value or zero if there is none.
+    #[inline]
+    pub fn load(&self, storage: &dyn Storage) -> StdResult<T> {
+        load(storage, N::NAMESPACE)
+    }
+
+    #[inline]
+    pub fn save(&self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
+        super::save(storage, N::NAMESPACE, value)
+    }
+
+    /// Adds one and returns the new value.
+    #[inline]
+    pub fn increment(&self, storage: &mut dyn Storage) -> StdResult<T> {
+        add(storage, N::NAMESPACE, T::one())
+    }
+
+    /// Subtracts one and returns the new value.
+    #[inline]
+    pub fn decrement(&self, storage: &mut dyn Storage) -> StdResult<T> {
+        sub(storage, N::NAMESPACE, T::one())
+    }
+
+    /// Adds the amount and returns the new value.
+    /// Returns an error if the result overflows.
+    #[inline]
+    pub fn add(&self, storage: &mut dyn Storage, amount: T) -> StdResult<T> {
+        add(storage, N::NAMESPACE, amount)
+    }
+
+    /// Subtracts the amount and returns the new value.
+    /// Returns an error if the result underflows.
+    #[inline]
+    pub fn sub(&self, storage: &mut dyn Storage, amount: T) -> StdResult<T> {
+        sub(storage, N::NAMESPACE, amount)
+    }
+
+    /// Adds the amount, saturating at the maximum value, and returns the
+    /// amount that was actually added.
+    #[inline]
+    pub fn saturating_add(&self, storage: &mut dyn Storage, amount: T) -> StdResult<T> {
+        saturating_add(storage, N::NAMESPACE, amount)
+    }
+
+    /// Subtracts the amount, saturating at zero, and returns the
+    /// amount that was actually subtracted.
+    #[inline]
+    pub fn saturating_sub(&self, storage: &mut dyn Storage, amount: T) -> StdResult<T> {
+        saturating_sub(storage, N::NAMESPACE, amount)
+    }
+}
+
+impl<T: CounterValue, N: Namespace, K: Key> CounterSpace<T, N, K> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            value_data: PhantomData,
+            ns_data: PhantomData,
+            key_data: PhantomData
+        }
+    }
+
+    /// Returns the stored value or zero if there is none.
+    #[inline]
+    pub fn load(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<T> {
+        load(storage, Self::key(key))
+    }
+
+    #[inline]
+    pub fn save(&self, storage: &mut dyn Storage, key: impl Into<K>, value: &T) -> StdResult<()> {
+        super::save(storage, Self::key(key), value)
+    }
+
+    #[inline]
+    pub fn remove(&self, storage: &mut dyn Storage, key: impl Into<K>) {
+        storage.remove(&Self::key(key));
+    }
+
+    /// Adds one and returns the new value.
+    #[inline]
+    pub fn increment(&self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<T> {
+        add(storage, Self::key(key), T::one())
+    }
+
+    /// Subtracts one and returns the new value.
+    #[inline]
+    pub fn decrement(&self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<T> {
+        sub(storage, Self::key(key), T::one())
+    }
+
+    /// Adds the amount and returns the new value.
+    /// Returns an error if the result overflows.
+    #[inline]
+    pub fn add(&self, storage: &mut dyn Storage, key: impl Into<K>, amount: T) -> StdResult<T> {
+        add(storage, Self::key(key), amount)
+    }
+
+    /// Subtracts the amount and returns the new value.
+    /// Returns an error if the result underflows.
+    #[inline]
+    pub fn sub(&self, storage: &mut dyn Storage, key: impl Into<K>, amount: T) -> StdResult<T> {
+        sub(storage, Self::key(key), amount)
+    }
+
+    /// Adds the amount, saturating at the maximum value, and returns the
+    /// amount that was actually added.
+    #[inline]
+    pub fn saturating_add(&self, storage: &mut dyn Storage, key: impl Into<K>, amount: T) -> StdResult<T> {
+        saturating_add(storage, Self::key(key), amount)
+    }
+
+    /// Subtracts the amount, saturating at zero, and returns the
+    /// amount that was actually subtracted.
+    #[inline]
+    pub fn saturating_sub(&self, storage: &mut dyn Storage, key: impl Into<K>, amount: T) -> StdResult<T> {
+        saturating_sub(storage, Self::key(key), amount)
+    }
+
+    #[inline]
+    fn key(key: impl Into<K>) -> Vec<u8> {
+        let key = key.into();
+
+        let mut bytes = Vec::with_capacity(N::NAMESPACE.len() + key.size());
+        bytes.extend_from_slice(N::NAMESPACE);
+        key.write_segments(&mut bytes);
+
+        bytes
+    }
+}
+
+impl<K: Key, N: Namespace> RewardPerShare<K, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `RewardPerShare`:
+    ///  * N + "pool"
+    ///  * N + "account" + K
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            key_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Distributes the amount between all current shares.
+    pub fn distribute(&self, storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+        let mut pool = self.load_pool(storage)?;
+        let amount = amount.checked_add(pool.undistributed)?;
+
+        if pool.total_shares.is_zero() {
+            pool.undistributed = amount;
+        } else {
+            let increase = Uint256::from(amount).checked_mul(INDEX_SCALE)? /
+                Uint256::from(pool.total_shares);
+
+            pool.index = pool.index.checked_add(increase)?;
+            pool.undistributed = Uint128::zero();
+        }
+
+        super::save(storage, Self::pool_key(), &pool)
+    }
+
+    /// Adds shares to the account. Rewards are only
+    /// earned from distributions that happen after this.
+    pub fn deposit(
+        &self,
+        storage: &mut dyn Storage,
+        account: impl Into<K>,
+        shares: Uint128
+    ) -> StdResult<()> {
+        let key = Self::account_key(account);
+
+        let mut pool = self.load_pool(storage)?;
+        let mut account = Self::load_account(storage, &pool, &key)?;
+
+        account.shares = account.shares.checked_add(shares)?;
+        pool.total_shares = pool.total_shares.checked_add(shares)?;
+
+        super::save(storage, key, &account)?;
+        super::save(storage, Self::pool_key(), &pool)
+    }
+
+    /// Removes shares from the account. Rewards earned
+    /// until now can still be claimed afterwards.
+    pub fn withdraw(
+        &self,
+        storage: &mut dyn Storage,
+        account: impl Into<K>,
+        shares: Uint128
+    ) -> StdResult<()> {
+        let key = Self::account_key(account);
+
+        let mut pool = self.load_pool(storage)?;
+        let mut account = Self::load_account(storage, &pool, &key)?;
+
+        account.shares = account.shares.checked_sub(shares)?;
+        pool.total_shares = pool.total_shares.checked_sub(shares)?;
+
+        super::save(storage, key, &account)?;
+        super::save(storage, Self::pool_key(), &pool)
+    }
+
+    /// Returns the rewards earned by the account and resets them to zero.
+    pub fn claim(&self, storage: &mut dyn Storage, account: impl Into<K>) -> StdResult<Uint128> {
+        let key = Self::account_key(account);
+
+        let pool = self.load_pool(storage)?;
+        let mut account = Self::load_account(storage, &pool, &key)?;
+
+        let amount = account.pending;
+        account.pending = Uint128::zero();
+
+        if account.shares.is_zero() {
+            storage.remove(&key);
+        } else {
+            super::save(storage, key, &account)?;
+        }
+
+        Ok(amount)
+    }
+
+    /// Returns the rewards earned by the account that haven't been claimed yet.
+    #[inline]
+    pub fn pending(&self, storage: &dyn Storage, account: impl Into<K>) -> StdResult<Uint128> {
+        let pool = self.load_pool(storage)?;
+        let account = Self::load_account(storage, &pool, &Self::account_key(account))?;
+
+        Ok(account.pending)
+    }
+
+    #[inline]
+    pub fn shares(&self, storage: &dyn Storage, account: impl Into<K>) -> StdResult<Uint128> {
+        let account: Option<RewardAccount> = super::load(storage, Self::account_key(account))?;
+
+        Ok(account.map(|x| x.shares).unwrap_or_default())
+    }
+
+    #[inline]
+    pub fn total_shares(&self, storage: &dyn Storage) -> StdResult<Uint128> {
+        Ok(self.load_pool(storage)?.total_shares)
+    }
+
+    #[inline]
+    fn load_pool(&self, storage: &dyn Storage) -> StdResult<RewardPool> {
+        let pool: Option<RewardPool> = super::load(storage, Self::pool_key())?;
+
+        Ok(pool.unwrap_or_default())
+    }
+
+    /// Loads the account and adds the rewards earned since it was last updated.
+    fn load_account(storage: &dyn Storage, pool: &RewardPool, key: &[u8]) -> StdResult<RewardAccount> {
+        let account: Option<RewardAccount> = super::load(storage, key)?;
+        let Some(mut account) = account else {
+            return Ok(RewardAccount {
+                index: pool.index,
+                ..RewardAccount::default()
+            });
+        };
+
+        let earned = Uint256::from(account.shares)
+            .checked_mul(pool.index - account.index)? / INDEX_SCALE;
+        let earned = Uint128::try_from(earned).map_err(|_|
+            StdError::generic_err("RewardPerShare: the earned rewards overflow.")
+        )?;
+
+        account.pending = account.pending.checked_add(earned)?;
+        account.index = pool.index;
+
+        Ok(account)
+    }
+
+    #[inline]
+    fn pool_key() -> Vec<u8> {
+        [N::NAMESPACE, KEY_POOL].concat()
+    }
+
+    #[inline]
+    fn account_key(account: impl Into<K>) -> Vec<u8> {
+        let account = account.into();
+
+        let mut bytes = Vec::with_capacity(N::NAMESPACE.len() + KEY_ACCOUNT.len() + account.size());
+        bytes.extend_from_slice(N::NAMESPACE);
+        bytes.extend_from_slice(KEY_ACCOUNT);
+        account.write_segments(&mut bytes);
+
+        bytes
+    }
+}
+
+#[inline]
+fn load<T: CounterValue>(storage: &dyn Storage, key: impl AsRef<[u8]>) -> StdResult<T> {
+    let value: Option<T> = super::load(storage, key)?;
+
+    Ok(value.unwrap_or_default())
+}
+
+#[inline]
+fn add<T: CounterValue>(storage: &mut dyn Storage, key: impl AsRef<[u8]>, amount: T) -> StdResult<T> {
+    let value: T = load(storage, &key)?;
+    let value = value.checked_add(amount).ok_or_else(||
+        OverflowError::new(OverflowOperation::Add, value, amount)
+    )?;
+
+    super::save(storage, key, &value)?;
+
+    Ok(value)
+}
+
+#[inline]
+fn sub<T: CounterValue>(storage: &mut dyn Storage, key: impl AsRef<[u8]>, amount: T) -> StdResult<T> {
+    let value: T = load(storage, &key)?;
+    let value = value.checked_sub(amount).ok_or_else(||
+        OverflowError::new(OverflowOperation::Sub, value, amount)
+    )?;
+
+    super::save(storage, key, &value)?;
+
+    Ok(value)
+}
+
+#[inline]
+fn saturating_add<T: CounterValue>(storage: &mut dyn Storage, key: impl AsRef<[u8]>, amount: T) -> StdResult<T> {
+    let value: T = load(storage, &key)?;
+    let new_value = value.saturating_add(amount);
+
+    super::save(storage, key, &new_value)?;
+
+    Ok(new_value.saturating_sub(value))
+}
+
+#[inline]
+fn saturating_sub<T: CounterValue>(storage: &mut dyn Storage, key: impl AsRef<[u8]>, amount: T) -> StdResult<T> {
+    let value: T = load(storage, &key)?;
+    let new_value = value.saturating_sub(amount);
+
+    super::save(storage, key, &new_value)?;
+
+    Ok(value.saturating_sub(new_value))
+}
+
+macro_rules! impl_counter_value {
+    ($data:ty, $one:expr) => {
+        impl CounterValue for $data {
+            #[inline]
+            fn one() -> Self {
+                $one
+            }
+
+            #[inline]
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$data>::checked_add(self, rhs).ok()
+            }
+
+            #[inline]
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$data>::checked_sub(self, rhs).ok()
+            }
+
+            #[inline]
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$data>::saturating_add(self, rhs)
+            }
+
+            #[inline]
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$data>::saturating_sub(self, rhs)
+            }
+        }
+    };
+    ($data:ty) => {
+        impl CounterValue for $data {
+            #[inline]
+            fn one() -> Self {
+                1
+            }
+
+            #[inline]
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$data>::checked_add(self, rhs)
+            }
+
+            #[inline]
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$data>::checked_sub(self, rhs)
+            }
+
+            #[inline]
+            fn saturating_add(self, rhs: Self) -> Self {
+                <$data>::saturating_add(self, rhs)
+            }
+
+            #[inline]
+            fn saturating_sub(self, rhs: Self) -> Self {
+                <$data>::saturating_sub(self, rhs)
+            }
+        }
+    };
+}
+
+impl_counter_value!(u8);
+impl_counter_value!(u16);
+impl_counter_value!(u32);
+impl_counter_value!(u64);
+impl_counter_value!(u128);
+impl_counter_value!(Uint64, Uint64::one());
+impl_counter_value!(Uint128, Uint128::one());
+impl_counter_value!(Uint256, Uint256::one());
+impl_counter_value!(Uint512, Uint512::one());
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    const COUNTER: Counter<u8, TestNs> = Counter::new();
+    const REWARDS: RewardPerShare<TypedKey<u8>, TestNs> = RewardPerShare::new();
+
+    #[test]
+    fn counter_bounds() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        assert!(COUNTER.decrement(storage).is_err());
+        assert_eq!(COUNTER.increment(storage).unwrap(), 1);
+        assert_eq!(COUNTER.add(storage, 254).unwrap(), 255);
+
+        let err = COUNTER.increment(storage).unwrap_err();
+        assert_eq!(err, OverflowError::new(OverflowOperation::Add, 255, 1).into());
+        assert_eq!(COUNTER.load(storage).unwrap(), 255);
+
+        assert_eq!(COUNTER.saturating_add(storage, 10).unwrap(), 0);
+        assert_eq!(COUNTER.saturating_sub(storage, 5).unwrap(), 5);
+        assert_eq!(COUNTER.load(storage).unwrap(), 250);
+        assert_eq!(COUNTER.saturating_sub(storage, 255).unwrap(), 250);
+        assert_eq!(COUNTER.saturating_add(storage, 10).unwrap(), 10);
+        assert_eq!(COUNTER.sub(storage, 10).unwrap(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_reward_per_share(ops in prop::collection::vec((0u8..4, 0u8..3, 1u64..1_000_000), 1..100)) {
+            let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+            let mut shares = [0u128; 4];
+            let mut claimed = 0u128;
+            let mut distributed = 0u128;
+            let mut undistributed = 0u128;
+
+            for (account, op, amount) in ops {
+                let amount = amount as u128;
+
+                match op {
+                    0 => {
+                        REWARDS.deposit(storage, &account, Uint128::new(amount)).unwrap();
+                        shares[account as usize] += amount;
+                    }
+                    1 => {
+                        let amount = amount.min(shares[account as usize]);
+                        REWARDS.withdraw(storage, &account, Uint128::new(amount)).unwrap();
+                        shares[account as usize] -= amount;
+                    }
+                    _ => {
+                        let total = shares.iter().sum::<u128>();
+                        let pending = (0..4u8)
+                            .map(|x| REWARDS.pending(storage, &x).unwrap().u128())
+                            .collect::<Vec<_>>();
+
+                        REWARDS.distribute(storage, Uint128::new(amount)).unwrap();
+
+                        if total == 0 {
+                            undistributed += amount;
+                        } else {
+                            let amount = amount + undistributed;
+                            undistributed = 0;
+                            distributed += amount;
+
+                            // Each account receives its proportion, give or take rounding.
+                            for x in 0..4u8 {
+                                let earned = REWARDS.pending(storage, &x).unwrap().u128() - pending[x as usize];
+                                let expected = amount * shares[x as usize] / total;
+
+                                prop_assert!(earned.abs_diff(expected) <= 1);
+                            }
+                        }
+
+                        claimed += REWARDS.claim(storage, &account).unwrap().u128();
+                    }
+                }
+
+                prop_assert_eq!(REWARDS.shares(storage, &account).unwrap().u128(), shares[account as usize]);
+                prop_assert_eq!(REWARDS.total_shares(storage).unwrap().u128(), shares.iter().sum::<u128>());
+            }
+
+            for x in 0..4u8 {
+                claimed += REWARDS.claim(storage, &x).unwrap().u128();
+            }
+
+            prop_assert!(claimed <= distributed && distributed - claimed <= 4 * 100);
+        }
+    }
+
+    #[test]
+    fn rewards_without_shares_are_kept() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        REWARDS.distribute(storage, Uint128::new(100)).unwrap();
+        REWARDS.deposit(storage, &1, Uint128::new(10)).unwrap();
+        REWARDS.distribute(storage, Uint128::new(50)).unwrap();
+
+        assert_eq!(REWARDS.claim(storage, &1).unwrap(), Uint128::new(150));
+
+        REWARDS.withdraw(storage, &1, Uint128::new(10)).unwrap();
+        assert!(REWARDS.withdraw(storage, &1, Uint128::new(1)).is_err());
+        assert_eq!(REWARDS.claim(storage, &1).unwrap(), Uint128::zero());
+        assert_eq!(storage.get(&RewardPerShare::<TypedKey<u8>, TestNs>::account_key(&1)), None);
+    }
+}
@@ -0,0 +1,593 @@
+//! A double-ended queue whose items are stored under individual keys.
+//! Pushing and popping at either end only touches the item and a small
+//! metadata entry, so it suits queues such as withdrawal or unbonding
+//! requests that are processed in order.
+
+use std::{mem, marker::PhantomData};
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult, StdError}
+};
+use super::Key;
+
+const KEY_META: &[u8] = b"meta";
+
+/// Stores items in a double-ended queue. Items can be pushed to and
+/// popped from both ends in constant time (unlike [`super::iterable::IterableStorage`]
+/// which can only remove from the end) and accessed by their index which
+/// is always relative to the front of the queue.
+///
+/// # Examples
+///
+/// ```
+/// # use fadroma::storage::{deque::Deque, CompositeKey};
+/// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let key = CompositeKey::new(&[b"withdrawals"]);
+/// let mut queue = Deque::<u64, _>::new(key);
+///
+/// queue.push_back(storage, &1)?;
+/// queue.push_back(storage, &2)?;
+/// queue.push_front(storage, &0)?;
+///
+/// assert_eq!(queue.get(storage, 0)?, Some(0));
+/// assert_eq!(queue.pop_front(storage)?, Some(0));
+/// assert_eq!(queue.pop_front(storage)?, Some(1));
+/// assert_eq!(queue.pop_back(storage)?, Some(2));
+/// assert_eq!(queue.pop_back(storage)?, None);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Deque<T: FadromaSerialize + FadromaDeserialize, K: Key> {
+    ns: K,
+    meta: Option<DequeMeta>,
+    data: PhantomData<T>
+}
+
+/// Stores up to a fixed number of items. Once full, pushing a new
+/// item overwrites the oldest one. Useful for bounded histories such
+/// as recent prices or transaction logs.
+///
+/// # Examples
+///
+/// ```
+/// # use fadroma::storage::{deque::RingBuffer, CompositeKey};
+/// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let key = CompositeKey::new(&[b"prices"]);
+/// let mut prices = RingBuffer::<u64, _>::new(key, 3);
+///
+/// for price in 1..=5 {
+///     prices.push(storage, &price)?;
+/// }
+///
+/// let stored = prices.iter(storage)?.collect::<StdResult<Vec<u64>>>()?;
+/// assert_eq!(stored, [3, 4, 5]);
+/// assert_eq!(prices.latest(storage)?, Some(5));
+/// # Ok(())
+/// # }
+/// ```
+pub struct RingBuffer<T: FadromaSerialize + FadromaDeserialize, K: Key> {
+    ns: K,
+    capacity: u64,
+    meta: Option<RingMeta>,
+    data: PhantomData<T>
+}
+
+/// [`Deque`] and [`RingBuffer`] iterator. Iterates over values from the front to the back.
+pub struct Iter<'storage, T: FadromaDeserialize> {
+    storage: &'storage dyn Storage,
+    ns: Vec<u8>,
+    head: u64,
+    /// Zero if the positions wrap around at [`u64::MAX`].
+    capacity: u64,
+    current: u64,
+    end: u64,
+    result: PhantomData<T>
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Clone, Copy, Default)]
+struct DequeMeta {
+    head: u64,
+    len: u64
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Clone, Copy)]
+struct RingMeta {
+    capacity: u64,
+    head: u64,
+    len: u64
+}
+
+impl<T: FadromaSerialize + FadromaDeserialize, K: Key> Deque<T, K> {
+    const ERR_MSG: &str = "Deque: index out of bounds.";
+
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `Deque`:
+    ///  * `ns` + "meta"
+    ///  * `ns` + n - where n is a number
+    #[inline]
+    pub fn new(ns: K) -> Self {
+        Self {
+            ns,
+            meta: None,
+            data: PhantomData
+        }
+    }
+
+    /// Returns the number of elements currently stored.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.meta(storage)?.len)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    pub fn push_back(&mut self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+
+        super::save(storage, slot_key(&self.ns, meta.head.wrapping_add(meta.len)), value)?;
+        meta.len += 1;
+
+        self.save_meta(storage, meta)
+    }
+
+    pub fn push_front(&mut self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+
+        meta.head = meta.head.wrapping_sub(1);
+        meta.len += 1;
+        super::save(storage, slot_key(&self.ns, meta.head), value)?;
+
+        self.save_meta(storage, meta)
+    }
+
+    /// Removes and returns the element at the back or [`None`] if empty.
+    pub fn pop_back(&mut self, storage: &mut dyn Storage) -> StdResult<Option<T>> {
+        let mut meta = self.meta(storage)?;
+
+        if meta.len == 0 {
+            return Ok(None);
+        }
+
+        meta.len -= 1;
+        let value = self.take(storage, meta.head.wrapping_add(meta.len))?;
+
+        self.save_meta(storage, meta)?;
+
+        Ok(Some(value))
+    }
+
+    /// Removes and returns the element at the front or [`None`] if empty.
+    pub fn pop_front(&mut self, storage: &mut dyn Storage) -> StdResult<Option<T>> {
+        let mut meta = self.meta(storage)?;
+
+        if meta.len == 0 {
+            return Ok(None);
+        }
+
+        let value = self.take(storage, meta.head)?;
+        meta.head = meta.head.wrapping_add(1);
+        meta.len -= 1;
+
+        self.save_meta(storage, meta)?;
+
+        Ok(Some(value))
+    }
+
+    /// Returns the element at the given index, counting from the
+    /// front, or [`None`] if the index is out of bounds.
+    pub fn get(&self, storage: &dyn Storage, index: u64) -> StdResult<Option<T>> {
+        let meta = self.meta(storage)?;
+
+        if index >= meta.len {
+            return Ok(None);
+        }
+
+        super::load(storage, slot_key(&self.ns, meta.head.wrapping_add(index)))
+    }
+
+    #[inline]
+    pub fn front(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        self.get(storage, 0)
+    }
+
+    #[inline]
+    pub fn back(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        let len = self.len(storage)?;
+
+        match len {
+            0 => Ok(None),
+            len => self.get(storage, len - 1)
+        }
+    }
+
+    /// Overwrites the value at the given index.
+    /// Returns an error if the index is out of bounds.
+    pub fn set(&mut self, storage: &mut dyn Storage, index: u64, value: &T) -> StdResult<()> {
+        let meta = self.meta(storage)?;
+
+        if index >= meta.len {
+            return Err(StdError::generic_err(Self::ERR_MSG));
+        }
+
+        super::save(storage, slot_key(&self.ns, meta.head.wrapping_add(index)), value)
+    }
+
+    /// Returns an iterator that goes through the stored
+    /// elements from the front to the back.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage
+    ) -> StdResult<Iter<'storage, T>> {
+        let meta = self.meta(storage)?;
+
+        Ok(Iter::new(storage, &self.ns, meta.head, 0, meta.len))
+    }
+
+    fn take(&self, storage: &mut dyn Storage, slot: u64) -> StdResult<T> {
+        let key = slot_key(&self.ns, slot);
+
+        let value = super::load(storage, &key)?
+            .ok_or_else(|| StdError::generic_err(Self::ERR_MSG))?;
+        super::remove(storage, key);
+
+        Ok(value)
+    }
+
+    fn meta(&self, storage: &dyn Storage) -> StdResult<DequeMeta> {
+        if let Some(meta) = self.meta {
+            return Ok(meta);
+        }
+
+        let meta: Option<DequeMeta> = super::load(storage, meta_key(&self.ns))?;
+
+        Ok(meta.unwrap_or_default())
+    }
+
+    #[inline]
+    fn save_meta(&mut self, storage: &mut dyn Storage, meta: DequeMeta) -> StdResult<()> {
+        super::save(storage, meta_key(&self.ns), &meta)?;
+        self.meta = Some(meta);
+
+        Ok(())
+    }
+}
+
+impl<T: FadromaSerialize + FadromaDeserialize, K: Key> RingBuffer<T, K> {
+    /// Creates an instance for the given namespace that holds up to `capacity`
+    /// items. The capacity is stored on the first push and using a different
+    /// one with the same namespace afterwards results in an error.
+    /// The following namespaces are reserved by `RingBuffer`:
+    ///  * `ns` + "meta"
+    ///  * `ns` + n - where n is a number
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    #[inline]
+    pub fn new(ns: K, capacity: u64) -> Self {
+        assert!(capacity > 0, "RingBuffer: capacity must be bigger than 0.");
+
+        Self {
+            ns,
+            capacity,
+            meta: None,
+            data: PhantomData
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the number of elements currently stored.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.meta(storage)?.len)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Adds an element, overwriting the oldest one if the buffer is full.
+    pub fn push(&mut self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+
+        let slot = (meta.head + meta.len) % meta.capacity;
+        super::save(storage, slot_key(&self.ns, slot), value)?;
+
+        if meta.len == meta.capacity {
+            meta.head = (meta.head + 1) % meta.capacity;
+        } else {
+            meta.len += 1;
+        }
+
+        super::save(storage, meta_key(&self.ns), &meta)?;
+        self.meta = Some(meta);
+
+        Ok(())
+    }
+
+    /// Returns the element at the given index, counting from the oldest
+    /// one, or [`None`] if the index is out of bounds.
+    pub fn get(&self, storage: &dyn Storage, index: u64) -> StdResult<Option<T>> {
+        let meta = self.meta(storage)?;
+
+        if index >= meta.len {
+            return Ok(None);
+        }
+
+        super::load(storage, slot_key(&self.ns, (meta.head + index) % meta.capacity))
+    }
+
+    /// Returns the most recently pushed element.
+    #[inline]
+    pub fn latest(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        let len = self.len(storage)?;
+
+        match len {
+            0 => Ok(None),
+            len => self.get(storage, len - 1)
+        }
+    }
+
+    /// Returns an iterator that goes through the stored
+    /// elements from the oldest to the most recent one.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage
+    ) -> StdResult<Iter<'storage, T>> {
+        let meta = self.meta(storage)?;
+
+        Ok(Iter::new(storage, &self.ns, meta.head, meta.capacity, meta.len))
+    }
+
+    fn meta(&self, storage: &dyn Storage) -> StdResult<RingMeta> {
+        if let Some(meta) = self.meta {
+            return Ok(meta);
+        }
+
+        let meta: Option<RingMeta> = super::load(storage, meta_key(&self.ns))?;
+        match meta {
+            Some(meta) if meta.capacity != self.capacity => Err(StdError::generic_err(format!(
+                "RingBuffer: expected a capacity of {} but the stored one is {}.",
+                self.capacity,
+                meta.capacity
+            ))),
+            Some(meta) => Ok(meta),
+            None => Ok(RingMeta {
+                capacity: self.capacity,
+                head: 0,
+                len: 0
+            })
+        }
+    }
+}
+
+impl<'storage, T: FadromaDeserialize> Iter<'storage, T> {
+    fn new<K: Key>(
+        storage: &'storage dyn Storage,
+        ns: &K,
+        head: u64,
+        capacity: u64,
+        len: u64
+    ) -> Self {
+        let mut key = Vec::with_capacity(ns.size() + mem::size_of::<u64>());
+        ns.write_segments(&mut key);
+
+        Self {
+            storage,
+            ns: key,
+            head,
+            capacity,
+            current: 0,
+            end: len,
+            result: PhantomData
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.current)
+    }
+
+    fn load(&mut self, index: u64) -> StdResult<T> {
+        let slot = match self.capacity {
+            0 => self.head.wrapping_add(index),
+            capacity => (self.head + index) % capacity
+        };
+
+        self.ns.extend_from_slice(&slot.to_be_bytes());
+        let result = super::load(self.storage, &self.ns)
+            .and_then(|x| x.ok_or_else(|| StdError::generic_err("Deque: missing element.")));
+
+        self.ns.truncate(self.ns.len() - mem::size_of::<u64>());
+
+        result
+    }
+}
+
+impl<'storage, T: FadromaDeserialize> Iterator for Iter<'storage, T> {
+    type Item = StdResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current >= self.end {
+            return None;
+        }
+
+        let result = self.load(self.current);
+        self.current += 1;
+
+        Some(result)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len() as usize;
+
+        (len, Some(len))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.current = self.current.saturating_add(n as u64);
+
+        self.next()
+    }
+}
+
+impl<'storage, T: FadromaDeserialize> DoubleEndedIterator for Iter<'storage, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.current >= self.end {
+            return None;
+        }
+
+        self.end -= 1;
+
+        Some(self.load(self.end))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.end = self.end.saturating_sub(n as u64);
+
+        self.next_back()
+    }
+}
+
+impl<'storage, T: FadromaDeserialize> ExactSizeIterator for Iter<'storage, T> { }
+
+#[inline]
+fn slot_key(ns: &impl Key, slot: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(ns.size() + mem::size_of::<u64>());
+    ns.write_segments(&mut key);
+    key.extend_from_slice(&slot.to_be_bytes());
+
+    key
+}
+
+#[inline]
+fn meta_key(ns: &impl Key) -> Vec<u8> {
+    let mut key = Vec::with_capacity(ns.size() + KEY_META.len());
+    ns.write_segments(&mut key);
+    key.extend_from_slice(KEY_META);
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::CompositeKey
+    };
+
+    #[test]
+    fn deque_matches_vec_deque() {
+        let ref mut deps = mock_dependencies();
+        let key = CompositeKey::new(&[b"queue"]);
+
+        let mut deque = Deque::<u32, _>::new(key);
+        let mut model = VecDeque::new();
+
+        for i in 0..100u32 {
+            match i % 7 {
+                0 | 3 => {
+                    deque.push_front(&mut deps.storage, &i).unwrap();
+                    model.push_front(i);
+                },
+                1 | 4 | 6 => {
+                    deque.push_back(&mut deps.storage, &i).unwrap();
+                    model.push_back(i);
+                },
+                2 => assert_eq!(deque.pop_front(&mut deps.storage).unwrap(), model.pop_front()),
+                _ => assert_eq!(deque.pop_back(&mut deps.storage).unwrap(), model.pop_back())
+            }
+
+            // Create new to invalidate the cached metadata.
+            let fresh = Deque::<u32, _>::new(key);
+            assert_eq!(fresh.len(&deps.storage).unwrap(), model.len() as u64);
+        }
+
+        let stored = deque.iter(&deps.storage).unwrap()
+            .collect::<StdResult<Vec<u32>>>()
+            .unwrap();
+        assert_eq!(stored, model.iter().copied().collect::<Vec<u32>>());
+
+        let stored = deque.iter(&deps.storage).unwrap()
+            .rev()
+            .collect::<StdResult<Vec<u32>>>()
+            .unwrap();
+        assert_eq!(stored, model.iter().rev().copied().collect::<Vec<u32>>());
+
+        assert_eq!(deque.front(&deps.storage).unwrap(), model.front().copied());
+        assert_eq!(deque.back(&deps.storage).unwrap(), model.back().copied());
+
+        deque.set(&mut deps.storage, 2, &1000).unwrap();
+        assert_eq!(deque.get(&deps.storage, 2).unwrap(), Some(1000));
+
+        let len = deque.len(&deps.storage).unwrap();
+        assert_eq!(deque.get(&deps.storage, len).unwrap(), None);
+        assert_eq!(
+            deque.set(&mut deps.storage, len, &0).unwrap_err(),
+            StdError::generic_err("Deque: index out of bounds.")
+        );
+
+        while deque.pop_front(&mut deps.storage).unwrap().is_some() { }
+
+        assert!(deque.is_empty(&deps.storage).unwrap());
+        assert_eq!(deque.pop_back(&mut deps.storage).unwrap(), None);
+
+        // Only the metadata is left.
+        assert_eq!(deps.storage.range(None, None, crate::cosmwasm_std::Order::Ascending).count(), 1);
+    }
+
+    #[test]
+    fn ring_buffer_overwrites_oldest() {
+        let ref mut deps = mock_dependencies();
+        let key = CompositeKey::new(&[b"ring"]);
+
+        let mut ring = RingBuffer::<u32, _>::new(key, 4);
+        assert_eq!(ring.latest(&deps.storage).unwrap(), None);
+
+        for i in 0..10u32 {
+            ring.push(&mut deps.storage, &i).unwrap();
+
+            let expected = (i.saturating_sub(3)..=i).collect::<Vec<u32>>();
+            let stored = ring.iter(&deps.storage).unwrap()
+                .collect::<StdResult<Vec<u32>>>()
+                .unwrap();
+
+            assert_eq!(stored, expected);
+            assert_eq!(ring.latest(&deps.storage).unwrap(), Some(i));
+            assert_eq!(ring.get(&deps.storage, 0).unwrap(), expected.first().copied());
+        }
+
+        assert_eq!(ring.len(&deps.storage).unwrap(), 4);
+        assert_eq!(ring.get(&deps.storage, 4).unwrap(), None);
+
+        // Capacity + the metadata.
+        assert_eq!(deps.storage.range(None, None, crate::cosmwasm_std::Order::Ascending).count(), 5);
+
+        let mut other = RingBuffer::<u32, _>::new(key, 5);
+        assert!(other.len(&deps.storage).is_err());
+        assert!(other.push(&mut deps.storage, &1).is_err());
+    }
+}
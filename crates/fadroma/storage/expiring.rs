@@ -0,0 +1,373 @@
+use std::marker::PhantomData;
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult, BlockInfo, Binary, Timestamp},
+    schemars::{self, JsonSchema},
+    serde::{Serialize, Deserialize}
+};
+use super::{
+    Key, Namespace, serialize, deserialize, not_found_error,
+    btree::{BTree, Bound, Order}
+};
+
+const KEY_VALUE: &[u8] = b"value";
+const KEY_HEIGHT: &[u8] = b"height";
+const KEY_TIME: &[u8] = b"time";
+
+/// When a value stored in [`ExpiringMap`] expires.
+#[derive(Serialize, Deserialize, JsonSchema, FadromaSerialize, FadromaDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiry {
+    /// Expires once the block height reaches the given one.
+    AtHeight(u64),
+    /// Expires once the block time reaches the given one.
+    AtTime(Timestamp),
+    Never
+}
+
+/// Stores values together with an [`Expiry`]. Values that have expired are
+/// treated as absent when loading them and can be removed from storage in
+/// bounded batches by calling [`ExpiringMap::gc`]. The expiries are indexed
+/// in order so that garbage collection only ever visits expired entries.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::{mock_dependencies, mock_env}},
+///     storage::{TypedKey, expiring::{ExpiringMap, Expiry}}
+/// };
+///
+/// fadroma::namespace!(NoncesNs, b"nonces");
+/// const NONCES: ExpiringMap<TypedKey<u64>, bool, NoncesNs> = ExpiringMap::new();
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let mut env = mock_env();
+/// let expiry = Expiry::AtHeight(env.block.height + 10);
+///
+/// NONCES.save(storage, &1, &true, expiry)?;
+/// assert_eq!(NONCES.load(storage, &env.block, &1)?, Some(true));
+///
+/// env.block.height += 10;
+/// assert_eq!(NONCES.load(storage, &env.block, &1)?, None);
+///
+/// assert_eq!(NONCES.gc(storage, &env.block, 100)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ExpiringMap<K: Key, V: FadromaSerialize + FadromaDeserialize, N: Namespace> {
+    key_data: PhantomData<K>,
+    value_data: PhantomData<V>,
+    ns_data: PhantomData<N>
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize)]
+struct ExpiringEntry {
+    expiry: Expiry,
+    value: Binary
+}
+
+impl Expiry {
+    /// Returns `true` if the expiry has been reached at the given block.
+    #[inline]
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        match self {
+            Self::AtHeight(height) => block.height >= *height,
+            Self::AtTime(time) => block.time >= *time,
+            Self::Never => false
+        }
+    }
+}
+
+impl<K: Key, V: FadromaSerialize + FadromaDeserialize, N: Namespace> ExpiringMap<K, V, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `ExpiringMap`:
+    ///  * N + "value" + K
+    ///  * N + "height" + "meta"
+    ///  * N + "height" + "node" + n - where n is a number
+    ///  * N + "time" + "meta"
+    ///  * N + "time" + "node" + n - where n is a number
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            key_data: PhantomData,
+            value_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Saves the value under the given key, replacing any
+    /// previous value and its expiry.
+    pub fn save(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        value: &V,
+        expiry: Expiry
+    ) -> StdResult<()> {
+        let key = Self::key_bytes(key.into());
+
+        if let Some(entry) = Self::load_entry(storage, &key)? {
+            Self::unindex(storage, &key, entry.expiry)?;
+        }
+
+        if let Some(index) = Self::index_key(&key, expiry) {
+            BTree::new(&Self::tree_ns(expiry)).insert(storage, &index)?;
+        }
+
+        let entry = ExpiringEntry {
+            expiry,
+            value: Binary(serialize(value)?)
+        };
+
+        super::save(storage, Self::value_key(&key), &entry)
+    }
+
+    /// Loads the value under the given key.
+    /// Returns `None` if it has expired at the given block.
+    #[inline]
+    pub fn load(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+        key: impl Into<K>
+    ) -> StdResult<Option<V>> {
+        let result = self.load_with_expiry(storage, block, key)?;
+
+        Ok(result.map(|(value, _)| value))
+    }
+
+    #[inline]
+    pub fn load_or_error(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+        key: impl Into<K>
+    ) -> StdResult<V> {
+        let result = self.load(storage, block, key)?;
+
+        result.ok_or_else(|| not_found_error::<V>())
+    }
+
+    /// Loads the value under the given key together with its expiry.
+    /// Returns `None` if it has expired at the given block.
+    pub fn load_with_expiry(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+        key: impl Into<K>
+    ) -> StdResult<Option<(V, Expiry)>> {
+        let key = Self::key_bytes(key.into());
+
+        match Self::load_entry(storage, &key)? {
+            Some(entry) if !entry.expiry.is_expired(block) => {
+                Ok(Some((deserialize(&entry.value.0)?, entry.expiry)))
+            },
+            _ => Ok(None)
+        }
+    }
+
+    /// Removes the value under the given key regardless of whether it
+    /// has expired or not. Returns `true` if there was a value stored.
+    pub fn remove(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>
+    ) -> StdResult<bool> {
+        let key = Self::key_bytes(key.into());
+
+        match Self::load_entry(storage, &key)? {
+            Some(entry) => {
+                Self::unindex(storage, &key, entry.expiry)?;
+                storage.remove(&Self::value_key(&key));
+
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+
+    /// Removes up to `limit` values that have expired at the given block
+    /// from storage. Returns the number of values removed.
+    ///
+    /// Values that expire at a height are removed first, from the lowest height
+    /// up, followed by values that expire at a time, from the earliest time on.
+    /// Heights and times can't be compared, so a value that expired at a time
+    /// long ago may be removed after values that expired at a height more recently.
+    pub fn gc(
+        &self,
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        limit: u32
+    ) -> StdResult<u32> {
+        let mut removed = 0;
+
+        for (expiry, now) in [
+            (Expiry::AtHeight(0), block.height),
+            (Expiry::AtTime(Timestamp::default()), block.time.nanos())
+        ] {
+            let ns = Self::tree_ns(expiry);
+            let tree = BTree::new(&ns);
+
+            // Everything that expires at or before `now`.
+            let max = match now.checked_add(1) {
+                Some(next) => Bound::Exclusive(next.to_be_bytes().to_vec()),
+                None => Bound::Unbounded
+            };
+
+            let expired = tree.keys(storage, Bound::Unbounded, max, Order::Ascending)?
+                .take((limit - removed) as usize)
+                .collect::<StdResult<Vec<_>>>()?;
+
+            for index in expired {
+                tree.remove(storage, &index)?;
+                storage.remove(&Self::value_key(&index[8..]));
+
+                removed += 1;
+            }
+
+            if removed == limit {
+                break;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    #[inline]
+    fn load_entry(storage: &dyn Storage, key: &[u8]) -> StdResult<Option<ExpiringEntry>> {
+        super::load(storage, Self::value_key(key))
+    }
+
+    #[inline]
+    fn unindex(storage: &mut dyn Storage, key: &[u8], expiry: Expiry) -> StdResult<()> {
+        if let Some(index) = Self::index_key(key, expiry) {
+            BTree::new(&Self::tree_ns(expiry)).remove(storage, &index)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn key_bytes(key: K) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(key.size());
+        key.write_segments(&mut bytes);
+
+        bytes
+    }
+
+    #[inline]
+    fn value_key(key: &[u8]) -> Vec<u8> {
+        [N::NAMESPACE, KEY_VALUE, key].concat()
+    }
+
+    #[inline]
+    fn tree_ns(expiry: Expiry) -> Vec<u8> {
+        match expiry {
+            Expiry::AtTime(_) => [N::NAMESPACE, KEY_TIME].concat(),
+            _ => [N::NAMESPACE, KEY_HEIGHT].concat()
+        }
+    }
+
+    /// The expiry in big endian followed by the key so that
+    /// the entries in the index are ordered by expiry.
+    #[inline]
+    fn index_key(key: &[u8], expiry: Expiry) -> Option<Vec<u8>> {
+        let expires_at = match expiry {
+            Expiry::AtHeight(height) => height,
+            Expiry::AtTime(time) => time.nanos(),
+            Expiry::Never => return None
+        };
+
+        Some([&expires_at.to_be_bytes(), key].concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::{mock_dependencies, mock_env},
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    const MAP: ExpiringMap<TypedKey<u16>, u16, TestNs> = ExpiringMap::new();
+
+    #[test]
+    fn expires_by_height_and_time() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut block = mock_env().block;
+        let (height, time) = (block.height, block.time);
+
+        MAP.save(storage, &1, &1, Expiry::AtHeight(height + 5)).unwrap();
+        MAP.save(storage, &2, &2, Expiry::AtTime(time.plus_seconds(60))).unwrap();
+        MAP.save(storage, &3, &3, Expiry::Never).unwrap();
+
+        assert_eq!(MAP.load(storage, &block, &1).unwrap(), Some(1));
+        assert_eq!(
+            MAP.load_with_expiry(storage, &block, &2).unwrap(),
+            Some((2, Expiry::AtTime(time.plus_seconds(60))))
+        );
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 0);
+
+        block.height += 5;
+        assert_eq!(MAP.load(storage, &block, &1).unwrap(), None);
+        assert_eq!(MAP.load(storage, &block, &2).unwrap(), Some(2));
+
+        block.time = block.time.plus_seconds(60);
+        assert_eq!(MAP.load(storage, &block, &2).unwrap(), None);
+        MAP.load_or_error(storage, &block, &2).unwrap_err();
+
+        block.height = u64::MAX;
+        block.time = Timestamp::from_nanos(u64::MAX - 1);
+        assert_eq!(MAP.load(storage, &block, &3).unwrap(), Some(3));
+
+        // Saving again replaces the expiry.
+        MAP.save(storage, &1, &10, Expiry::Never).unwrap();
+        assert_eq!(MAP.load(storage, &block, &1).unwrap(), Some(10));
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 1);
+        assert!(MAP.remove(storage, &1).unwrap());
+        assert!(!MAP.remove(storage, &1).unwrap());
+    }
+
+    #[test]
+    fn incremental_gc() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut block = mock_env().block;
+        block.height = 100;
+
+        for i in 0..50u16 {
+            MAP.save(storage, &i, &i, Expiry::AtHeight(100 + i as u64 % 10)).unwrap();
+        }
+
+        block.height = 104;
+
+        // 5 entries expire at each of the heights from 100 to 104.
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 10);
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 10);
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 5);
+        assert_eq!(MAP.gc(storage, &block, 10).unwrap(), 0);
+
+        for i in 0..50u16 {
+            let expired = i % 10 < 5;
+            assert_eq!(storage.get(&value_key(i)).is_some(), !expired);
+        }
+
+        block.height = 200;
+        assert_eq!(MAP.gc(storage, &block, 100).unwrap(), 25);
+
+        // Only the empty index metadata remains.
+        assert_eq!(storage.range(None, None, crate::cosmwasm_std::Order::Ascending).count(), 1);
+    }
+
+    fn value_key(key: u16) -> Vec<u8> {
+        ExpiringMap::<TypedKey<u16>, u16, TestNs>::value_key(&key.to_be_bytes())
+    }
+}
@@ -0,0 +1,548 @@
+//! A sorted key-value storage type with secondary indexes.
+
+use std::marker::PhantomData;
+
+use crate::{
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult, StdError}
+};
+use super::{
+    Namespace, Key, Segment,
+    btree::{BTree, Keys, segment_bytes, encode, after, split_encoded},
+    ordered::{OrderedMap, Range, Order, Bound, key_bytes, value_key},
+    not_found_error
+};
+
+const KEY_INDEX: &[u8] = b"idx";
+
+/// A secondary index of an [`IndexedMap`]. The index key of each value
+/// is computed by the provided function and is expected to be in the
+/// same format as the bytes written by the [`Segment`] that is used to
+/// query the index.
+///
+/// A **unique** index allows at most a single entry per index key while
+/// a **multi** index allows any number of entries to share the same key.
+pub struct Index<V> {
+    name: &'static [u8],
+    is_unique: bool,
+    key: fn(&V) -> Vec<u8>
+}
+
+/// A key-value storage type that keeps its entries sorted just like an
+/// [`OrderedMap`] and maintains any number of secondary [`Index`]es of
+/// them. The indexes are updated together with the entries on each insert
+/// and remove and entries can then be looked up and iterated by them.
+///
+/// Each index is stored in its own B+ tree as the index key followed
+/// by the primary key of the entry, so entries that share an index key
+/// are iterated in the order of their primary keys.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::mock_dependencies},
+///     bin_serde::{FadromaSerialize, FadromaDeserialize},
+///     storage::{
+///         TypedKey,
+///         ordered::Order,
+///         indexed::{IndexedMap, Index}
+///     }
+/// };
+///
+/// #[derive(FadromaSerialize, FadromaDeserialize, PartialEq, Debug)]
+/// struct Token {
+///     owner: String,
+///     name: String
+/// }
+///
+/// fadroma::namespace!(TokensNs, b"tokens");
+///
+/// const BY_OWNER: Index<Token> = Index::multi(b"owner", |x| x.owner.as_bytes().to_vec());
+/// const BY_NAME: Index<Token> = Index::unique(b"name", |x| x.name.as_bytes().to_vec());
+///
+/// const TOKENS: IndexedMap<TypedKey<u64>, Token, TokensNs> =
+///     IndexedMap::new(&[BY_OWNER, BY_NAME]);
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// TOKENS.insert(storage, &1, &Token { owner: "alice".into(), name: "one".into() })?;
+/// TOKENS.insert(storage, &2, &Token { owner: "bob".into(), name: "two".into() })?;
+/// TOKENS.insert(storage, &3, &Token { owner: "alice".into(), name: "three".into() })?;
+///
+/// let names = TOKENS.find(storage, &BY_OWNER, &"alice", Order::Ascending)?
+///     .map(|x| x.map(|(_, token)| token.name))
+///     .collect::<StdResult<Vec<String>>>()?;
+///
+/// assert_eq!(names, ["one", "three"]);
+///
+/// let (_, token) = TOKENS.find_unique(storage, &BY_NAME, &"two")?.unwrap();
+/// assert_eq!(token.owner, "bob");
+///
+/// // The name is already taken.
+/// let result = TOKENS.insert(storage, &4, &Token { owner: "bob".into(), name: "one".into() });
+/// assert!(result.is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub struct IndexedMap<
+    K: Key,
+    V: FadromaSerialize + FadromaDeserialize + 'static,
+    N: Namespace
+> {
+    map: OrderedMap<K, V, N>,
+    indexes: &'static [Index<V>]
+}
+
+/// Iterator over the entries of an [`IndexedMap`] in the order of an [`Index`].
+/// Yields the primary key of each entry together with its value.
+pub struct IndexRange<'storage, V: FadromaDeserialize> {
+    storage: &'storage dyn Storage,
+    keys: Keys<'storage>,
+    ns: &'static [u8],
+    data: PhantomData<V>
+}
+
+impl<V> Index<V> {
+    /// # Panics
+    ///
+    /// If `name` is longer than 255 bytes. Fails the build when used in a constant.
+    ///
+    /// ```compile_fail
+    /// use fadroma::storage::indexed::Index;
+    ///
+    /// const BY_NAME: Index<String> = Index::unique(&[b'a'; 256], |x| x.as_bytes().to_vec());
+    /// let _ = BY_NAME.name();
+    /// ```
+    #[inline]
+    pub const fn unique(name: &'static [u8], key: fn(&V) -> Vec<u8>) -> Self {
+        Self { name: Self::check_name(name), is_unique: true, key }
+    }
+
+    /// # Panics
+    ///
+    /// If `name` is longer than 255 bytes. Fails the build when used in a constant.
+    #[inline]
+    pub const fn multi(name: &'static [u8], key: fn(&V) -> Vec<u8>) -> Self {
+        Self { name: Self::check_name(name), is_unique: false, key }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static [u8] {
+        self.name
+    }
+
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.is_unique
+    }
+
+    #[inline]
+    const fn check_name(name: &'static [u8]) -> &'static [u8] {
+        // The length is stored as a single byte in the namespace of the index.
+        assert!(name.len() <= u8::MAX as usize, "IndexedMap: index names must be at most 255 bytes long.");
+
+        name
+    }
+
+        /// The namespace of the B+ tree that stores this index.
+    fn ns(&self, map_ns: &[u8]) -> Vec<u8> {
+        let mut ns = Vec::with_capacity(map_ns.len() + KEY_INDEX.len() + 1 + self.name.len());
+        ns.extend_from_slice(map_ns);
+        ns.extend_from_slice(KEY_INDEX);
+        // Length prefixed so that an index name can't be the prefix of another.
+        ns.push(self.name.len() as u8);
+        ns.extend_from_slice(self.name);
+
+        ns
+    }
+}
+
+impl<
+    K: Key,
+    V: FadromaSerialize + FadromaDeserialize + 'static,
+    N: Namespace
+> IndexedMap<K, V, N> {
+    /// Creates an instance for the given namespace and indexes.
+    /// The index names must be unique and at most 255 bytes long.
+    /// The following namespaces are reserved by `IndexedMap`
+    /// in addition to the ones reserved by [`OrderedMap`]:
+    ///  * `ns` + "idx" + index name length + index name + ...
+    #[inline]
+    pub const fn new(indexes: &'static [Index<V>]) -> Self {
+        Self {
+            map: OrderedMap::new(),
+            indexes
+        }
+    }
+
+    /// Inserts or overwrites the value under the given key and updates
+    /// all indexes. Returns `true` if the key didn't exist before.
+    /// Returns an error without changing anything if the value would
+    /// violate a unique index.
+    pub fn insert(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        value: &V
+    ) -> StdResult<bool> {
+        let primary = key_bytes(key.into());
+        let old: Option<V> = self.map.get_raw(storage, &primary)?;
+
+        // Check all constraints before writing anything.
+        for index in self.indexes.iter().filter(|x| x.is_unique) {
+            let index_key = (index.key)(value);
+
+            if let Some(existing) = self.first_match(storage, index, &index_key)? {
+                if existing != primary {
+                    return Err(StdError::generic_err(format!(
+                        "IndexedMap: an entry with the same key in the unique index \"{}\" already exists.",
+                        String::from_utf8_lossy(index.name)
+                    )));
+                }
+            }
+        }
+
+        for index in self.indexes {
+            let new_key = (index.key)(value);
+            let tree_ns = index.ns(N::NAMESPACE);
+            let tree = BTree::new(&tree_ns);
+
+            if let Some(old) = &old {
+                let old_key = (index.key)(old);
+
+                if old_key == new_key {
+                    continue;
+                }
+
+                tree.remove(storage, &index_entry(&old_key, &primary))?;
+            }
+
+            tree.insert(storage, &index_entry(&new_key, &primary))?;
+        }
+
+        self.map.insert_raw(storage, &primary, value)
+    }
+
+    /// Removes the entry under the given key together with its index
+    /// entries. Returns `true` if the key existed.
+    pub fn remove(&self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<bool> {
+        let primary = key_bytes(key.into());
+
+        let Some(old) = self.map.get_raw(storage, &primary)? else {
+            return Ok(false);
+        };
+
+        for index in self.indexes {
+            let tree_ns = index.ns(N::NAMESPACE);
+            BTree::new(&tree_ns).remove(storage, &index_entry(&(index.key)(&old), &primary))?;
+        }
+
+        self.map.remove_raw(storage, &primary)
+    }
+
+    #[inline]
+    pub fn get(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<Option<V>> {
+        self.map.get(storage, key)
+    }
+
+    #[inline]
+    pub fn get_or_error(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<V> {
+        self.map.get_or_error(storage, key)
+    }
+
+    #[inline]
+    pub fn contains(&self, storage: &dyn Storage, key: impl Into<K>) -> bool {
+        self.map.contains(storage, key)
+    }
+
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        self.map.len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        self.map.is_empty(storage)
+    }
+
+    /// Iterates over all entries in the order of their primary keys.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        order: Order
+    ) -> StdResult<Range<'storage, V>> {
+        self.map.iter(storage, order)
+    }
+
+    /// Iterates over the entries with primary keys between `min` and `max`.
+    #[inline]
+    pub fn range<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        min: Bound,
+        max: Bound,
+        order: Order
+    ) -> StdResult<Range<'storage, V>> {
+        self.map.range(storage, min, max, order)
+    }
+
+    /// Returns the entry with the given key in a unique `index`.
+    pub fn find_unique(
+        &self,
+        storage: &dyn Storage,
+        index: &Index<V>,
+        index_key: &(impl Segment + ?Sized)
+    ) -> StdResult<Option<(Vec<u8>, V)>> {
+        self.assert_registered(index)?;
+
+        let Some(primary) = self.first_match(storage, index, &segment_bytes(index_key))? else {
+            return Ok(None);
+        };
+
+        let value = self.map.get_raw(storage, &primary)?
+            .ok_or_else(|| not_found_error::<V>())?;
+
+        Ok(Some((primary, value)))
+    }
+
+    /// Iterates over all entries with the given key in `index`.
+    pub fn find<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        index: &Index<V>,
+        index_key: &(impl Segment + ?Sized),
+        order: Order
+    ) -> StdResult<IndexRange<'storage, V>> {
+        self.assert_registered(index)?;
+
+        let (min, max) = Bound::prefix(encode(&segment_bytes(index_key)));
+        let tree_ns = index.ns(N::NAMESPACE);
+
+        Ok(IndexRange {
+            storage,
+            keys: BTree::new(&tree_ns).keys(storage, min, max, order)?,
+            ns: N::NAMESPACE,
+            data: PhantomData
+        })
+    }
+
+    /// Iterates over the entries with keys between `min` and `max` in `index`.
+    /// The entries are sorted by their index key and then by their primary key.
+    pub fn range_by<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        index: &Index<V>,
+        min: Bound,
+        max: Bound,
+        order: Order
+    ) -> StdResult<IndexRange<'storage, V>> {
+        self.assert_registered(index)?;
+
+        // Translate the bounds on index keys to bounds on the index
+        // entries which have the primary key appended to them.
+        let min = match min {
+            Bound::Inclusive(key) => Bound::Inclusive(encode(&key)),
+            Bound::Exclusive(key) => Bound::Inclusive(after(encode(&key))),
+            Bound::Unbounded => Bound::Unbounded
+        };
+
+        let max = match max {
+            Bound::Inclusive(key) => Bound::Exclusive(after(encode(&key))),
+            Bound::Exclusive(key) => Bound::Exclusive(encode(&key)),
+            Bound::Unbounded => Bound::Unbounded
+        };
+
+        let tree_ns = index.ns(N::NAMESPACE);
+
+        Ok(IndexRange {
+            storage,
+            keys: BTree::new(&tree_ns).keys(storage, min, max, order)?,
+            ns: N::NAMESPACE,
+            data: PhantomData
+        })
+    }
+
+    /// Returns the primary key of the first entry with the given index key.
+    fn first_match(
+        &self,
+        storage: &dyn Storage,
+        index: &Index<V>,
+        index_key: &[u8]
+    ) -> StdResult<Option<Vec<u8>>> {
+        let (min, max) = Bound::prefix(encode(index_key));
+        let tree_ns = index.ns(N::NAMESPACE);
+
+        let mut keys = BTree::new(&tree_ns).keys(storage, min, max, Order::Ascending)?;
+
+        match keys.next() {
+            Some(entry) => Ok(Some(primary_key(&entry?)?.to_vec())),
+            None => Ok(None)
+        }
+    }
+
+    fn assert_registered(&self, index: &Index<V>) -> StdResult<()> {
+        if self.indexes.iter().any(|x| x.name == index.name) {
+            Ok(())
+        } else {
+            Err(StdError::generic_err(format!(
+                "IndexedMap: index \"{}\" is not registered.",
+                String::from_utf8_lossy(index.name)
+            )))
+        }
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> Iterator for IndexRange<'storage, V> {
+    type Item = StdResult<(Vec<u8>, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.keys.next()?.and_then(|entry| {
+            let primary = primary_key(&entry)?.to_vec();
+
+            let value = super::load(self.storage, value_key(self.ns, &primary))?
+                .ok_or_else(|| not_found_error::<V>())?;
+
+            Ok((primary, value))
+        });
+
+        Some(result)
+    }
+}
+
+#[inline]
+fn index_entry(index_key: &[u8], primary: &[u8]) -> Vec<u8> {
+    let mut entry = encode(index_key);
+    entry.extend_from_slice(primary);
+
+    entry
+}
+
+/// Returns the primary key part of an index entry.
+#[inline]
+fn primary_key(entry: &[u8]) -> StdResult<&[u8]> {
+    match split_encoded(entry) {
+        Some((_, primary)) => Ok(primary),
+        None => Err(StdError::generic_err("IndexedMap: invalid index entry."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as fadroma,
+        cosmwasm_std::testing::mock_dependencies,
+        storage::{TypedKey, StaticKey},
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    #[derive(FadromaSerialize, FadromaDeserialize, Clone, PartialEq, Debug)]
+    struct Offer {
+        owner: Vec<u8>,
+        price: u32,
+        id: String
+    }
+
+    const BY_OWNER: Index<Offer> = Index::multi(b"owner", |x| x.owner.clone());
+    const BY_PRICE: Index<Offer> = Index::multi(b"price", |x| x.price.to_be_bytes().to_vec());
+    const BY_ID: Index<Offer> = Index::unique(b"id", |x| x.id.as_bytes().to_vec());
+
+    const ORDERS: IndexedMap<TypedKey<u8>, Offer, TestNs> =
+        IndexedMap::new(&[BY_OWNER, BY_PRICE, BY_ID]);
+
+    fn order(owner: &[u8], price: u32, id: &str) -> Offer {
+        Offer { owner: owner.to_vec(), price, id: id.into() }
+    }
+
+    fn keys<V: FadromaDeserialize>(range: IndexRange<V>) -> Vec<u8> {
+        range.map(|x| x.unwrap().0[0]).collect()
+    }
+
+    #[test]
+    fn maintains_indexes() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        ORDERS.insert(storage, &1, &order(b"a", 30, "one")).unwrap();
+        ORDERS.insert(storage, &2, &order(b"a\0", 10, "two")).unwrap();
+        ORDERS.insert(storage, &3, &order(b"a", 20, "three")).unwrap();
+        ORDERS.insert(storage, &4, &order(b"", 20, "four")).unwrap();
+
+        // Keys that contain zero bytes or are prefixes of others are kept apart.
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"a"), Order::Ascending).unwrap()), [1, 3]);
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"a\0"), Order::Ascending).unwrap()), [2]);
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b""), Order::Ascending).unwrap()), [4]);
+
+        let by_price = |storage: &dyn Storage, min, max, order| keys(ORDERS.range_by(storage, &BY_PRICE, min, max, order).unwrap());
+
+        assert_eq!(by_price(storage, Bound::Unbounded, Bound::Unbounded, Order::Ascending), [2, 3, 4, 1]);
+        assert_eq!(by_price(storage, Bound::Unbounded, Bound::Unbounded, Order::Descending), [1, 4, 3, 2]);
+        assert_eq!(by_price(storage, Bound::exclusive(&10u32), Bound::inclusive(&20u32), Order::Ascending), [3, 4]);
+        assert_eq!(by_price(storage, Bound::inclusive(&10u32), Bound::exclusive(&20u32), Order::Descending), [2]);
+
+        // Updating moves the entry in the changed indexes.
+        ORDERS.insert(storage, &3, &order(b"b", 20, "three")).unwrap();
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"a"), Order::Ascending).unwrap()), [1]);
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"b"), Order::Ascending).unwrap()), [3]);
+
+        let (key, value) = ORDERS.find_unique(storage, &BY_ID, &"three").unwrap().unwrap();
+        assert_eq!(key, [3]);
+        assert_eq!(value, order(b"b", 20, "three"));
+
+        assert!(ORDERS.remove(storage, &3).unwrap());
+        assert!(!ORDERS.remove(storage, &3).unwrap());
+        assert_eq!(ORDERS.find_unique(storage, &BY_ID, &"three").unwrap(), None);
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"b"), Order::Ascending).unwrap()), Vec::<u8>::new());
+        assert_eq!(by_price(storage, Bound::Unbounded, Bound::Unbounded, Order::Ascending), [2, 4, 1]);
+    }
+
+    #[test]
+    fn unique_index_violation_changes_nothing() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        ORDERS.insert(storage, &1, &order(b"a", 1, "one")).unwrap();
+        ORDERS.insert(storage, &2, &order(b"b", 2, "two")).unwrap();
+
+        let err = ORDERS.insert(storage, &2, &order(b"c", 3, "one")).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("IndexedMap: an entry with the same key in the unique index \"id\" already exists.")
+        );
+
+        assert_eq!(ORDERS.get(storage, &2).unwrap(), Some(order(b"b", 2, "two")));
+        assert_eq!(keys(ORDERS.find(storage, &BY_OWNER, &StaticKey(b"c"), Order::Ascending).unwrap()), Vec::<u8>::new());
+
+        // Updating an entry without changing its unique key is fine.
+        ORDERS.insert(storage, &1, &order(b"c", 3, "one")).unwrap();
+
+        const UNKNOWN: Index<Offer> = Index::unique(b"unknown", |x| x.id.as_bytes().to_vec());
+        assert!(ORDERS.find_unique(storage, &UNKNOWN, &"one").is_err());
+    }
+
+    #[test]
+    fn encoding_keeps_order() {
+        let keys: [&[u8]; 6] = [b"", b"\0", b"\0\0", b"\0\x01", b"a", b"a\0"];
+
+        for pair in keys.windows(2) {
+            assert!(encode(pair[0]) < encode(pair[1]));
+        }
+
+        for key in keys {
+            let entry = index_entry(key, b"\0primary\0");
+            assert_eq!(primary_key(&entry).unwrap(), b"\0primary\0");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index names must be at most 255 bytes long")]
+    fn index_name_too_long() {
+        static NAME: [u8; 256] = [b'a'; 256];
+
+        Index::<Offer>::unique(&NAME[..255], |x| x.id.as_bytes().to_vec());
+        Index::<Offer>::multi(&NAME, |x| x.id.as_bytes().to_vec());
+    }
+}
@@ -0,0 +1,828 @@
+//! Reconstructs storage types from a raw dump of a contract's key-value pairs.
+//!
+//! Register the namespaces of the storage types used by the contract with an
+//! [`Inspector`] together with the types of their keys and values. It then
+//! groups the entries of the dump by the namespace they fall under, decodes
+//! them according to the layout of the storage type and reports any entries
+//! that are inconsistent with it (i.e. values that can't be decoded or items
+//! missing from a [`super::map::Map`]). The resulting [`Report`] can be printed
+//! or exported as JSON. This is useful for auditing the state of a contract
+//! or verifying that a migration left it in the expected shape.
+//!
+//! Dumps can be read from storage directly with [`Inspector::inspect_storage`]
+//! (i.e. the ensemble's storage or a [`crate::cosmwasm_std::testing::MockStorage`])
+//! or parsed with [`parse_dump`] from the JSON returned by the `contract-state all`
+//! query of a node. The `fadroma-inspect` binary does the latter for storage types
+//! whose keys and values are built-in types. For any other types, register them in
+//! a small binary of your own and pass the dump to [`Inspector::inspect`].
+//!
+//! Requires the `inspect` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use fadroma::{
+//!     cosmwasm_std::{testing::mock_dependencies, Uint128},
+//!     storage::{
+//!         Namespace, SingleItem, TypedKey,
+//!         map::Map,
+//!         inspect::{Inspector, StructureKind}
+//!     }
+//! };
+//!
+//! fadroma::namespace!(AdminNs, b"admin");
+//! fadroma::namespace!(BalancesNs, b"balances");
+//!
+//! let mut deps = mock_dependencies();
+//! let storage = deps.as_mut().storage;
+//!
+//! SingleItem::<String, AdminNs>::new().save(storage, &"admin".into()).unwrap();
+//!
+//! let mut balances = Map::<TypedKey<&str>, Uint128, BalancesNs>::new();
+//! balances.insert(storage, &"alice", &Uint128::new(100)).unwrap();
+//!
+//! let mut inspector = Inspector::new();
+//! inspector
+//!     .single_item::<String>("admin", AdminNs::NAMESPACE)
+//!     .map::<TypedKey<&str>, Uint128>("balances", BalancesNs::NAMESPACE);
+//!
+//! let report = inspector.inspect_storage(storage);
+//! assert!(!report.has_issues());
+//!
+//! assert_eq!(report.structures[1].kind, StructureKind::Map);
+//! assert_eq!(
+//!     report.structures[1].value,
+//!     serde_json::json!([{ "key": "alice", "value": "100" }])
+//! );
+//!
+//! println!("{}", report);
+//! println!("{}", report.to_json());
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    collections::BTreeMap
+};
+
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+
+use crate::{
+    bin_serde::{FadromaDeserialize, Deserializer},
+    cosmwasm_std::{Storage, Order, Binary, HexBinary, StdResult, StdError}
+};
+use super::{DecodeKey, map::ItemEntry};
+
+/// The suffix of the key under which [`super::iterable::IterableStorage`] stores its length.
+const KEY_LEN: &[u8] = b"index";
+/// The suffix of the keys under which maps store the index of each key.
+const KEY_MAP: &[u8] = b"key";
+
+const UNEXPECTED_KEY: &str = "The key is not part of the storage type.";
+
+/// Decodes either a key (without its namespace) or a value into JSON.
+pub type Decoder = fn(&[u8]) -> StdResult<Value>;
+
+/// Describes how a storage type lays out its data under its namespace.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StructureKind {
+    /// [`super::SingleItem`] - The namespace itself is the key.
+    SingleItem,
+    /// [`super::ItemSpace`] - Each key is appended to the namespace.
+    ItemSpace,
+    /// [`super::iterable::IterableStorage`]
+    Iterable,
+    /// [`super::map::Map`]
+    Map,
+    /// [`super::map::InsertOnlyMap`]
+    InsertOnlyMap
+}
+
+/// Decodes a dump of key-value pairs according to
+/// the storage types that have been registered with it.
+/// See the [module level documentation](self).
+#[derive(Default, Debug)]
+pub struct Inspector {
+    structures: Vec<Structure>
+}
+
+#[derive(Clone, Debug)]
+struct Structure {
+    name: String,
+    ns: Vec<u8>,
+    kind: StructureKind,
+    key: Decoder,
+    value: Decoder
+}
+
+/// The result of [`Inspector::inspect`].
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Report {
+    /// Every registered storage type, in the order of registration.
+    pub structures: Vec<StructureDump>,
+    /// Entries which don't belong to any of the registered storage types.
+    pub unrecognized: Vec<RawEntry>
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct StructureDump {
+    pub name: String,
+    /// The namespace as text if it's printable or hex encoded otherwise.
+    pub namespace: String,
+    pub kind: StructureKind,
+    /// The decoded contents of the storage type:
+    ///  * [`StructureKind::SingleItem`] - the value or `null` if it's not stored.
+    ///  * [`StructureKind::Iterable`] - an array of the items in order.
+    ///  * [`StructureKind::ItemSpace`] and the maps - an array of `{ "key", "value" }` objects.
+    ///
+    /// Values that couldn't be decoded are `null` and reported in the issues.
+    pub value: Value,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<Issue>
+}
+
+/// An entry which is inconsistent with the layout of the storage type that it belongs to.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Issue {
+    /// The full key, hex encoded.
+    pub key: String,
+    pub message: String
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct RawEntry {
+    /// Hex encoded.
+    pub key: String,
+    /// Hex encoded.
+    pub value: String
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Dump {
+    Query { models: Vec<Model> },
+    Entries(Vec<Model>)
+}
+
+#[derive(Deserialize)]
+struct Model {
+    key: String,
+    value: Binary
+}
+
+/// Parses a dump of key-value pairs from JSON. Keys are expected to be
+/// hex encoded and values to be base64 encoded, as returned by the
+/// `contract-state all` query. Both the response of the query, which
+/// is an object with a `models` array, and the array by itself are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::storage::inspect::parse_dump;
+///
+/// let entries = parse_dump(br#"{ "models": [{ "key": "6e756d", "value": "AQU=" }] }"#).unwrap();
+/// assert_eq!(entries, vec![(b"num".to_vec(), vec![1, 5])]);
+/// ```
+pub fn parse_dump(json: &[u8]) -> StdResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    let dump: Dump = serde_json::from_slice(json).map_err(|e|
+        StdError::parse_err("storage dump", e)
+    )?;
+
+    let models = match dump {
+        Dump::Query { models } => models,
+        Dump::Entries(models) => models
+    };
+
+    models.into_iter().map(|x| {
+        let key = HexBinary::from_hex(&x.key)?;
+
+        Ok((key.to_vec(), x.value.0))
+    }).collect()
+}
+
+/// Decodes a value of type `T` into JSON. Fails if there are any bytes left after decoding.
+pub fn decode_value<T: FadromaDeserialize + Serialize>(bytes: &[u8]) -> StdResult<Value> {
+    let mut de = Deserializer::from(&bytes);
+    let value = de.deserialize::<T>().map_err(|e|
+        StdError::parse_err(std::any::type_name::<T>(), e)
+    )?;
+
+    if !de.is_finished() {
+        return Err(StdError::generic_err(
+            "Inspector: unexpected bytes left after decoding."
+        ));
+    }
+
+    to_json(&value)
+}
+
+/// Decodes a key using its [`DecodeKey`] implementation into JSON.
+pub fn decode_key<K: DecodeKey>(bytes: &[u8]) -> StdResult<Value>
+    where K::Output: Serialize
+{
+    to_json(&K::decode_key(bytes)?)
+}
+
+/// Leaves the key bytes as they are, hex encoded.
+pub fn raw_key(bytes: &[u8]) -> StdResult<Value> {
+    Ok(Value::String(hex(bytes)))
+}
+
+impl Inspector {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a [`super::SingleItem`] storing a `T`.
+    #[inline]
+    pub fn single_item<T: FadromaDeserialize + Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8]
+    ) -> &mut Self {
+        self.register(name, ns, StructureKind::SingleItem, raw_key, decode_value::<T>)
+    }
+
+    /// Registers an [`super::ItemSpace`] storing a `T` under keys of type `K`.
+    /// Use [`Inspector::register`] with [`raw_key`] if the keys can't be decoded.
+    #[inline]
+    pub fn item_space<K: DecodeKey, T: FadromaDeserialize + Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8]
+    ) -> &mut Self
+        where K::Output: Serialize
+    {
+        self.register(name, ns, StructureKind::ItemSpace, decode_key::<K>, decode_value::<T>)
+    }
+
+    /// Registers an [`super::iterable::IterableStorage`] storing items of type `T`.
+    #[inline]
+    pub fn iterable<T: FadromaDeserialize + Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8]
+    ) -> &mut Self {
+        self.register(name, ns, StructureKind::Iterable, raw_key, decode_value::<T>)
+    }
+
+    /// Registers a [`super::map::Map`] with keys of type `K` and values of type `V`.
+    #[inline]
+    pub fn map<K: DecodeKey, V: FadromaDeserialize + Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8]
+    ) -> &mut Self
+        where K::Output: Serialize
+    {
+        self.register(name, ns, StructureKind::Map, decode_key::<K>, decode_value::<V>)
+    }
+
+    /// Registers a [`super::map::InsertOnlyMap`] with keys of type `K` and values of type `V`.
+    #[inline]
+    pub fn insert_only_map<K: DecodeKey, V: FadromaDeserialize + Serialize>(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8]
+    ) -> &mut Self
+        where K::Output: Serialize
+    {
+        self.register(name, ns, StructureKind::InsertOnlyMap, decode_key::<K>, decode_value::<V>)
+    }
+
+    /// Registers a storage type with the given decoders. The key decoder
+    /// receives the key without the namespace and is not used by
+    /// [`StructureKind::SingleItem`] and [`StructureKind::Iterable`].
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        ns: &[u8],
+        kind: StructureKind,
+        key: Decoder,
+        value: Decoder
+    ) -> &mut Self {
+        self.structures.push(Structure {
+            name: name.into(),
+            ns: ns.to_vec(),
+            kind,
+            key,
+            value
+        });
+
+        self
+    }
+
+    /// Decodes all entries of the given storage.
+    pub fn inspect_storage(&self, storage: &dyn Storage) -> Report {
+        self.inspect(storage.range(None, None, Order::Ascending))
+    }
+
+    /// Decodes the given key-value pairs. Each entry is assigned to the storage
+    /// type with the longest namespace that its key starts with.
+    pub fn inspect(&self, entries: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>) -> Report {
+        let mut groups = vec![BTreeMap::new(); self.structures.len()];
+        let mut unrecognized = vec![];
+
+        for (key, value) in entries {
+            let found = self.structures.iter()
+                .enumerate()
+                .filter(|(_, x)| x.contains(&key))
+                .max_by_key(|(_, x)| x.ns.len());
+
+            match found {
+                Some((i, structure)) => {
+                    groups[i].insert(key[structure.ns.len()..].to_vec(), value);
+                },
+                None => unrecognized.push(RawEntry {
+                    key: hex(&key),
+                    value: hex(&value)
+                })
+            }
+        }
+
+        let structures = self.structures.iter()
+            .zip(groups)
+            .map(|(structure, entries)| structure.dump(entries))
+            .collect();
+
+        Report {
+            structures,
+            unrecognized
+        }
+    }
+}
+
+impl Structure {
+    #[inline]
+    fn contains(&self, key: &[u8]) -> bool {
+        match self.kind {
+            StructureKind::SingleItem => key == self.ns,
+            _ => key.starts_with(&self.ns)
+        }
+    }
+
+    fn dump(&self, entries: BTreeMap<Vec<u8>, Vec<u8>>) -> StructureDump {
+        let mut issues = Issues {
+            ns: &self.ns,
+            issues: vec![]
+        };
+
+        let value = match self.kind {
+            StructureKind::SingleItem => match entries.get(&[] as &[u8]) {
+                Some(value) => issues.check(&[], (self.value)(value)),
+                None => Value::Null
+            },
+            StructureKind::ItemSpace => Value::Array(entries.iter().map(|(key, value)|
+                json!({
+                    "key": issues.check(key, (self.key)(key)),
+                    "value": issues.check(key, (self.value)(value))
+                })
+            ).collect()),
+            StructureKind::Iterable => {
+                let items = self.items(&entries, &mut issues, |_, _| Some(UNEXPECTED_KEY));
+
+                Value::Array(items.into_iter().map(|(key, value)|
+                    issues.check(&key, (self.value)(&value))
+                ).collect())
+            },
+            StructureKind::Map => self.dump_map(&entries, &mut issues),
+            StructureKind::InsertOnlyMap => self.dump_insert_only_map(&entries, &mut issues)
+        };
+
+        StructureDump {
+            name: self.name.clone(),
+            namespace: display_bytes(&self.ns),
+            kind: self.kind,
+            value,
+            issues: issues.issues
+        }
+    }
+
+    fn dump_map(
+        &self,
+        entries: &BTreeMap<Vec<u8>, Vec<u8>>,
+        issues: &mut Issues
+    ) -> Value {
+        let mut keys = BTreeMap::new();
+        let items = self.items(entries, issues, |_, key| {
+            if key.starts_with(KEY_MAP) {
+                keys.insert(key.to_vec(), ());
+
+                None
+            } else {
+                Some(UNEXPECTED_KEY)
+            }
+        });
+
+        let mut result = vec![];
+
+        for (key, bytes) in items {
+            let entry = match issues.check_entry(&key, decode::<ItemEntry>(&bytes)) {
+                Some(entry) => entry,
+                None => {
+                    result.push(Value::Null);
+
+                    continue;
+                }
+            };
+
+            let map_key = match entry.key.strip_prefix(self.ns.as_slice()) {
+                Some(map_key) if map_key.starts_with(KEY_MAP) => map_key,
+                _ => {
+                    issues.push(&key, "The stored key is outside of the namespace.");
+                    result.push(Value::Null);
+
+                    continue;
+                }
+            };
+
+            match entries.get(map_key).map(|x| decode::<u64>(x)) {
+                Some(Ok(index)) if index.to_be_bytes() == key.as_slice() => {},
+                _ => issues.push(map_key, "The key doesn't point to the item that stores it.")
+            }
+
+            keys.remove(map_key);
+
+            result.push(json!({
+                "key": issues.check(map_key, (self.key)(&map_key[KEY_MAP.len()..])),
+                "value": issues.check(&key, (self.value)(&entry.item))
+            }));
+        }
+
+        for key in keys.into_keys() {
+            issues.push(&key, "The key doesn't point to any item.");
+        }
+
+        Value::Array(result)
+    }
+
+    fn dump_insert_only_map(
+        &self,
+        entries: &BTreeMap<Vec<u8>, Vec<u8>>,
+        issues: &mut Issues
+    ) -> Value {
+        let mut keys: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+        let mut invalid = vec![];
+
+        let items = self.items(entries, issues, |value, key| {
+            if !key.starts_with(KEY_MAP) {
+                return Some(UNEXPECTED_KEY);
+            }
+
+            match decode::<u64>(value) {
+                Ok(index) => {
+                    keys.insert(index, key.to_vec());
+                },
+                Err(err) => invalid.push((key.to_vec(), err))
+            }
+
+            None
+        });
+
+        for (key, err) in invalid {
+            issues.push(&key, err);
+        }
+
+        let result = items.into_iter().enumerate().map(|(index, (key, value))| {
+            let map_key = match keys.remove(&(index as u64)) {
+                Some(map_key) => issues.check(&map_key, (self.key)(&map_key[KEY_MAP.len()..])),
+                None => {
+                    issues.push(&key, "No key points to the item.");
+
+                    Value::Null
+                }
+            };
+
+            json!({
+                "key": map_key,
+                "value": issues.check(&key, (self.value)(&value))
+            })
+        }).collect();
+
+        for key in keys.into_values() {
+            issues.push(&key, "The key doesn't point to any item.");
+        }
+
+        Value::Array(result)
+    }
+
+    /// Returns the items of an [`super::iterable::IterableStorage`] in order together
+    /// with their keys. Entries which aren't part of it are passed to `other`.
+    fn items(
+        &self,
+        entries: &BTreeMap<Vec<u8>, Vec<u8>>,
+        issues: &mut Issues,
+        mut other: impl FnMut(&[u8], &[u8]) -> Option<&'static str>
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let len = match entries.get(KEY_LEN).map(|x| decode::<u64>(x)) {
+            Some(Ok(len)) => len,
+            Some(Err(err)) => {
+                issues.push(KEY_LEN, err);
+
+                0
+            },
+            None => 0
+        };
+
+        let mut items = vec![];
+
+        for (key, value) in entries {
+            if key == KEY_LEN {
+                continue;
+            }
+
+            if key.len() == 8 && !key.starts_with(KEY_MAP) {
+                let index = u64::from_be_bytes(key.as_slice().try_into().unwrap());
+
+                if index < len {
+                    items.push((key.clone(), value.clone()));
+                } else {
+                    issues.push(key, format!("The item is beyond the length of {}.", len));
+                }
+
+                continue;
+            }
+
+            if let Some(message) = other(value, key) {
+                issues.push(key, message);
+            }
+        }
+
+        if items.len() as u64 != len {
+            issues.push(KEY_LEN, format!(
+                "The length is {} but {} items are stored.",
+                len,
+                items.len()
+            ));
+        }
+
+        items
+    }
+}
+
+impl StructureDump {
+    /// Returns the decoded entries as `(key, value)` pairs. The key is `null` for
+    /// [`StructureKind::SingleItem`] and the index for [`StructureKind::Iterable`].
+    pub fn entries(&self) -> Vec<(Value, Value)> {
+        match (self.kind, &self.value) {
+            (StructureKind::SingleItem, Value::Null) => vec![],
+            (StructureKind::SingleItem, value) => vec![(Value::Null, value.clone())],
+            (StructureKind::Iterable, Value::Array(items)) => items.iter()
+                .enumerate()
+                .map(|(i, x)| (i.into(), x.clone()))
+                .collect(),
+            (_, Value::Array(items)) => items.iter()
+                .map(|x| (x["key"].clone(), x["value"].clone()))
+                .collect(),
+            _ => vec![]
+        }
+    }
+}
+
+impl Report {
+    /// Returns `true` if any of the storage types has issues
+    /// or if there are any entries that weren't recognized.
+    #[inline]
+    pub fn has_issues(&self) -> bool {
+        !self.unrecognized.is_empty() ||
+            self.structures.iter().any(|x| !x.issues.is_empty())
+    }
+
+    /// Serializes the report to pretty printed JSON.
+    #[inline]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for structure in &self.structures {
+            writeln!(
+                f,
+                "[{}] {:?} \"{}\"",
+                structure.name,
+                structure.kind,
+                structure.namespace
+            )?;
+
+            for (key, value) in structure.entries() {
+                if key.is_null() {
+                    writeln!(f, "  = {}", value)?;
+                } else {
+                    writeln!(f, "  {} = {}", key, value)?;
+                }
+            }
+
+            for issue in &structure.issues {
+                writeln!(f, "  ! 0x{}: {}", issue.key, issue.message)?;
+            }
+        }
+
+        if !self.unrecognized.is_empty() {
+            writeln!(f, "[?] unrecognized")?;
+
+            for entry in &self.unrecognized {
+                writeln!(f, "  0x{} = 0x{}", entry.key, entry.value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Issues<'a> {
+    ns: &'a [u8],
+    issues: Vec<Issue>
+}
+
+impl<'a> Issues<'a> {
+    fn push(&mut self, key: &[u8], message: impl ToString) {
+        self.issues.push(Issue {
+            key: hex(&[self.ns, key].concat()),
+            message: message.to_string()
+        });
+    }
+
+    /// Returns the decoded value or `null` if decoding failed, recording the error.
+    fn check(&mut self, key: &[u8], result: StdResult<Value>) -> Value {
+        self.check_entry(key, result).unwrap_or(Value::Null)
+    }
+
+    fn check_entry<T>(&mut self, key: &[u8], result: StdResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(key, err);
+
+                None
+            }
+        }
+    }
+}
+
+#[inline]
+fn decode<T: FadromaDeserialize>(bytes: &[u8]) -> StdResult<T> {
+    super::deserialize(bytes)
+}
+
+#[inline]
+fn to_json<T: Serialize>(value: &T) -> StdResult<Value> {
+    serde_json::to_value(value).map_err(|e|
+        StdError::serialize_err(std::any::type_name::<T>(), e)
+    )
+}
+
+#[inline]
+fn hex(bytes: &[u8]) -> String {
+    HexBinary::from(bytes).to_hex()
+}
+
+/// Renders namespaces such as `b"balances"` as text.
+fn display_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.chars().all(|x| x.is_ascii_graphic()) => text.to_string(),
+        _ => format!("0x{}", hex(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::{testing::mock_dependencies, Uint128},
+        storage::{
+            Namespace, SingleItem, ItemSpace, TypedKey,
+            iterable::IterableStorage,
+            map::{Map, InsertOnlyMap}
+        },
+        namespace
+    };
+
+    namespace!(ItemNs, b"item");
+    namespace!(SpaceNs, b"space");
+    namespace!(ListNs, b"list");
+    namespace!(MapNs, b"map");
+    namespace!(InsertOnlyNs, b"insert");
+
+    fn inspector() -> Inspector {
+        let mut inspector = Inspector::new();
+        inspector
+            .single_item::<String>("item", ItemNs::NAMESPACE)
+            .item_space::<TypedKey<u64>, bool>("space", SpaceNs::NAMESPACE)
+            .iterable::<u32>("list", ListNs::NAMESPACE)
+            .map::<TypedKey<&str>, Uint128>("map", MapNs::NAMESPACE)
+            .insert_only_map::<TypedKey<&str>, u8>("insert", InsertOnlyNs::NAMESPACE);
+
+        inspector
+    }
+
+    #[test]
+    fn decodes_storage_types() {
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+
+        SingleItem::<String, ItemNs>::new().save(storage, &"value".into()).unwrap();
+        ItemSpace::<bool, SpaceNs, TypedKey<u64>>::new().save(storage, &7, &true).unwrap();
+
+        let mut list = IterableStorage::<u32, _>::new(TypedKey::from(&"list"));
+        list.push(storage, &1).unwrap();
+        list.push(storage, &2).unwrap();
+
+        let keys = ["a", "b", "c"];
+
+        let mut map = Map::<TypedKey<&str>, Uint128, MapNs>::new();
+        map.insert(storage, &keys[0], &Uint128::new(1)).unwrap();
+        map.insert(storage, &keys[1], &Uint128::new(2)).unwrap();
+        map.insert(storage, &keys[2], &Uint128::new(3)).unwrap();
+        map.remove(storage, &keys[0]).unwrap();
+
+        let mut insert_only = InsertOnlyMap::<TypedKey<&str>, u8, InsertOnlyNs>::new();
+        insert_only.insert(storage, &keys[1], &5).unwrap();
+
+        storage.set(b"other", b"x");
+
+        let report = inspector().inspect_storage(storage);
+        assert!(report.structures.iter().all(|x| x.issues.is_empty()));
+
+        let values = report.structures.iter().map(|x| x.value.clone()).collect::<Vec<_>>();
+        assert_eq!(values, vec![
+            json!("value"),
+            json!([{ "key": 7, "value": true }]),
+            json!([1, 2]),
+            json!([{ "key": "c", "value": "3" }, { "key": "b", "value": "2" }]),
+            json!([{ "key": "b", "value": 5 }])
+        ]);
+
+        assert_eq!(report.unrecognized, vec![RawEntry {
+            key: hex(b"other"),
+            value: hex(b"x")
+        }]);
+        assert!(report.has_issues());
+
+        let text = report.to_string();
+        assert!(text.contains("[map] Map \"map\"\n  \"c\" = \"3\"\n  \"b\" = \"2\"\n"));
+        assert!(text.contains("[list] Iterable \"list\"\n  0 = 1\n  1 = 2\n"));
+    }
+
+    #[test]
+    fn reports_inconsistent_entries() {
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+
+        let keys = ["a", "b"];
+
+        let mut map = Map::<TypedKey<&str>, Uint128, MapNs>::new();
+        map.insert(storage, &keys[0], &Uint128::new(1)).unwrap();
+        map.insert(storage, &keys[1], &Uint128::new(2)).unwrap();
+
+        // Remove the second item without updating the length or the key.
+        storage.remove(&[MapNs::NAMESPACE, &1u64.to_be_bytes()].concat());
+        // Not a u32.
+        super::super::save(storage, [ListNs::NAMESPACE, KEY_LEN].concat(), &1u64).unwrap();
+        storage.set(&[ListNs::NAMESPACE, &0u64.to_be_bytes()].concat(), &[1]);
+        storage.set(&[ListNs::NAMESPACE, b"extra"].concat(), &[1]);
+
+        let report = inspector().inspect_storage(storage);
+
+        let list = &report.structures[2];
+        assert_eq!(list.value, json!([null]));
+        assert_eq!(list.issues, vec![
+            Issue {
+                key: hex(b"listextra"),
+                message: UNEXPECTED_KEY.into()
+            },
+            Issue {
+                key: hex(&[ListNs::NAMESPACE, &0u64.to_be_bytes()].concat()),
+                message: "Error parsing into type u32: Attempted to read 1 bytes but 0 remain.".into()
+            }
+        ]);
+
+        let map = &report.structures[3];
+        assert_eq!(map.value, json!([{ "key": "a", "value": "1" }]));
+        assert_eq!(map.issues, vec![
+            Issue {
+                key: hex(b"mapindex"),
+                message: "The length is 2 but 1 items are stored.".into()
+            },
+            Issue {
+                key: hex(b"mapkeyb"),
+                message: "The key doesn't point to any item.".into()
+            }
+        ]);
+    }
+
+    #[test]
+    fn parses_dumps() {
+        let entries = parse_dump(br#"[{ "key": "6974656d", "value": "BXZhbHVl" }]"#).unwrap();
+        assert_eq!(entries, vec![(b"item".to_vec(), b"\x05value".to_vec())]);
+
+        let report = inspector().inspect(entries);
+        assert_eq!(report.structures[0].value, json!("value"));
+
+        assert!(parse_dump(br#"[{ "key": "zz", "value": "" }]"#).is_err());
+        assert!(parse_dump(br#"{ "entries": [] }"#).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{mem, marker::PhantomData};
+use std::{mem, marker::PhantomData, ops::{Range, RangeBounds, Bound}};
 
 use crate::{
     bin_serde::{FadromaSerialize, FadromaDeserialize},
@@ -7,6 +7,10 @@ use crate::{
 };
 use super::{Key, not_found_error};
 
+/// The items removed by a call to [`IterableStorage::drain_batch`] or
+/// [`super::map::Map::drain_batch`] together with the range to continue from.
+pub type DrainBatch<T> = (Vec<T>, Option<Range<u64>>);
+
 /// Stores items in a way that allows for iterating over them
 /// in a sequential order just like a Vec. It's also possible to
 /// retrieve or update inidividual items based on their index.
@@ -19,6 +23,7 @@ pub struct IterableStorage<T: FadromaSerialize + FadromaDeserialize, K: Key> {
 impl<T: FadromaSerialize + FadromaDeserialize, K: Key> IterableStorage<T, K> {
     const KEY_INDEX: &'static [u8] = b"index";
     const ERR_MSG: &str = "IterableStorage: index out of bounds.";
+    const ERR_LIMIT: &str = "IterableStorage: limit must be bigger than 0.";
 
     /// Creates an instance for the given namespace.
     /// The following namespaces are reserved by `IterableStorage`:
@@ -362,6 +367,300 @@ impl<T: FadromaSerialize + FadromaDeserialize, K: Key> IterableStorage<T, K> {
         Ok(result.unwrap_or(0))
     }
 
+    /// Appends all of the given items to the end of the collection.
+    /// The length is only written once regardless of how many items there are.
+    pub fn extend<'a>(
+        &mut self,
+        storage: &mut dyn Storage,
+        items: impl IntoIterator<Item = &'a T>
+    ) -> StdResult<()>
+        where T: 'a
+    {
+        let mut len = self.len(storage)?;
+
+        for item in items {
+            super::save(storage, self.key(len), item)?;
+            len += 1;
+        }
+
+        self.save_len(storage, len)
+    }
+
+    /// Removes all elements at or after the given index.
+    /// Does nothing if the collection is not longer than `len`.
+    #[inline]
+    pub fn truncate(&mut self, storage: &mut dyn Storage, len: u64) -> StdResult<()> {
+        self.truncate_batch(storage, len, u64::MAX)?;
+
+        Ok(())
+    }
+
+    /// Removes at most `limit` elements from the end of the collection until it is
+    /// no longer than `len`. Returns the number of elements that are left to remove
+    /// so that it can be called again in subsequent transactions until it returns 0.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{iterable::IterableStorage, CompositeKey};
+    /// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// let key = CompositeKey::new(&[b"numbers"]);
+    /// let mut iterable = IterableStorage::<u8, _>::new(key);
+    /// iterable.extend(storage, &[1, 2, 3, 4, 5])?;
+    /// 
+    /// assert_eq!(iterable.truncate_batch(storage, 1, 3)?, 1);
+    /// assert_eq!(iterable.len(storage)?, 2);
+    /// 
+    /// assert_eq!(iterable.truncate_batch(storage, 1, 3)?, 0);
+    /// assert_eq!(iterable.len(storage)?, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn truncate_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        len: u64,
+        limit: u64
+    ) -> StdResult<u64> {
+        if limit == 0 {
+            return Err(StdError::generic_err(Self::ERR_LIMIT));
+        }
+
+        let current = self.len(storage)?;
+
+        if current <= len {
+            return Ok(0);
+        }
+
+        let new_len = len.max(current.saturating_sub(limit));
+
+        for index in new_len..current {
+            storage.remove(&self.key(index));
+        }
+
+        self.save_len(storage, new_len)?;
+
+        Ok(new_len - len)
+    }
+
+    /// Removes all elements.
+    #[inline]
+    pub fn clear(&mut self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.truncate(storage, 0)
+    }
+
+    /// Removes at most `limit` elements. Returns the number of elements that are left.
+    /// See [`IterableStorage::truncate_batch`].
+    #[inline]
+    pub fn clear_batch(&mut self, storage: &mut dyn Storage, limit: u64) -> StdResult<u64> {
+        self.truncate_batch(storage, 0, limit)
+    }
+
+    /// Removes all elements for which `predicate` returns `false`.
+    /// Removed elements are replaced by the last element like in
+    /// [`IterableStorage::swap_remove`], so ordering is not preserved.
+    #[inline]
+    pub fn retain(
+        &mut self,
+        storage: &mut dyn Storage,
+        predicate: impl FnMut(&T) -> bool
+    ) -> StdResult<()> {
+        self.retain_batch(storage, 0, u64::MAX, predicate)?;
+
+        Ok(())
+    }
+
+    /// Same as [`IterableStorage::retain`] but only checks at most `limit` elements,
+    /// starting at the index given by `cursor`. Returns the cursor to continue from
+    /// in the next call or [`None`] if all elements have been checked.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{iterable::IterableStorage, CompositeKey};
+    /// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// let key = CompositeKey::new(&[b"numbers"]);
+    /// let mut iterable = IterableStorage::<u8, _>::new(key);
+    /// iterable.extend(storage, &[1, 2, 3, 4, 5, 6])?;
+    /// 
+    /// let mut cursor = Some(0);
+    /// 
+    /// while let Some(start) = cursor {
+    ///     cursor = iterable.retain_batch(storage, start, 2, |x| x % 2 == 0)?;
+    /// }
+    /// 
+    /// let items = iterable.iter(storage)?.collect::<StdResult<Vec<_>>>()?;
+    /// assert_eq!(items, [6, 2, 4]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retain_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        cursor: u64,
+        limit: u64,
+        mut predicate: impl FnMut(&T) -> bool
+    ) -> StdResult<Option<u64>> {
+        if limit == 0 {
+            return Err(StdError::generic_err(Self::ERR_LIMIT));
+        }
+
+        let mut index = cursor;
+        let mut len = self.len(storage)?;
+        let mut checked = 0;
+
+        while index < len {
+            if checked == limit {
+                return Ok(Some(index));
+            }
+
+            checked += 1;
+
+            let item = self.get_or_error(storage, index)?;
+
+            if predicate(&item) {
+                index += 1;
+            } else {
+                self.swap_remove(storage, index)?;
+                len -= 1;
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Removes the elements in the given range and returns them. The elements
+    /// after the range are moved back in order to fill the gap which requires
+    /// a storage write for each one of them. Draining from the end of the
+    /// collection, such as `len - n..`, only touches the drained elements.
+    /// Returns an error if the range is out of bounds.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{iterable::IterableStorage, CompositeKey};
+    /// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// let key = CompositeKey::new(&[b"numbers"]);
+    /// let mut iterable = IterableStorage::<u8, _>::new(key);
+    /// iterable.extend(storage, &[1, 2, 3, 4, 5])?;
+    /// 
+    /// assert_eq!(iterable.drain(storage, 1..3)?, [2, 3]);
+    /// 
+    /// let items = iterable.iter(storage)?.collect::<StdResult<Vec<_>>>()?;
+    /// assert_eq!(items, [1, 4, 5]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drain(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>
+    ) -> StdResult<Vec<T>> {
+        let len = self.len(storage)?;
+        let (start, end) = resolve_range(range, len)
+            .ok_or_else(|| StdError::generic_err(Self::ERR_MSG))?;
+
+        let mut drained = Vec::with_capacity((end - start) as usize);
+
+        for index in start..end {
+            drained.push(self.get_or_error(storage, index)?);
+        }
+
+        for index in end..len {
+            let item: T = self.get_or_error(storage, index)?;
+            super::save(storage, self.key(index - (end - start)), &item)?;
+        }
+
+        self.truncate(storage, len - (end - start))?;
+
+        Ok(drained)
+    }
+
+    /// Same as [`IterableStorage::drain`] but only removes at most `limit` elements.
+    /// Returns the removed elements together with the range to pass in the next call
+    /// or [`None`] if all elements in the range have been removed. Removed elements
+    /// are replaced by the last element like in [`IterableStorage::swap_remove`] so
+    /// each call only touches the removed elements and the ones that take their place.
+    /// Because of that, the order of both the remaining and the removed elements is not
+    /// preserved. Returns an error if the range is out of bounds or `limit` is 0.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{iterable::IterableStorage, CompositeKey};
+    /// # use fadroma::cosmwasm_std::{StdResult, testing::mock_dependencies};
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// let key = CompositeKey::new(&[b"numbers"]);
+    /// let mut iterable = IterableStorage::<u8, _>::new(key);
+    /// iterable.extend(storage, &[1, 2, 3, 4, 5, 6])?;
+    /// 
+    /// let mut range = Some(1..4);
+    /// let mut drained = vec![];
+    /// 
+    /// while let Some(current) = range {
+    ///     let (items, next) = iterable.drain_batch(storage, current, 2)?;
+    ///     drained.extend(items);
+    ///     range = next;
+    /// }
+    /// 
+    /// drained.sort();
+    /// assert_eq!(drained, [2, 3, 4]);
+    /// 
+    /// let items = iterable.iter(storage)?.collect::<StdResult<Vec<_>>>()?;
+    /// assert_eq!(items, [1, 6, 5]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drain_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>,
+        limit: u64
+    ) -> StdResult<DrainBatch<T>> {
+        if limit == 0 {
+            return Err(StdError::generic_err(Self::ERR_LIMIT));
+        }
+
+        let mut len = self.len(storage)?;
+        let (mut start, mut end) = resolve_range(range, len)
+            .ok_or_else(|| StdError::generic_err(Self::ERR_MSG))?;
+
+        let mut drained = Vec::with_capacity((end - start).min(limit) as usize);
+
+        while start < end {
+            if drained.len() as u64 == limit {
+                return Ok((drained, Some(start..end)));
+            }
+
+            drained.push(self.get_or_error(storage, start)?);
+
+            // When the range reaches the end, the element that takes the place
+            // of the removed one is also in the range and has to be removed next.
+            let is_tail = end == len;
+            self.swap_remove(storage, start)?;
+            len -= 1;
+
+            if is_tail {
+                end -= 1;
+            } else {
+                start += 1;
+            }
+        }
+
+        Ok((drained, None))
+    }
+
     fn increment_index(&mut self, storage: &mut dyn Storage) -> StdResult<u64> {
         let current = self.len(storage)?;
         let new = current + 1;
@@ -382,6 +681,14 @@ impl<T: FadromaSerialize + FadromaDeserialize, K: Key> IterableStorage<T, K> {
         Ok(new)
     }
 
+    #[inline]
+    fn save_len(&mut self, storage: &mut dyn Storage, len: u64) -> StdResult<()> {
+        super::save(storage, self.key_len(), &len)?;
+        self.len = Some(len);
+
+        Ok(())
+    }
+
     #[inline]
     fn key(&self, index: u64) -> Vec<u8> {
         let mut key = Vec::with_capacity(self.ns.size() + 8);
@@ -554,6 +861,28 @@ impl<'storage, T: FadromaDeserialize> DoubleEndedIterator for Iter<'storage, T>
 
 impl<'storage, T: FadromaDeserialize> ExactSizeIterator for Iter<'storage, T> { }
 
+/// Converts the range into a start and an exclusive end
+/// or returns [`None`] if it is not within `0..len`.
+pub(crate) fn resolve_range(range: impl RangeBounds<u64>, len: u64) -> Option<(u64, u64)> {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.checked_add(1)?,
+        Bound::Unbounded => 0
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(end) => end.checked_add(1)?,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len
+    };
+
+    if start > end || end > len {
+        return None;
+    }
+
+    Some((start, end))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -768,4 +1097,63 @@ mod tests {
 
         assert_eq!(storage.len(&deps.storage).unwrap(), 0);
     }
+
+    #[test]
+    fn iterable_storage_bulk() {
+        let ref mut deps = mock_dependencies();
+
+        let key = CompositeKey::new(&[b"numbers"]);
+        let mut storage = IterableStorage::<u8, _>::new(key);
+
+        let items = (0..20).collect::<Vec<u8>>();
+        storage.extend(&mut deps.storage, &items).unwrap();
+        assert_eq!(storage.len(&deps.storage).unwrap(), 20);
+
+        let err = storage.drain(&mut deps.storage, 15..21).unwrap_err();
+        assert_eq!(err, StdError::generic_err("IterableStorage: index out of bounds."));
+
+        assert_eq!(storage.drain(&mut deps.storage, 15..).unwrap(), [15, 16, 17, 18, 19]);
+        assert_eq!(storage.drain(&mut deps.storage, ..=1).unwrap(), [0, 1]);
+        assert!(storage.drain(&mut deps.storage, 3..3).unwrap().is_empty());
+
+        let all = |storage: &IterableStorage<u8, _>, deps: &crate::cosmwasm_std::OwnedDeps<_, _, _>|
+            storage.iter(&deps.storage).unwrap().map(|x| x.unwrap()).collect::<Vec<u8>>();
+        assert_eq!(all(&storage, deps), (2..15).collect::<Vec<u8>>());
+
+        storage.retain(&mut deps.storage, |x| x % 3 != 0).unwrap();
+        let mut retained = all(&storage, deps);
+        retained.sort();
+        assert_eq!(retained, [2, 4, 5, 7, 8, 10, 11, 13, 14]);
+
+        storage.truncate(&mut deps.storage, 10).unwrap();
+        assert_eq!(storage.len(&deps.storage).unwrap(), 9);
+
+        assert_eq!(storage.clear_batch(&mut deps.storage, 5).unwrap(), 4);
+        assert_eq!(storage.get(&deps.storage, 4).unwrap(), None);
+        assert_eq!(storage.clear_batch(&mut deps.storage, 5).unwrap(), 0);
+        assert_eq!(storage.len(&deps.storage).unwrap(), 0);
+        assert_eq!(storage.get(&deps.storage, 0).unwrap(), None);
+
+        let err = StdError::generic_err("IterableStorage: limit must be bigger than 0.");
+        assert_eq!(storage.clear_batch(&mut deps.storage, 0).unwrap_err(), err);
+        assert_eq!(storage.retain_batch(&mut deps.storage, 0, 0, |_| true).unwrap_err(), err);
+        assert_eq!(storage.drain_batch(&mut deps.storage, .., 0).unwrap_err(), err);
+
+        storage.extend(&mut deps.storage, &items).unwrap();
+
+        let (drained, next) = storage.drain_batch(&mut deps.storage, 5..15, 4).unwrap();
+        assert_eq!(drained, [5, 6, 7, 8]);
+        assert_eq!(next, Some(9..15));
+        assert_eq!(all(&storage, deps), [0, 1, 2, 3, 4, 19, 18, 17, 16, 9, 10, 11, 12, 13, 14, 15]);
+
+        // Once the remaining range reaches the end, it is drained from there.
+        let (drained, next) = storage.drain_batch(&mut deps.storage, 9..15, 10).unwrap();
+        assert_eq!(drained, [9, 10, 14, 13, 12, 11]);
+        assert_eq!(next, None);
+        assert_eq!(all(&storage, deps), [0, 1, 2, 3, 4, 19, 18, 17, 16, 15]);
+
+        // A new instance loads the length from storage.
+        let storage = IterableStorage::<u8, _>::new(key);
+        assert_eq!(storage.len(&deps.storage).unwrap(), 10);
+    }
 }
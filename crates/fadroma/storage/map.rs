@@ -1,14 +1,14 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::RangeBounds};
 
 use crate::{
     self as fadroma,
     bin_serde::{FadromaSerialize, FadromaDeserialize},
-    cosmwasm_std::{Storage, Deps, DepsMut, Binary, StdResult},
+    cosmwasm_std::{Storage, Deps, DepsMut, Binary, StdResult, StdError},
     core::{Canonize, Humanize}
 };
 use super::{
-    Namespace, Key, StaticKey,
-    iterable::{IterableStorage, Iter},
+    Namespace, Key, DecodeKey, StaticKey,
+    iterable::{IterableStorage, Iter, DrainBatch, resolve_range},
     serialize, deserialize, not_found_error
 };
 
@@ -48,8 +48,15 @@ pub struct MapValueIter<'storage, T: FadromaDeserialize> {
     data: PhantomData<T>
 }
 
+/// Iterator over the keys and values of [`Map`].
+pub struct MapIter<'storage, K: DecodeKey, V: FadromaDeserialize> {
+    inner: Iter<'storage, ItemEntry>,
+    prefix_len: usize,
+    data: PhantomData<(K, V)>
+}
+
 #[derive(FadromaSerialize, FadromaDeserialize)]
-struct ItemEntry {
+pub(crate) struct ItemEntry {
     // Using Binary instead of Vec<u8> because the former
     // serializes and deserializes bytes more efficiently.
     // Otherwise, Vec will use the generic implementation
@@ -57,8 +64,8 @@ struct ItemEntry {
     // the entire slice.
 
     /// The key that maps to the index in the keys of the map.
-    key: Binary,
-    item: Binary
+    pub(crate) key: Binary,
+    pub(crate) item: Binary
 }
 
 impl<
@@ -244,11 +251,7 @@ impl<
         let key = self.inner.map_key(&key.into());
         let exists = match self.inner.load_index(storage, &key)? {
             Some(index) => {
-                storage.remove(&key);
-
-                if let Some(swapped) = self.inner.iterable.swap_remove(storage, index)? {
-                    self.inner.save_index(storage, &swapped.key.0, index)?;
-                }
+                self.remove_at(storage, &key, index)?;
 
                 true
             }
@@ -258,6 +261,308 @@ impl<
         Ok(exists)
     }
 
+    /// Inserts all of the given entries, overwriting existing values.
+    pub fn extend<'a, Q: Into<K>>(
+        &mut self,
+        storage: &mut dyn Storage,
+        entries: impl IntoIterator<Item = (Q, &'a V)>
+    ) -> StdResult<()>
+        where V: 'a
+    {
+        for (key, value) in entries {
+            self.insert(storage, key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the entries at or after the given position in iteration order.
+    #[inline]
+    pub fn truncate(&mut self, storage: &mut dyn Storage, len: u64) -> StdResult<()> {
+        self.truncate_batch(storage, len, u64::MAX)?;
+
+        Ok(())
+    }
+
+    /// Removes at most `limit` entries from the end of the map until it has
+    /// no more than `len` entries. Returns the number of entries that are left to
+    /// remove so that it can be called again in subsequent transactions until it returns 0.
+    pub fn truncate_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        len: u64,
+        limit: u64
+    ) -> StdResult<u64> {
+        if limit == 0 {
+            return Err(StdError::generic_err("Map: limit must be bigger than 0."));
+        }
+
+        let current = self.inner.iterable.len(storage)?;
+
+        if current <= len {
+            return Ok(0);
+        }
+
+        let new_len = len.max(current.saturating_sub(limit));
+
+        for index in new_len..current {
+            let entry = self.inner.iterable.get_or_error(storage, index)?;
+            storage.remove(&entry.key.0);
+        }
+
+        self.inner.iterable.truncate(storage, new_len)?;
+
+        Ok(new_len - len)
+    }
+
+    /// Removes all entries.
+    #[inline]
+    pub fn clear(&mut self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.truncate(storage, 0)
+    }
+
+    /// Removes at most `limit` entries. Returns the number of entries that are left.
+    /// See [`Map::truncate_batch`].
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{map::Map, TypedKey};
+    /// # use fadroma::cosmwasm_std::{
+    /// #     StdResult,
+    /// #     testing::mock_dependencies
+    /// # };
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// fadroma::namespace!(NumbersNs, b"numbers");
+    /// let mut map = Map::<TypedKey<u64>, u64, NumbersNs>::new();
+    /// 
+    /// let keys = (0..10).collect::<Vec<u64>>();
+    /// map.extend(storage, keys.iter().zip(&keys))?;
+    /// 
+    /// // Each call could be made in a separate transaction.
+    /// assert_eq!(map.clear_batch(storage, 4)?, 6);
+    /// assert_eq!(map.clear_batch(storage, 4)?, 2);
+    /// assert_eq!(map.clear_batch(storage, 4)?, 0);
+    /// 
+    /// assert_eq!(map.get(storage, &1)?, None);
+    /// assert_eq!(map.values(storage)?.len(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn clear_batch(&mut self, storage: &mut dyn Storage, limit: u64) -> StdResult<u64> {
+        self.truncate_batch(storage, 0, limit)
+    }
+
+    /// Same as [`Map::retain`] but passes the encoded key to `predicate` which
+    /// allows it to be used with keys that don't implement [`DecodeKey`].
+    /// The key doesn't include the namespace of the map.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{map::Map, TypedKey2};
+    /// # use fadroma::cosmwasm_std::{
+    /// #     StdResult,
+    /// #     testing::mock_dependencies
+    /// # };
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// fadroma::namespace!(BalancesNs, b"balances");
+    /// let mut map = Map::<TypedKey2<String, String>, u64, BalancesNs>::new();
+    /// 
+    /// let (alice, bob) = ("alice".to_string(), "bob".to_string());
+    /// let token = "token".to_string();
+    /// map.insert(storage, (&alice, &token), &100)?;
+    /// map.insert(storage, (&bob, &token), &0)?;
+    /// 
+    /// map.retain_raw(storage, |_, balance| *balance > 0)?;
+    /// 
+    /// assert_eq!(map.get(storage, (&alice, &token))?, Some(100));
+    /// assert_eq!(map.get(storage, (&bob, &token))?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn retain_raw(
+        &mut self,
+        storage: &mut dyn Storage,
+        predicate: impl FnMut(&[u8], &V) -> bool
+    ) -> StdResult<()> {
+        self.retain_batch_raw(storage, 0, u64::MAX, predicate)?;
+
+        Ok(())
+    }
+
+    /// Same as [`Map::retain_batch`] but passes the encoded key to `predicate`.
+    /// See [`Map::retain_raw`].
+    #[inline]
+    pub fn retain_batch_raw(
+        &mut self,
+        storage: &mut dyn Storage,
+        cursor: u64,
+        limit: u64,
+        mut predicate: impl FnMut(&[u8], &V) -> bool
+    ) -> StdResult<Option<u64>> {
+        self.retain_impl(storage, cursor, limit, |key, value| Ok(predicate(key, value)))
+    }
+
+    /// Same as [`Map::drain`] but returns the encoded keys which
+    /// allows it to be used with keys that don't implement [`DecodeKey`].
+    /// The keys don't include the namespace of the map.
+    #[inline]
+    pub fn drain_raw(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>
+    ) -> StdResult<Vec<(Vec<u8>, V)>> {
+        self.drain_impl(storage, range, |key| Ok(key.to_vec()))
+    }
+
+    /// Same as [`Map::drain_batch`] but returns the encoded keys.
+    /// See [`Map::drain_raw`].
+    #[inline]
+    pub fn drain_batch_raw(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>,
+        limit: u64
+    ) -> StdResult<DrainBatch<(Vec<u8>, V)>> {
+        self.drain_batch_impl(storage, range, limit, |key| Ok(key.to_vec()))
+    }
+
+    fn retain_impl(
+        &mut self,
+        storage: &mut dyn Storage,
+        cursor: u64,
+        limit: u64,
+        mut predicate: impl FnMut(&[u8], &V) -> StdResult<bool>
+    ) -> StdResult<Option<u64>> {
+        if limit == 0 {
+            return Err(StdError::generic_err("Map: limit must be bigger than 0."));
+        }
+
+        let prefix_len = N::NAMESPACE.len() + KEY_NS.size();
+
+        let mut index = cursor;
+        let mut len = self.inner.iterable.len(storage)?;
+        let mut checked = 0;
+
+        while index < len {
+            if checked == limit {
+                return Ok(Some(index));
+            }
+
+            checked += 1;
+
+            let entry = self.inner.iterable.get_or_error(storage, index)?;
+            let value = deserialize(&entry.item.0)?;
+
+            if predicate(&entry.key.0[prefix_len..], &value)? {
+                index += 1;
+            } else {
+                self.remove_at(storage, &entry.key.0, index)?;
+                len -= 1;
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn drain_impl<T>(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>,
+        decode: impl Fn(&[u8]) -> StdResult<T>
+    ) -> StdResult<Vec<(T, V)>> {
+        let prefix_len = N::NAMESPACE.len() + KEY_NS.size();
+
+        let len = self.inner.iterable.len(storage)?;
+        let (start, end) = resolve_range(range, len)
+            .ok_or_else(|| StdError::generic_err("Map: range out of bounds."))?;
+
+        let entries = self.inner.iterable.drain(storage, start..end)?;
+
+        for index in start..(len - (end - start)) {
+            let entry = self.inner.iterable.get_or_error(storage, index)?;
+            self.inner.save_index(storage, &entry.key.0, index)?;
+        }
+
+        let mut drained = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            storage.remove(&entry.key.0);
+
+            let key = decode(&entry.key.0[prefix_len..])?;
+            drained.push((key, deserialize(&entry.item.0)?));
+        }
+
+        Ok(drained)
+    }
+
+    fn drain_batch_impl<T>(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>,
+        limit: u64,
+        decode: impl Fn(&[u8]) -> StdResult<T>
+    ) -> StdResult<DrainBatch<(T, V)>> {
+        if limit == 0 {
+            return Err(StdError::generic_err("Map: limit must be bigger than 0."));
+        }
+
+        let prefix_len = N::NAMESPACE.len() + KEY_NS.size();
+
+        let mut len = self.inner.iterable.len(storage)?;
+        let (mut start, mut end) = resolve_range(range, len)
+            .ok_or_else(|| StdError::generic_err("Map: range out of bounds."))?;
+
+        let mut drained = Vec::with_capacity((end - start).min(limit) as usize);
+
+        while start < end {
+            if drained.len() as u64 == limit {
+                return Ok((drained, Some(start..end)));
+            }
+
+            let entry = self.inner.iterable.get_or_error(storage, start)?;
+            let key = decode(&entry.key.0[prefix_len..])?;
+            drained.push((key, deserialize(&entry.item.0)?));
+
+            // See IterableStorage::drain_batch
+            let is_tail = end == len;
+            self.remove_at(storage, &entry.key.0, start)?;
+            len -= 1;
+
+            if is_tail {
+                end -= 1;
+            } else {
+                start += 1;
+            }
+        }
+
+        Ok((drained, None))
+    }
+
+    /// Removes the entry at the given index together with its key
+    /// and updates the index of the entry that took its place.
+    fn remove_at(
+        &mut self,
+        storage: &mut dyn Storage,
+        key: &[u8],
+        index: u64
+    ) -> StdResult<()> {
+        storage.remove(key);
+
+        if let Some(swapped) = self.inner.iterable.swap_remove(storage, index)? {
+            self.inner.save_index(storage, &swapped.key.0, index)?;
+        }
+
+        Ok(())
+    }
+
     #[inline]
     fn get_impl(&self, storage: &dyn Storage, key: &K) -> StdResult<Option<V>> {
         let Ok(Some(entry)) = self.inner.get_impl(storage, key) else {
@@ -278,6 +583,112 @@ impl<
     }
 }
 
+impl<
+    K: DecodeKey,
+    V: FadromaSerialize + FadromaDeserialize,
+    N: Namespace
+> Map<K, V, N> {
+    /// Returns an iterator over all of the keys and values stored by the map.
+    /// The keys are decoded using [`DecodeKey`]. See [`super::Reversible`]
+    /// for keys with multiple segments.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use fadroma::storage::{map::Map, TypedKey};
+    /// # use fadroma::cosmwasm_std::{
+    /// #     StdResult,
+    /// #     testing::mock_dependencies
+    /// # };
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// fadroma::namespace!(NumbersNs, b"numbers");
+    /// let mut map = Map::<TypedKey<String>, u8, NumbersNs>::new();
+    /// 
+    /// let keys = ["one".to_string(), "two".to_string()];
+    /// map.insert(storage, &keys[0], &1)?;
+    /// map.insert(storage, &keys[1], &2)?;
+    /// 
+    /// let entries = map.iter(storage)?.collect::<StdResult<Vec<_>>>()?;
+    /// assert_eq!(entries, [("one".to_string(), 1), ("two".to_string(), 2)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage
+    ) -> StdResult<MapIter<'storage, K, V>> {
+        Ok(MapIter {
+            inner: self.inner.iterable.iter(storage)?,
+            prefix_len: N::NAMESPACE.len() + KEY_NS.size(),
+            data: PhantomData
+        })
+    }
+
+    /// Removes all entries for which `predicate` returns `false`.
+    /// Removed entries are replaced by the last one, so the
+    /// iteration order is not preserved. See [`Map::retain_raw`]
+    /// for keys that can't be decoded.
+    #[inline]
+    pub fn retain(
+        &mut self,
+        storage: &mut dyn Storage,
+        predicate: impl FnMut(&K::Output, &V) -> bool
+    ) -> StdResult<()> {
+        self.retain_batch(storage, 0, u64::MAX, predicate)?;
+
+        Ok(())
+    }
+
+    /// Same as [`Map::retain`] but only checks at most `limit` entries, starting
+    /// at the position given by `cursor`. Returns the cursor to continue from
+    /// in the next call or [`None`] if all entries have been checked.
+    #[inline]
+    pub fn retain_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        cursor: u64,
+        limit: u64,
+        mut predicate: impl FnMut(&K::Output, &V) -> bool
+    ) -> StdResult<Option<u64>> {
+        self.retain_impl(storage, cursor, limit, |key, value| {
+            K::decode_key(key).map(|key| predicate(&key, value))
+        })
+    }
+
+    /// Removes the entries in the given range of positions in iteration order and
+    /// returns them. The entries after the range are moved back in order to fill the
+    /// gap which requires updating each one of them. Draining from the end of the map,
+    /// such as `len - n..`, only touches the drained entries.
+    /// Returns an error if the range is out of bounds.
+    #[inline]
+    pub fn drain(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>
+    ) -> StdResult<Vec<(K::Output, V)>> {
+        self.drain_impl(storage, range, K::decode_key)
+    }
+
+    /// Same as [`Map::drain`] but only removes at most `limit` entries. Returns the
+    /// removed entries together with the range to pass in the next call or [`None`]
+    /// if all entries in the range have been removed. Removed entries are replaced
+    /// by the last one like in [`Map::retain_batch`], so the order of both the remaining
+    /// and the removed entries is not preserved.
+    /// Returns an error if the range is out of bounds or `limit` is 0.
+    #[inline]
+    pub fn drain_batch(
+        &mut self,
+        storage: &mut dyn Storage,
+        range: impl RangeBounds<u64>,
+        limit: u64
+    ) -> StdResult<DrainBatch<(K::Output, V)>> {
+        self.drain_batch_impl(storage, range, limit, K::decode_key)
+    }
+}
+
 impl<
     K: Key,
     V: FadromaSerialize + FadromaDeserialize + Default,
@@ -816,12 +1227,68 @@ impl<'storage, T: FadromaDeserialize> DoubleEndedIterator for MapValueIter<'stor
 
 impl<'storage, T: FadromaDeserialize> ExactSizeIterator for MapValueIter<'storage, T> { }
 
+impl<'storage, K: DecodeKey, V: FadromaDeserialize> MapIter<'storage, K, V> {
+    #[inline]
+    fn decode_entry(
+        &self,
+        entry: Option<StdResult<ItemEntry>>
+    ) -> Option<StdResult<(K::Output, V)>> {
+        entry.map(|x| x.and_then(|entry| {
+            let key = K::decode_key(&entry.key.0[self.prefix_len..])?;
+            let value = deserialize(&entry.item.0)?;
+
+            Ok((key, value))
+        }))
+    }
+}
+
+impl<'storage, K: DecodeKey, V: FadromaDeserialize> Iterator for MapIter<'storage, K, V> {
+    type Item = StdResult<(K::Output, V)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.next();
+
+        self.decode_entry(entry)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let entry = self.inner.nth(n);
+
+        self.decode_entry(entry)
+    }
+}
+
+impl<'storage, K: DecodeKey, V: FadromaDeserialize> DoubleEndedIterator for MapIter<'storage, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entry = self.inner.next_back();
+
+        self.decode_entry(entry)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let entry = self.inner.nth_back(n);
+
+        self.decode_entry(entry)
+    }
+}
+
+impl<'storage, K: DecodeKey, V: FadromaDeserialize> ExactSizeIterator for MapIter<'storage, K, V> { }
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         cosmwasm_std::testing::mock_dependencies,
-        storage::TypedKey,
+        storage::{TypedKey, TypedKey2, Reversible},
         namespace
     };
 
@@ -1054,4 +1521,188 @@ mod tests {
         let iter_next = map.values(storage).unwrap().next();
         assert!(iter_next.is_none());
     }
+
+    #[test]
+    fn map_iter() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut map = Map::<Reversible<TypedKey2<String, u16>>, u8, TestNs>::new();
+
+        let keys = ["one", "two", "three", "four"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+
+        let ids = [0, 1, 2, 3];
+
+        for (key, id) in keys.iter().zip(&ids) {
+            map.insert(storage, (key, id), &(*id as u8)).unwrap();
+        }
+
+        // The last entry is swapped into the place of the removed one.
+        map.remove(storage, (&keys[1], &ids[1])).unwrap();
+
+        let mut iter = map.iter(storage).unwrap();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next().unwrap().unwrap(), ((keys[0].clone(), 0), 0));
+        assert_eq!(iter.next_back().unwrap().unwrap(), ((keys[2].clone(), 2), 2));
+        assert_eq!(iter.next().unwrap().unwrap(), ((keys[3].clone(), 3), 3));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn map_bulk_raw() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut map = Map::<TypedKey2<String, u16>, u16, TestNs>::new();
+
+        let names = ["one", "two", "three", "four", "five"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+
+        let ids = [0, 1, 2, 3, 4];
+
+        for (name, id) in names.iter().zip(&ids) {
+            map.insert(storage, (name, id), &(id * 10)).unwrap();
+        }
+
+        let encode = |name: &String, id: &u16| {
+            let key: TypedKey2<String, u16> = (name, id).into();
+            let mut buf = Vec::with_capacity(key.size());
+            key.write_segments(&mut buf);
+
+            buf
+        };
+
+        let mut seen = vec![];
+        map.retain_raw(storage, |key, value| {
+            seen.push(key.to_vec());
+
+            *value != 10
+        }).unwrap();
+
+        seen.sort();
+        let mut expected = names.iter().zip(&ids).map(|(name, id)| encode(name, id)).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(seen, expected);
+        assert_eq!(map.get(storage, (&names[1], &ids[1])).unwrap(), None);
+
+        assert_eq!(map.retain_batch_raw(storage, 0, 2, |_, value| *value != 20).unwrap(), Some(2));
+        assert_eq!(map.retain_batch_raw(storage, 2, 2, |_, value| *value != 20).unwrap(), None);
+        assert_eq!(map.get(storage, (&names[2], &ids[2])).unwrap(), None);
+        assert!(map.retain_batch_raw(storage, 0, 0, |_, _| true).is_err());
+
+        // Remaining in order: one, five, four.
+        let drained = map.drain_raw(storage, 1..2).unwrap();
+        assert_eq!(drained, vec![(encode(&names[4], &ids[4]), 40)]);
+        assert_eq!(map.get(storage, (&names[4], &ids[4])).unwrap(), None);
+        assert_eq!(map.get(storage, (&names[3], &ids[3])).unwrap(), Some(30));
+
+        let (drained, rest) = map.drain_batch_raw(storage, .., 1).unwrap();
+        assert_eq!(drained, vec![(encode(&names[0], &ids[0]), 0)]);
+        assert_eq!(rest, Some(0..1));
+
+        let (drained, rest) = map.drain_batch_raw(storage, 0..1, 1).unwrap();
+        assert_eq!(drained, vec![(encode(&names[3], &ids[3]), 30)]);
+        assert_eq!(rest, None);
+        assert_eq!(map.values(storage).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn map_bulk() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut map = Map::<TypedKey<u16>, u16, TestNs>::new();
+
+        let keys = (0..30).collect::<Vec<u16>>();
+        let values = keys.iter().map(|x| x * 10).collect::<Vec<u16>>();
+        map.extend(storage, keys.iter().zip(&values)).unwrap();
+
+        let assert_consistent = |map: &Map<TypedKey<u16>, u16, TestNs>, storage: &dyn Storage, expected: &[u16]| {
+            let mut entries = map.iter(storage).unwrap().map(|x| x.unwrap()).collect::<Vec<_>>();
+
+            for (key, value) in &entries {
+                assert_eq!(map.get(storage, key).unwrap(), Some(*value));
+            }
+
+            entries.sort();
+            assert_eq!(entries, expected.iter().map(|x| (*x, x * 10)).collect::<Vec<_>>());
+        };
+
+        let drained = map.drain(storage, 5..10).unwrap();
+        assert_eq!(drained, (5..10).map(|x| (x, x * 10)).collect::<Vec<_>>());
+        assert_eq!(map.get(storage, &5).unwrap(), None);
+        assert!(map.drain(storage, 20..30).is_err());
+
+        let mut expected = (0..5).chain(10..30).collect::<Vec<u16>>();
+        assert_consistent(&map, storage, &expected);
+
+        let mut cursor = Some(0);
+        let mut calls = 0;
+
+        while let Some(start) = cursor {
+            cursor = map.retain_batch(storage, start, 4, |key, _| key % 2 == 0).unwrap();
+            calls += 1;
+        }
+
+        assert_eq!(calls, 7);
+        expected.retain(|x| x % 2 == 0);
+        assert_consistent(&map, storage, &expected);
+
+        assert!(!map.insert(storage, &1, &10).unwrap());
+        expected.push(1);
+        expected.sort();
+        assert_consistent(&map, storage, &expected);
+
+        assert_eq!(map.truncate_batch(storage, 5, 4).unwrap(), 5);
+        map.truncate(storage, 5).unwrap();
+        assert_eq!(map.values(storage).unwrap().len(), 5);
+
+        assert!(map.truncate_batch(storage, 0, 0).is_err());
+        assert!(map.retain_batch(storage, 0, 0, |_, _| true).is_err());
+        assert!(map.drain_batch(storage, 0..1, 0).is_err());
+        assert!(map.drain_batch(storage, 4..6, 1).is_err());
+
+        // Drains from the middle and then from the end.
+        for range in [1..3, 1..3] {
+            let positions = map.iter(storage).unwrap().map(|x| x.unwrap().0).collect::<Vec<_>>();
+            let mut expected = positions[range.start as usize..range.end as usize].to_vec();
+            expected.sort();
+
+            let mut next = Some(range);
+            let mut drained = vec![];
+
+            while let Some(range) = next {
+                let (entries, rest) = map.drain_batch(storage, range, 1).unwrap();
+                assert_eq!(entries.len(), 1);
+
+                drained.extend(entries.into_iter().map(|(key, value)| {
+                    assert_eq!(value, key * 10);
+
+                    key
+                }));
+                next = rest;
+            }
+
+            drained.sort();
+            assert_eq!(drained, expected);
+
+            let mut remaining = positions.clone();
+            remaining.retain(|x| !drained.contains(x));
+            remaining.sort();
+            assert_consistent(&map, storage, &remaining);
+        }
+
+        assert_eq!(map.values(storage).unwrap().len(), 1);
+
+        let remaining = map.iter(storage).unwrap().map(|x| x.unwrap().0).collect::<Vec<_>>();
+        let mut remaining_sorted = remaining.clone();
+        remaining_sorted.sort();
+        assert_consistent(&map, storage, &remaining_sorted);
+
+        map.clear(storage).unwrap();
+        assert_eq!(map.values(storage).unwrap().len(), 0);
+
+        for key in remaining {
+            assert_eq!(map.get(storage, &key).unwrap(), None);
+        }
+    }
 }
@@ -0,0 +1,464 @@
+//! An append-only log whose entries are accumulated into a Merkle tree.
+//!
+//! The tree is built the same way as in Certificate Transparency
+//! ([RFC 6962](https://www.rfc-editor.org/rfc/rfc6962#section-2.1)) using SHA-256.
+//! Leaves are hashed as `sha256(0x00 || entry)` where `entry` is the entry serialized
+//! with [`FadromaSerialize`] and inner nodes as `sha256(0x01 || left || right)`. The
+//! domain separation prevents passing off an inner node as a leaf.
+//!
+//! The current root commits to every entry in the log and their order. A user
+//! holding an entry can check that it is part of the log using an [`InclusionProof`]
+//! without seeing any other entry, while an indexer that has downloaded the entire
+//! log can check that it is complete and untampered with by recomputing the root
+//! using [`compute_root`].
+
+use std::marker::PhantomData;
+
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+
+use crate::{
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    crypto::sha_256,
+    cosmwasm_std::{Storage, StdResult, StdError, Binary}
+};
+use super::{Namespace, not_found_error};
+
+pub type Hash = [u8; 32];
+
+const KEY_META: &[u8] = b"meta";
+const KEY_ENTRY: &[u8] = b"entry";
+const KEY_NODE: &[u8] = b"node";
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// An append-only log of entries which maintains the root of a Merkle tree over them.
+/// Appending an entry writes an amortized constant number of hashes while computing
+/// the root or an inclusion proof reads a logarithmic number of them.
+/// See [`crate::storage::merkle`] for how the tree is constructed.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::testing::mock_dependencies,
+///     storage::merkle::MerkleLog
+/// };
+///
+/// fadroma::namespace!(AuditLogNs, b"audit_log");
+/// const AUDIT_LOG: MerkleLog<String, AuditLogNs> = MerkleLog::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// for action in ["mint", "pause", "unpause", "burn", "transfer"] {
+///     AUDIT_LOG.append(storage, &action.to_string()).unwrap();
+/// }
+///
+/// // Returned by a query to the user that is interested in the third entry.
+/// let root = AUDIT_LOG.root(storage).unwrap().unwrap();
+/// let proof = AUDIT_LOG.proof(storage, 2).unwrap();
+///
+/// // Verified off-chain.
+/// let leaf = MerkleLog::<String, AuditLogNs>::leaf_hash(&"unpause".to_string()).unwrap();
+/// assert!(proof.verify(&root, &leaf));
+///
+/// let leaf = MerkleLog::<String, AuditLogNs>::leaf_hash(&"upgrade".to_string()).unwrap();
+/// assert!(!proof.verify(&root, &leaf));
+/// ```
+pub struct MerkleLog<T: FadromaSerialize + FadromaDeserialize, N: Namespace> {
+    value_data: PhantomData<T>,
+    ns_data: PhantomData<N>
+}
+
+/// Proves that a leaf is at the given index of a tree with the given size.
+/// Consists of the hashes of the sibling subtrees from the leaf up to the root.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq, Debug)]
+pub struct InclusionProof {
+    pub index: u64,
+    pub size: u64,
+    pub path: Vec<Binary>
+}
+
+impl<T: FadromaSerialize + FadromaDeserialize, N: Namespace> MerkleLog<T, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `MerkleLog`:
+    ///  * N + "meta"
+    ///  * N + "entry" + n - where n is a number
+    ///  * N + "node" + level + n - where level and n are numbers
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            value_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Hashes the entry the same way it is hashed when appended to the log.
+    #[inline]
+    pub fn leaf_hash(entry: &T) -> StdResult<Hash> {
+        Ok(leaf_hash(&super::serialize(entry)?))
+    }
+
+    /// Appends the entry to the end of the log and returns its index.
+    pub fn append(&self, storage: &mut dyn Storage, entry: &T) -> StdResult<u64> {
+        let bytes = super::serialize(entry)?;
+        let index = self.len(storage)?;
+
+        storage.set(&Self::entry_key(index), &bytes);
+
+        // Complete every subtree that the new leaf is the last one of.
+        let mut hash = leaf_hash(&bytes);
+        let mut level = 0;
+        let mut position = index;
+
+        Self::save_node(storage, level, position, &hash);
+
+        while position & 1 == 1 {
+            let left = Self::load_node(storage, level, position - 1)?;
+            hash = node_hash(&left, &hash);
+
+            level += 1;
+            position >>= 1;
+
+            Self::save_node(storage, level, position, &hash);
+        }
+
+        super::save(storage, Self::meta_key(), &(index + 1))?;
+
+        Ok(index)
+    }
+
+    #[inline]
+    pub fn get(&self, storage: &dyn Storage, index: u64) -> StdResult<Option<T>> {
+        super::load(storage, Self::entry_key(index))
+    }
+
+    #[inline]
+    pub fn get_or_error(&self, storage: &dyn Storage, index: u64) -> StdResult<T> {
+        let result = self.get(storage, index)?;
+
+        result.ok_or_else(not_found_error::<T>)
+    }
+
+    /// Returns the number of entries in the log.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        let len: Option<u64> = super::load(storage, Self::meta_key())?;
+
+        Ok(len.unwrap_or_default())
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns the root of the tree over all entries or [`None`] if the log is empty.
+    pub fn root(&self, storage: &dyn Storage) -> StdResult<Option<Hash>> {
+        let len = self.len(storage)?;
+
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let edge = Self::right_edge(storage, len)?;
+
+        Ok(Some(edge[0].1))
+    }
+
+    /// Returns the proof that the entry at the given index is
+    /// included in the tree over all entries currently in the log.
+    /// Reads at most two hashes for each level of the tree.
+    pub fn proof(&self, storage: &dyn Storage, index: u64) -> StdResult<InclusionProof> {
+        let size = self.len(storage)?;
+
+        if index >= size {
+            return Err(StdError::generic_err("MerkleLog: index out of bounds."));
+        }
+
+        let edge = Self::right_edge(storage, size)?;
+
+        let subtree_hash = |start: u64, end: u64| -> StdResult<Hash> {
+            if end == size {
+                if let Some((_, hash)) = edge.iter().find(|(x, _)| *x == start) {
+                    return Ok(*hash);
+                }
+            }
+
+            // Any other subtree is complete and therefore stored.
+            let level = (end - start).trailing_zeros();
+
+            Self::load_node(storage, level as u8, start >> level)
+        };
+
+        let mut path = vec![];
+        let (mut start, mut end) = (0, size);
+
+        // Descend from the root, recording the sibling of each subtree on the way.
+        while end - start > 1 {
+            let split = start + split_point(end - start);
+
+            if index < split {
+                path.push(subtree_hash(split, end)?);
+                end = split;
+            } else {
+                path.push(subtree_hash(start, split)?);
+                start = split;
+            }
+        }
+
+        Ok(InclusionProof {
+            index,
+            size,
+            path: path.into_iter().rev().map(|x| Binary(x.to_vec())).collect()
+        })
+    }
+
+    /// Returns the hashes of the subtrees on the right edge of the tree over `size` leaves
+    /// from the root down, together with the index of the first leaf of each one. The left
+    /// subtree of each of them is complete and therefore stored, so every hash is computed
+    /// from a single read and the hash of the subtree below it.
+    fn right_edge(storage: &dyn Storage, size: u64) -> StdResult<Vec<(u64, Hash)>> {
+        // The complete subtrees that the leaves are split into, largest first.
+        let mut peaks = vec![];
+        let mut start = 0;
+
+        while start < size {
+            let level = 63 - (size - start).leading_zeros();
+            peaks.push((start, Self::load_node(storage, level as u8, start >> level)?));
+
+            start += 1 << level;
+        }
+
+        let mut edge: Vec<(u64, Hash)> = Vec::with_capacity(peaks.len());
+
+        for (start, hash) in peaks.into_iter().rev() {
+            let hash = match edge.last() {
+                Some((_, right)) => node_hash(&hash, right),
+                None => hash
+            };
+
+            edge.push((start, hash));
+        }
+
+        edge.reverse();
+
+        Ok(edge)
+    }
+
+    #[inline]
+    fn save_node(storage: &mut dyn Storage, level: u8, position: u64, hash: &Hash) {
+        storage.set(&Self::node_key(level, position), hash);
+    }
+
+    #[inline]
+    fn load_node(storage: &dyn Storage, level: u8, position: u64) -> StdResult<Hash> {
+        storage.get(&Self::node_key(level, position))
+            .and_then(|x| Hash::try_from(x.as_slice()).ok())
+            .ok_or_else(|| StdError::generic_err("MerkleLog: missing tree node."))
+    }
+
+    #[inline]
+    fn meta_key() -> Vec<u8> {
+        [N::NAMESPACE, KEY_META].concat()
+    }
+
+    #[inline]
+    fn entry_key(index: u64) -> Vec<u8> {
+        [N::NAMESPACE, KEY_ENTRY, &index.to_be_bytes()].concat()
+    }
+
+    #[inline]
+    fn node_key(level: u8, position: u64) -> Vec<u8> {
+        [N::NAMESPACE, KEY_NODE, &[level], &position.to_be_bytes()].concat()
+    }
+}
+
+impl InclusionProof {
+    /// Returns `true` if the proof shows that the leaf is at `self.index`
+    /// in the tree of `self.size` leaves with the given root.
+    pub fn verify(&self, root: &[u8], leaf: &Hash) -> bool {
+        if self.index >= self.size {
+            return false;
+        }
+
+        let mut index = self.index;
+        let mut last = self.size - 1;
+        let mut hash = *leaf;
+
+        for sibling in &self.path {
+            let Ok(sibling) = Hash::try_from(sibling.as_slice()) else {
+                return false;
+            };
+
+            if last == 0 {
+                return false;
+            }
+
+            if index & 1 == 1 || index == last {
+                hash = node_hash(&sibling, &hash);
+
+                // Skip the levels at which the node has no sibling.
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                hash = node_hash(&hash, &sibling);
+            }
+
+            index >>= 1;
+            last >>= 1;
+        }
+
+        last == 0 && hash.as_slice() == root
+    }
+}
+
+/// Computes the root of the tree over the given leaf hashes in order or returns
+/// [`None`] if there are none. Use [`MerkleLog::leaf_hash`] to hash the entries.
+pub fn compute_root(leaves: impl IntoIterator<Item = Hash>) -> Option<Hash> {
+    // The roots of the complete subtrees so far, largest first.
+    let mut peaks: Vec<(u32, Hash)> = vec![];
+
+    for leaf in leaves {
+        let mut hash = leaf;
+        let mut level = 0;
+
+        while let Some((peak_level, peak)) = peaks.last() {
+            if *peak_level != level {
+                break;
+            }
+
+            hash = node_hash(peak, &hash);
+            level += 1;
+            peaks.pop();
+        }
+
+        peaks.push((level, hash));
+    }
+
+    peaks.into_iter()
+        .rev()
+        .map(|(_, hash)| hash)
+        .reduce(|right, left| node_hash(&left, &right))
+}
+
+/// The size of the left subtree which is the largest power of two smaller than `size`.
+#[inline]
+fn split_point(size: u64) -> u64 {
+    1 << (63 - (size - 1).leading_zeros())
+}
+
+#[inline]
+fn leaf_hash(bytes: &[u8]) -> Hash {
+    let mut data = Vec::with_capacity(1 + bytes.len());
+    data.push(LEAF_PREFIX);
+    data.extend_from_slice(bytes);
+
+    sha_256(&data)
+}
+
+#[inline]
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut data = [0; 65];
+    data[0] = NODE_PREFIX;
+    data[1..33].copy_from_slice(left);
+    data[33..].copy_from_slice(right);
+
+    sha_256(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::cache::CachedStorage,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    const LOG: MerkleLog<u64, TestNs> = MerkleLog::new();
+
+    /// The recursive definition from RFC 6962.
+    fn reference_root(leaves: &[Hash]) -> Hash {
+        if leaves.len() == 1 {
+            return leaves[0];
+        }
+
+        let split = split_point(leaves.len() as u64) as usize;
+
+        node_hash(&reference_root(&leaves[..split]), &reference_root(&leaves[split..]))
+    }
+
+    #[test]
+    fn roots_and_proofs() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut leaves = vec![];
+
+        assert_eq!(LOG.root(storage).unwrap(), None);
+        assert_eq!(compute_root(vec![]), None);
+
+        for i in 0..70u64 {
+            assert_eq!(LOG.append(storage, &(i * 7)).unwrap(), i);
+            leaves.push(MerkleLog::<u64, TestNs>::leaf_hash(&(i * 7)).unwrap());
+
+            let root = LOG.root(storage).unwrap().unwrap();
+            assert_eq!(root, reference_root(&leaves));
+            assert_eq!(compute_root(leaves.iter().copied()), Some(root));
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = LOG.proof(storage, index as u64).unwrap();
+                assert!(proof.verify(&root, leaf));
+
+                // The proof is bound to the position of the leaf.
+                let wrong_index = InclusionProof {
+                    index: (index as u64 + 1) % (i + 1),
+                    ..proof.clone()
+                };
+                assert_eq!(wrong_index.verify(&root, leaf), i == 0);
+
+                let mut tampered = proof.clone();
+                if let Some(sibling) = tampered.path.first_mut() {
+                    sibling.0[0] ^= 1;
+                    assert!(!tampered.verify(&root, leaf));
+                }
+            }
+        }
+
+        assert_eq!(LOG.len(storage).unwrap(), 70);
+        assert_eq!(LOG.get(storage, 69).unwrap(), Some(69 * 7));
+        assert!(LOG.proof(storage, 70).is_err());
+
+        // Proofs are always verified against the root at the time they were generated.
+        let proof = LOG.proof(storage, 3).unwrap();
+        LOG.append(storage, &0).unwrap();
+        let root = LOG.root(storage).unwrap().unwrap();
+        assert!(!proof.verify(&root, &leaves[3]));
+        assert!(LOG.proof(storage, 3).unwrap().verify(&root, &leaves[3]));
+    }
+
+    #[test]
+    fn proof_reads() {
+        let mut deps = mock_dependencies();
+
+        for size in 1..=300u64 {
+            LOG.append(&mut deps.storage, &size).unwrap();
+
+            let depth = 64 - (size - 1).leading_zeros() as u64;
+
+            for index in [0, size / 2, size - 1] {
+                let storage = CachedStorage::new(&mut deps.storage);
+                LOG.proof(&storage, index).unwrap();
+
+                // The length and at most two hashes per level.
+                let stats = storage.stats();
+                assert!(stats.hits + stats.misses <= 1 + 2 * depth.max(1));
+            }
+        }
+    }
+}
@@ -0,0 +1,451 @@
+//! Versioned storage schema migrations. The contract's schema version
+//! is kept in storage and a [`Migrator`] holds the list of steps that
+//! each move the storage from one version to the next. Pending steps
+//! can be applied all at once in a `migrate` entry point or in batches
+//! over many blocks when a single step has too much data to go through
+//! in one transaction. In the latter case [`Migrator::assert_up_to_date`]
+//! can be used to reject other messages until the migration completes
+//! in the same way [`crate::killswitch`] does while the contract is paused.
+//!
+//! # Examples
+//!
+//! ```
+//! use fadroma::{
+//!     cosmwasm_std::{Storage, StdResult, testing::mock_dependencies},
+//!     storage::{self, Namespace, SingleItem, migration::{Migrator, Migration, MigrationStatus}}
+//! };
+//!
+//! fadroma::namespace!(ConfigNs, b"config");
+//! const CONFIG: SingleItem<u64, ConfigNs> = SingleItem::new();
+//!
+//! fn widen_config(storage: &mut dyn Storage) -> StdResult<()> {
+//!     storage::migration::migrate_value(storage, ConfigNs::NAMESPACE, |old: u32| Ok(old as u64))?;
+//!
+//!     Ok(())
+//! }
+//!
+//! const MIGRATOR: Migrator = Migrator::new(&[
+//!     Migration::once(0, "widen config to u64", widen_config)
+//! ]);
+//!
+//! # fn main() -> StdResult<()> {
+//! # let mut deps = mock_dependencies();
+//! # let storage = deps.as_mut().storage;
+//! // Data written by the previous version of the contract.
+//! storage::save(storage, ConfigNs::NAMESPACE, &7u32)?;
+//!
+//! let status = MIGRATOR.run(storage)?;
+//! assert_eq!(status, MigrationStatus { version: 1, target: 1, pending: false });
+//! assert_eq!(CONFIG.load(storage)?, Some(7));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult, StdError, Binary},
+    schemars::{self, JsonSchema},
+    serde::{Serialize, Deserialize}
+};
+use super::SingleItem;
+
+crate::namespace!(pub MigrationNs, b"Qm7VfX2cRs");
+const STATE: SingleItem<MigrationState, MigrationNs> = SingleItem::new();
+
+/// A function that migrates the storage in one go.
+pub type OnceFn = fn(storage: &mut dyn Storage) -> StdResult<()>;
+
+/// A function that migrates at most `limit` entries starting from
+/// `cursor` (which is `None` on the first call). It returns the cursor
+/// to continue from or `None` when all the data has been migrated.
+pub type BatchedFn = fn(
+    storage: &mut dyn Storage,
+    cursor: Option<Binary>,
+    limit: u32
+) -> StdResult<Option<Binary>>;
+
+/// A single step that migrates the storage from version `from` to `from + 1`.
+#[derive(Clone, Copy)]
+pub struct Migration {
+    from: u32,
+    name: &'static str,
+    step: Step
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Once(OnceFn),
+    Batched(BatchedFn)
+}
+
+/// Applies the registered [`Migration`] steps in order and keeps track
+/// of the current schema version in storage.
+pub struct Migrator {
+    steps: &'static [Migration]
+}
+
+/// Where the storage is at after running the [`Migrator`].
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct MigrationStatus {
+    /// The current schema version.
+    pub version: u32,
+    /// The version that the contract code expects.
+    pub target: u32,
+    /// Whether there are steps that haven't been fully applied yet.
+    pub pending: bool
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Default)]
+struct MigrationState {
+    version: u32,
+    /// Set if the step migrating from `version` ran out of its batch limit.
+    cursor: Option<Binary>
+}
+
+impl Migration {
+    /// A step that migrates everything in a single call.
+    #[inline]
+    pub const fn once(from: u32, name: &'static str, step: OnceFn) -> Self {
+        Self { from, name, step: Step::Once(step) }
+    }
+
+    /// A step that is able to migrate its data over multiple calls.
+    #[inline]
+    pub const fn batched(from: u32, name: &'static str, step: BatchedFn) -> Self {
+        Self { from, name, step: Step::Batched(step) }
+    }
+
+    #[inline]
+    pub fn from(&self) -> u32 {
+        self.from
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Migrator {
+    /// Creates a migrator from the given steps. The steps must be ordered
+    /// by their `from` version and each one must start where the previous
+    /// one ended. A storage that was never migrated is at version 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is a gap between steps or they are out of order.
+    /// In a `const` this is a compile time error.
+    pub const fn new(steps: &'static [Migration]) -> Self {
+        let mut i = 1;
+
+        while i < steps.len() {
+            if steps[i].from != steps[i - 1].from + 1 {
+                panic!("Migrator: migration steps must be consecutive.");
+            }
+
+            i += 1;
+        }
+
+        Self { steps }
+    }
+
+    /// The version that the storage will be at after all steps are applied.
+    #[inline]
+    pub fn target(&self) -> u32 {
+        match self.steps.last() {
+            Some(step) => step.from + 1,
+            None => 0
+        }
+    }
+
+    /// The current schema version stored.
+    #[inline]
+    pub fn version(&self, storage: &dyn Storage) -> StdResult<u32> {
+        Ok(Self::state(storage)?.version)
+    }
+
+    pub fn status(&self, storage: &dyn Storage) -> StdResult<MigrationStatus> {
+        let state = Self::state(storage)?;
+
+        Ok(MigrationStatus {
+            version: state.version,
+            target: self.target(),
+            pending: state.version < self.target()
+        })
+    }
+
+    /// Marks the storage as being at the latest version. Call this
+    /// when instantiating since there is nothing to migrate then.
+    #[inline]
+    pub fn init(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        STATE.save(storage, &MigrationState {
+            version: self.target(),
+            cursor: None
+        })
+    }
+
+    /// Applies all pending steps without any limit. Suitable for
+    /// a `migrate` entry point.
+    #[inline]
+    pub fn run(&self, storage: &mut dyn Storage) -> StdResult<MigrationStatus> {
+        self.run_batch(storage, u32::MAX)
+    }
+
+    /// Applies the pending steps in order, passing `limit` to each one
+    /// that is batched. Stops as soon as a step doesn't finish and saves
+    /// its cursor so that the next call continues where this one left off.
+    pub fn run_batch(&self, storage: &mut dyn Storage, limit: u32) -> StdResult<MigrationStatus> {
+        let mut state = Self::state(storage)?;
+        let target = self.target();
+
+        if state.version > target {
+            return Err(StdError::generic_err(format!(
+                "Migrator: the stored version {} is newer than the latest known version {}.",
+                state.version,
+                target
+            )));
+        }
+
+        while state.version < target {
+            let Some(migration) = self.steps.iter().find(|x| x.from == state.version) else {
+                return Err(StdError::generic_err(format!(
+                    "Migrator: no migration step from version {}.",
+                    state.version
+                )));
+            };
+
+            match migration.step {
+                Step::Once(step) => step(storage)?,
+                Step::Batched(step) => {
+                    if let Some(cursor) = step(storage, state.cursor.take(), limit)? {
+                        state.cursor = Some(cursor);
+
+                        break;
+                    }
+                }
+            }
+
+            state.version += 1;
+        }
+
+        STATE.save(storage, &state)?;
+
+        Ok(MigrationStatus {
+            version: state.version,
+            target,
+            pending: state.version < target
+        })
+    }
+
+    /// Fails if the storage is not at the latest version.
+    pub fn assert_up_to_date(&self, storage: &dyn Storage) -> StdResult<()> {
+        let version = self.version(storage)?;
+
+        if version != self.target() {
+            return Err(StdError::generic_err(format!(
+                "Migrator: the storage is at version {} but version {} is required.",
+                version,
+                self.target()
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn state(storage: &dyn Storage) -> StdResult<MigrationState> {
+        Ok(STATE.load(storage)?.unwrap_or_default())
+    }
+}
+
+/// Loads the value stored under `key` as `Old`, converts it
+/// and saves it back as `New`. Returns `false` if nothing was stored.
+pub fn migrate_value<Old: FadromaDeserialize, New: FadromaSerialize>(
+    storage: &mut dyn Storage,
+    key: impl AsRef<[u8]>,
+    convert: impl FnOnce(Old) -> StdResult<New>
+) -> StdResult<bool> {
+    let key = key.as_ref();
+
+    match super::load::<Old>(storage, key)? {
+        Some(old) => {
+            super::save(storage, key, &convert(old)?)?;
+
+            Ok(true)
+        },
+        None => Ok(false)
+    }
+}
+
+/// Writes the `fixture` entries into a fresh storage at schema `version`,
+/// runs the `migrator` (in batches of `limit` if given) until it's done and
+/// asserts that each key in `expected` ends up holding the given bytes or
+/// is missing if `None`. Returns the storage for any further checks.
+///
+/// # Panics
+///
+/// Panics if the migration fails or the storage doesn't match `expected`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn assert_migration(
+    migrator: &Migrator,
+    version: u32,
+    fixture: &[(&[u8], &[u8])],
+    expected: &[(&[u8], Option<&[u8]>)],
+    limit: Option<u32>
+) -> crate::cosmwasm_std::testing::MockStorage {
+    let mut storage = crate::cosmwasm_std::testing::MockStorage::new();
+
+    for (key, value) in fixture {
+        storage.set(key, value);
+    }
+
+    STATE.save(&mut storage, &MigrationState { version, cursor: None }).unwrap();
+
+    let limit = limit.unwrap_or(u32::MAX);
+    // Every batch must make progress so this is an upper bound on the number of calls.
+    let mut calls = 0usize;
+
+    while migrator.run_batch(&mut storage, limit).unwrap().pending {
+        calls += 1;
+
+        if calls > fixture.len() + migrator.steps.len() {
+            panic!("Migrator: the migration did not complete after {} batches.", calls);
+        }
+    }
+
+    for (key, value) in expected {
+        assert_eq!(
+            storage.get(key).as_deref(),
+            *value,
+            "Unexpected value for key {:?} after the migration.",
+            Binary::from(*key)
+        );
+    }
+
+    storage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::MockStorage,
+        storage::{self, Namespace},
+        namespace
+    };
+
+    namespace!(CountNs, b"count");
+    namespace!(BalancesNs, b"balances");
+
+    const USERS: [&str; 5] = ["alice", "bob", "carol", "dave", "eve"];
+
+    fn count_to_u64(storage: &mut dyn Storage) -> StdResult<()> {
+        migrate_value(storage, CountNs::NAMESPACE, |old: u8| Ok(old as u64))?;
+
+        Ok(())
+    }
+
+    fn double_balances(
+        storage: &mut dyn Storage,
+        cursor: Option<Binary>,
+        limit: u32
+    ) -> StdResult<Option<Binary>> {
+        let start = cursor.map(|x| x[0] as usize).unwrap_or_default();
+        let end = USERS.len().min(start + limit as usize);
+
+        for user in &USERS[start..end] {
+            let key = [BalancesNs::NAMESPACE, user.as_bytes()].concat();
+            migrate_value(storage, key, |old: u64| Ok(old * 2))?;
+        }
+
+        Ok((end < USERS.len()).then(|| Binary(vec![end as u8])))
+    }
+
+    const MIGRATOR: Migrator = Migrator::new(&[
+        Migration::once(0, "count to u64", count_to_u64),
+        Migration::batched(1, "double balances", double_balances)
+    ]);
+
+    #[test]
+    fn runs_in_batches() {
+        let storage = &mut MockStorage::new() as &mut dyn Storage;
+        storage::save(storage, CountNs::NAMESPACE, &3u8).unwrap();
+
+        for user in USERS {
+            let key = [BalancesNs::NAMESPACE, user.as_bytes()].concat();
+            storage::save(storage, key, &10u64).unwrap();
+        }
+
+        assert_eq!(MIGRATOR.target(), 2);
+        MIGRATOR.assert_up_to_date(storage).unwrap_err();
+
+        let status = MIGRATOR.run_batch(storage, 2).unwrap();
+        assert_eq!(status, MigrationStatus { version: 1, target: 2, pending: true });
+        assert_eq!(storage::load(storage, CountNs::NAMESPACE).unwrap(), Some(3u64));
+
+        let status = MIGRATOR.run_batch(storage, 2).unwrap();
+        assert_eq!(status, MigrationStatus { version: 1, target: 2, pending: true });
+
+        let status = MIGRATOR.run_batch(storage, 2).unwrap();
+        assert_eq!(status, MigrationStatus { version: 2, target: 2, pending: false });
+        MIGRATOR.assert_up_to_date(storage).unwrap();
+
+        for user in USERS {
+            let key = [BalancesNs::NAMESPACE, user.as_bytes()].concat();
+            assert_eq!(storage::load(storage, key).unwrap(), Some(20u64));
+        }
+
+        // Nothing left to do.
+        assert_eq!(MIGRATOR.run(storage).unwrap(), status);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let storage = &mut MockStorage::new() as &mut dyn Storage;
+
+        STATE.save(storage, &MigrationState { version: 3, cursor: None }).unwrap();
+        assert_eq!(
+            MIGRATOR.run(storage).unwrap_err(),
+            StdError::generic_err("Migrator: the stored version 3 is newer than the latest known version 2.")
+        );
+
+        const LATE: Migrator = Migrator::new(&[
+            Migration::batched(1, "double balances", double_balances)
+        ]);
+
+        STATE.save(storage, &MigrationState::default()).unwrap();
+        assert_eq!(
+            LATE.run(storage).unwrap_err(),
+            StdError::generic_err("Migrator: no migration step from version 0.")
+        );
+
+        LATE.init(storage).unwrap();
+        LATE.assert_up_to_date(storage).unwrap();
+    }
+
+    #[test]
+    fn fixture() {
+        let balance = |user: &str| [BalancesNs::NAMESPACE, user.as_bytes()].concat();
+        let fixture = USERS.iter().map(|x| balance(x)).collect::<Vec<_>>();
+        let ten = storage::serialize(&10u64).unwrap();
+        let twenty = storage::serialize(&20u64).unwrap();
+
+        let mut input: Vec<(&[u8], &[u8])> = fixture.iter()
+            .map(|x| (x.as_slice(), ten.as_slice()))
+            .collect();
+
+        let mut expected: Vec<(&[u8], Option<&[u8]>)> = fixture.iter()
+            .map(|x| (x.as_slice(), Some(twenty.as_slice())))
+            .collect();
+
+        let count = storage::serialize(&1u8).unwrap();
+        let count_migrated = storage::serialize(&1u64).unwrap();
+        input.push((CountNs::NAMESPACE, &count));
+        expected.push((CountNs::NAMESPACE, Some(&count_migrated)));
+        expected.push((b"missing", None));
+
+        let storage = assert_migration(&MIGRATOR, 0, &input, &expected, Some(1));
+        assert_eq!(MIGRATOR.version(&storage).unwrap(), 2);
+    }
+}
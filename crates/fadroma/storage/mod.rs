@@ -2,13 +2,78 @@
 
 pub mod iterable;
 pub mod map;
+pub mod ordered;
+pub mod indexed;
+pub mod deque;
+pub mod set;
+pub mod snapshot;
+pub mod migration;
+pub mod cache;
+pub mod registry;
+pub mod nested;
+pub mod expiring;
+pub mod sorted;
+pub mod counter;
+pub mod bitmap;
+#[cfg(feature = "crypto")]
+pub mod merkle;
+#[cfg(all(feature = "inspect", not(target_arch = "wasm32")))]
+pub mod inspect;
 
 mod single_item;
 mod item_space;
+mod btree;
 
 pub use single_item::*;
 pub use item_space::*;
 
+/// Derives a companion `{Name}Store<N: Namespace>` type which stores each
+/// field of the struct under its own key, formed by appending the field name
+/// (or its index for tuple structs) to the namespace. Besides loading and
+/// saving the whole struct, it provides a `load_{field}` and `save_{field}`
+/// method for each field, so that a single field can be read or updated
+/// without touching the rest. Fields must implement [`FadromaSerialize`]
+/// and [`FadromaDeserialize`].
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{testing::mock_dependencies, Uint128},
+///     storage::FieldStorage
+/// };
+///
+/// #[derive(FieldStorage, PartialEq, Debug)]
+/// pub struct Config {
+///     fee: Uint128,
+///     paused: bool,
+///     admins: Vec<String>
+/// }
+///
+/// fadroma::namespace!(ConfigNs, b"config");
+/// const CONFIG: ConfigStore<ConfigNs> = ConfigStore::new();
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// let config = Config {
+///     fee: Uint128::new(10),
+///     paused: false,
+///     admins: vec!["admin".into()]
+/// };
+///
+/// CONFIG.save(storage, &config).unwrap();
+///
+/// // Only the "paused" field is written.
+/// CONFIG.save_paused(storage, &true).unwrap();
+/// assert_eq!(CONFIG.load_paused(storage).unwrap(), Some(true));
+///
+/// let config = CONFIG.load_or_error(storage).unwrap();
+/// assert!(config.paused);
+/// assert_eq!(config.fee, Uint128::new(10));
+/// ```
+pub use fadroma_derive_serde::FieldStorage;
+
 use std::{any, convert::{TryFrom, TryInto}};
 
 use crate::{
@@ -23,6 +88,11 @@ use crate::{
 /// zero-sized struct with the given type name and
 /// implements [`Namespace`] on it with the provied
 /// byte slice literal.
+///
+/// The namespace isn't checked for collisions with other namespaces unless it
+/// is listed in [`crate::assert_namespaces`] or a [`registry::Registry`].
+/// Use [`crate::namespaces`] to declare several namespaces which are checked
+/// and registered automatically.
 /// 
 /// # Examples
 /// 
@@ -90,6 +160,46 @@ pub trait Segment {
     fn write_segment(&self, buf: &mut Vec<u8>);
 }
 
+/// Implemented by [`Segment`]s which can be read back from a storage key.
+///
+/// The bytes written by [`Segment::write_segment`] can only be decoded when
+/// the segment is the last one in the key, since variable length types such
+/// as strings don't record where they end. For this reason, this trait also
+/// provides an encoding which can be decoded regardless of what follows it.
+/// Fixed width types such as integers are written as they are and variable
+/// length types are prefixed with their length as a big endian `u32`. It is
+/// used by keys wrapped in [`Reversible`].
+pub trait DecodeSegment: Segment {
+    /// The owned type that the segment is decoded into.
+    type Output;
+
+    /// The amount of bytes written by [`DecodeSegment::write_encoded`].
+    fn encoded_size(&self) -> usize;
+    /// Writes the segment in a way that can be decoded by [`DecodeSegment::read_encoded`].
+    fn write_encoded(&self, buf: &mut Vec<u8>);
+    /// Decodes the segment from the start of `bytes` and advances past it.
+    fn read_encoded(bytes: &mut &[u8]) -> StdResult<Self::Output>;
+    /// Decodes the bytes written by [`Segment::write_segment`].
+    /// All of the given bytes are expected to belong to the segment.
+    fn decode_segment(bytes: &[u8]) -> StdResult<Self::Output>;
+}
+
+/// Implemented by [`Key`]s whose bytes can be decoded back into typed values.
+/// This allows storage types to yield the keys they store and tooling to
+/// render raw storage keys.
+///
+/// A [`TypedKey`] can be decoded as it is, so it can be used with data that is
+/// already in storage. Keys with multiple segments must be wrapped in [`Reversible`]
+/// in order to be decoded, which changes the bytes they write. Because of that,
+/// switching an existing storage type over to them requires migrating its data.
+pub trait DecodeKey: Key {
+    /// The owned type that the key is decoded into.
+    type Output;
+
+    /// Decodes the bytes written by [`Key::write_segments`].
+    fn decode_key(bytes: &[u8]) -> StdResult<Self::Output>;
+}
+
 /// A key with an arbitrary number of segments.
 /// Writes them in order of the iteration.
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
@@ -145,6 +255,45 @@ pub struct FixedSegmentSizeKey<'a, const N: usize>([&'a [u8]; N]);
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub struct TypedKey<'a, T: Segment + ?Sized>(&'a T);
 
+/// Wraps a [`TypedKey`] or one of its variants and writes its segments using
+/// the encoding provided by [`DecodeSegment`] so that it implements [`DecodeKey`].
+/// It is an opt-in since the resulting bytes differ from those of the wrapped
+/// key whenever it contains variable length segments.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::testing::mock_dependencies,
+///     storage::{Key, DecodeKey, Reversible, TypedKey2, map::Map}
+/// };
+///
+/// fadroma::namespace!(VotesNs, b"votes");
+///
+/// type VoteKey<'a> = Reversible<TypedKey2<'a, String, u64>>;
+///
+/// let mut deps = mock_dependencies();
+/// let storage = deps.as_mut().storage;
+///
+/// let voter = "alice".to_string();
+/// let mut votes = Map::<VoteKey, bool, VotesNs>::new();
+/// votes.insert(storage, (&voter, &3), &true).unwrap();
+///
+/// let (key, vote) = votes.iter(storage).unwrap().next().unwrap().unwrap();
+/// assert_eq!(key, ("alice".to_string(), 3));
+/// assert!(vote);
+///
+/// let voter = "bob".to_string();
+/// let key = VoteKey::from((&voter, &1));
+/// let mut bytes = Vec::with_capacity(key.size());
+/// key.write_segments(&mut bytes);
+///
+/// assert_eq!(bytes, [&3u32.to_be_bytes(), "bob".as_bytes(), &1u64.to_be_bytes()].concat());
+/// assert_eq!(VoteKey::decode_key(&bytes).unwrap(), ("bob".to_string(), 1));
+/// ```
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub struct Reversible<K>(pub K);
+
 /// Save something to the storage.
 #[inline]
 pub fn save<T: FadromaSerialize> (
@@ -300,6 +449,46 @@ impl<'a, T: Segment + ?Sized> From<&'a T> for TypedKey<'a, T> {
     }
 }
 
+impl<'a, T: DecodeSegment + ?Sized> DecodeKey for TypedKey<'a, T> {
+    type Output = T::Output;
+
+    #[inline]
+    fn decode_key(bytes: &[u8]) -> StdResult<Self::Output> {
+        T::decode_segment(bytes)
+    }
+}
+
+impl<'a, T: DecodeSegment + ?Sized> Key for Reversible<TypedKey<'a, T>> {
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.0.encoded_size()
+    }
+
+    #[inline]
+    fn write_segments(&self, buf: &mut Vec<u8>) {
+        self.0.0.write_encoded(buf);
+    }
+}
+
+impl<'a, T: DecodeSegment + ?Sized> DecodeKey for Reversible<TypedKey<'a, T>> {
+    type Output = T::Output;
+
+    #[inline]
+    fn decode_key(mut bytes: &[u8]) -> StdResult<Self::Output> {
+        let result = T::read_encoded(&mut bytes)?;
+        assert_consumed(bytes)?;
+
+        Ok(result)
+    }
+}
+
+impl<'a, T: DecodeSegment + ?Sized> From<&'a T> for Reversible<TypedKey<'a, T>> {
+    #[inline]
+    fn from(value: &'a T) -> Self {
+        Self(TypedKey(value))
+    }
+}
+
 macro_rules! impl_typed_key {
     ($name:ident $(<$lt:lifetime, $($param:ident),+>)+ [$($num:tt),+]) => {
         #[derive(Clone, Copy, PartialEq, Hash, Debug)]
@@ -324,6 +513,38 @@ macro_rules! impl_typed_key {
                 Self(value)
             }
         }
+
+        impl $(<$lt, $($param: DecodeSegment + ?Sized),+>)+ Key for Reversible<$name $(<$lt, $($param),+>)+> {
+            #[inline]
+            fn size(&self) -> usize {
+                self.0.0.0.encoded_size() $(+ self.0.0.$num.encoded_size())+
+            }
+
+            #[inline]
+            fn write_segments(&self, buf: &mut Vec<u8>) {
+                self.0.0.0.write_encoded(buf);
+                $(self.0.0.$num.write_encoded(buf);)+
+            }
+        }
+
+        impl $(<$lt, $($param: DecodeSegment + ?Sized),+>)+ DecodeKey for Reversible<$name $(<$lt, $($param),+>)+> {
+            type Output = ($($($param::Output),+)+);
+
+            #[inline]
+            fn decode_key(mut bytes: &[u8]) -> StdResult<Self::Output> {
+                let result = ($($($param::read_encoded(&mut bytes)?),+)+);
+                assert_consumed(bytes)?;
+
+                Ok(result)
+            }
+        }
+
+        impl $(<$lt, $($param: DecodeSegment + ?Sized),+>)+ From<($($(&$lt $param),+)+)> for Reversible<$name $(<$lt, $($param),+>)+> {
+            #[inline]
+            fn from(value: ($($(&$lt $param),+)+)) -> Self {
+                Self($name(value))
+            }
+        }
     };
 }
 
@@ -403,8 +624,47 @@ impl Segment for CanonicalAddr {
     }
 }
 
+macro_rules! impl_bytes_decode_segment {
+    ($data:ty, $output:ty, $decode:expr) => {
+        impl DecodeSegment for $data {
+            type Output = $output;
+
+            #[inline]
+            fn encoded_size(&self) -> usize {
+                4 + Segment::size(self)
+            }
+
+            #[inline]
+            fn write_encoded(&self, buf: &mut Vec<u8>) {
+                let size = u32::try_from(Segment::size(self))
+                    .expect("Key segments must be shorter than u32::MAX bytes.");
+
+                buf.extend_from_slice(&size.to_be_bytes());
+                self.write_segment(buf);
+            }
+
+            #[inline]
+            fn read_encoded(bytes: &mut &[u8]) -> StdResult<Self::Output> {
+                let size = u32::from_be_bytes(read_bytes(bytes)?);
+
+                Self::decode_segment(take_bytes(bytes, size as usize)?)
+            }
+
+            #[inline]
+            fn decode_segment(bytes: &[u8]) -> StdResult<Self::Output> {
+                $decode(bytes)
+            }
+        }
+    };
+}
+
+impl_bytes_decode_segment!(&str, String, decode_string);
+impl_bytes_decode_segment!(String, String, decode_string);
+impl_bytes_decode_segment!(Addr, Addr, |bytes| decode_string(bytes).map(Addr::unchecked));
+impl_bytes_decode_segment!(CanonicalAddr, CanonicalAddr, |bytes: &[u8]| Ok(CanonicalAddr::from(bytes)));
+
 macro_rules! impl_num_segment {
-    ($data:ty) => {
+    ($data:ty, $from_be_bytes:expr) => {
         impl Segment for $data {
             #[inline]
             fn size(&self) -> usize {
@@ -416,18 +676,82 @@ macro_rules! impl_num_segment {
                 buf.extend_from_slice(&self.to_be_bytes());
             }
         }
+
+        impl DecodeSegment for $data {
+            type Output = Self;
+
+            #[inline]
+            fn encoded_size(&self) -> usize {
+                std::mem::size_of::<Self>()
+            }
+
+            #[inline]
+            fn write_encoded(&self, buf: &mut Vec<u8>) {
+                self.write_segment(buf);
+            }
+
+            #[inline]
+            fn read_encoded(bytes: &mut &[u8]) -> StdResult<Self::Output> {
+                Ok($from_be_bytes(read_bytes(bytes)?))
+            }
+
+            #[inline]
+            fn decode_segment(mut bytes: &[u8]) -> StdResult<Self::Output> {
+                let result = Self::read_encoded(&mut bytes)?;
+                assert_consumed(bytes)?;
+
+                Ok(result)
+            }
+        }
     };
 }
 
-impl_num_segment!(u8);
-impl_num_segment!(u16);
-impl_num_segment!(u32);
-impl_num_segment!(u64);
-impl_num_segment!(u128);
-impl_num_segment!(Uint64);
-impl_num_segment!(Uint128);
-impl_num_segment!(Uint256);
-impl_num_segment!(Uint512);
+impl_num_segment!(u8, u8::from_be_bytes);
+impl_num_segment!(u16, u16::from_be_bytes);
+impl_num_segment!(u32, u32::from_be_bytes);
+impl_num_segment!(u64, u64::from_be_bytes);
+impl_num_segment!(u128, u128::from_be_bytes);
+impl_num_segment!(Uint64, |x| Uint64::new(u64::from_be_bytes(x)));
+impl_num_segment!(Uint128, |x| Uint128::new(u128::from_be_bytes(x)));
+impl_num_segment!(Uint256, Uint256::from_be_bytes);
+impl_num_segment!(Uint512, Uint512::from_be_bytes);
+
+#[inline]
+fn take_bytes<'a>(bytes: &mut &'a [u8], size: usize) -> StdResult<&'a [u8]> {
+    if bytes.len() < size {
+        return Err(StdError::invalid_data_size(size, bytes.len()));
+    }
+
+    let (result, rest) = bytes.split_at(size);
+    *bytes = rest;
+
+    Ok(result)
+}
+
+#[inline]
+fn read_bytes<const N: usize>(bytes: &mut &[u8]) -> StdResult<[u8; N]> {
+    let mut result = [0; N];
+    result.copy_from_slice(take_bytes(bytes, N)?);
+
+    Ok(result)
+}
+
+#[inline]
+fn assert_consumed(bytes: &[u8]) -> StdResult<()> {
+    if bytes.is_empty() {
+        Ok(())
+    } else {
+        Err(StdError::generic_err(format!(
+            "Key: {} unexpected bytes left after decoding.",
+            bytes.len()
+        )))
+    }
+}
+
+#[inline]
+fn decode_string(bytes: &[u8]) -> StdResult<String> {
+    String::from_utf8(bytes.to_vec()).map_err(StdError::invalid_utf8)
+}
 
 #[inline]
 fn not_found_error<T>() -> StdError {
@@ -455,4 +779,100 @@ mod tests {
         test(TypedKey3::from((&WORD, &WORD, &WORD)), 3);
         test(TypedKey4::from((&WORD, &WORD, &WORD, &WORD)), 4);
     }
+
+    fn key_bytes(key: impl Key) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(key.size());
+        key.write_segments(&mut buf);
+
+        assert_eq!(buf.len(), key.size());
+
+        buf
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn proptest_reversible_keys(a in ".*", b: u64, c: Vec<u8>, d: u128) {
+            let addr = Addr::unchecked(&a);
+            let canonical = CanonicalAddr::from(c.as_slice());
+            let uint = Uint128::new(d);
+
+            let bytes = key_bytes(Reversible(TypedKey4::from((&a, &b, &canonical, &uint))));
+            let decoded = Reversible::<TypedKey4<String, u64, CanonicalAddr, Uint128>>::decode_key(&bytes).unwrap();
+            proptest::prop_assert_eq!(decoded, (a.clone(), b, canonical.clone(), uint));
+
+            let bytes = key_bytes(Reversible::<TypedKey2<Addr, u128>>::from((&addr, &d)));
+            let decoded = Reversible::<TypedKey2<Addr, u128>>::decode_key(&bytes).unwrap();
+            proptest::prop_assert_eq!(decoded, (addr.clone(), d));
+
+            // Single segment keys can be decoded without opting in.
+            let bytes = key_bytes(TypedKey::from(&a.as_str()));
+            proptest::prop_assert_eq!(TypedKey::<&str>::decode_key(&bytes).unwrap(), a);
+
+            let bytes = key_bytes(TypedKey::from(&canonical));
+            proptest::prop_assert_eq!(TypedKey::<CanonicalAddr>::decode_key(&bytes).unwrap(), canonical);
+        }
+    }
+
+    #[test]
+    fn decode_invalid_keys() {
+        let bytes = key_bytes(Reversible::<TypedKey2<String, u8>>::from((&"key".to_string(), &1)));
+
+        let truncated = Reversible::<TypedKey2<String, u8>>::decode_key(&bytes[..bytes.len() - 1]);
+        assert_eq!(truncated.unwrap_err(), StdError::invalid_data_size(1, 0));
+
+        let trailing = Reversible::<TypedKey<String>>::decode_key(&bytes);
+        assert_eq!(trailing.unwrap_err(), StdError::generic_err("Key: 1 unexpected bytes left after decoding."));
+
+        assert!(TypedKey::<u32>::decode_key(&[0, 1]).is_err());
+        assert!(TypedKey::<String>::decode_key(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn field_storage() {
+        use crate::{self as fadroma, cosmwasm_std::testing::mock_dependencies};
+
+        #[derive(FieldStorage, PartialEq, Debug)]
+        struct Named {
+            a: Uint128,
+            r#type: String
+        }
+
+        #[derive(FieldStorage, PartialEq, Debug)]
+        struct Tuple(String, u64);
+
+        namespace!(TestNs, b"test");
+
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+
+        let store = NamedStore::<TestNs>::new();
+        assert_eq!(store.load(storage).unwrap(), None);
+        assert!(store.load_or_error(storage).is_err());
+
+        store.save_a(storage, &Uint128::new(20)).unwrap();
+        assert_eq!(store.load_a(storage).unwrap(), Some(Uint128::new(20)));
+        assert_eq!(
+            store.load(storage).unwrap_err(),
+            StdError::generic_err("NamedStore: only some of the fields are stored.")
+        );
+
+        store.save_type(storage, &"ABC".into()).unwrap();
+        assert_eq!(storage.get(b"testtype"), Some(serialize(&String::from("ABC")).unwrap()));
+        assert_eq!(
+            store.load(storage).unwrap(),
+            Some(Named { a: Uint128::new(20), r#type: "ABC".into() })
+        );
+
+        store.save(storage, &Named { a: Uint128::new(5), r#type: "DEF".into() }).unwrap();
+        assert_eq!(store.load_type(storage).unwrap(), Some(String::from("DEF")));
+
+        store.remove(storage);
+        assert_eq!(store.load(storage).unwrap(), None);
+
+        let store = TupleStore::<TestNs>::new();
+        store.save(storage, &Tuple("ABC".into(), 20)).unwrap();
+        store.save_1(storage, &30).unwrap();
+        assert_eq!(store.load_0(storage).unwrap(), Some(String::from("ABC")));
+        assert_eq!(store.load_or_error(storage).unwrap(), Tuple("ABC".into(), 30));
+    }
 }
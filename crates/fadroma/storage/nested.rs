@@ -0,0 +1,484 @@
+use std::marker::PhantomData;
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, Binary, StdResult, StdError},
+};
+use super::{
+    Namespace, Key,
+    iterable::{IterableStorage, Iter},
+    serialize, deserialize, not_found_error
+};
+
+const KEY_NS: &[u8] = b"key";
+
+/// A map of maps. Every outer key has its own inner map that keeps
+/// track of its length and can be iterated over or cleared independently
+/// of the others. Just like [`super::map::Map`], inner entries can be
+/// inserted, loaded and removed in constant time and the iteration order
+/// is the insertion order as long as nothing is removed.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{CanonicalAddr, StdResult, testing::mock_dependencies},
+///     storage::{TypedKey, nested::NestedMap}
+/// };
+///
+/// fadroma::namespace!(BalancesNs, b"balances");
+/// const BALANCES: NestedMap<TypedKey<CanonicalAddr>, TypedKey<&str>, u128, BalancesNs> = NestedMap::new();
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let alice = CanonicalAddr::from(b"alice".as_slice());
+/// let bob = CanonicalAddr::from(b"bob".as_slice());
+///
+/// BALANCES.insert(storage, &alice, &"uscrt", &100)?;
+/// BALANCES.insert(storage, &alice, &"sienna", &5)?;
+/// BALANCES.insert(storage, &bob, &"uscrt", &7)?;
+///
+/// assert_eq!(BALANCES.len(storage, &alice)?, 2);
+/// assert_eq!(BALANCES.get(storage, &alice, &"sienna")?, Some(5));
+///
+/// let balances = BALANCES.iter(storage, &alice)?.collect::<StdResult<Vec<_>>>()?;
+/// assert_eq!(balances, [(b"uscrt".to_vec(), 100), (b"sienna".to_vec(), 5)]);
+///
+/// assert_eq!(BALANCES.clear(storage, &alice)?, 2);
+/// assert!(BALANCES.is_empty(storage, &alice)?);
+/// assert_eq!(BALANCES.get(storage, &bob, &"uscrt")?, Some(7));
+/// # Ok(())
+/// # }
+/// ```
+pub struct NestedMap<
+    K1: Key,
+    K2: Key,
+    V: FadromaSerialize + FadromaDeserialize,
+    N: Namespace
+> {
+    outer_data: PhantomData<K1>,
+    inner_data: PhantomData<K2>,
+    value_data: PhantomData<V>,
+    ns_data: PhantomData<N>
+}
+
+/// Iterator over the entries of an inner map of [`NestedMap`].
+/// Yields the bytes written by the inner key together with the value.
+pub struct NestedIter<'storage, V: FadromaDeserialize> {
+    inner: Iter<'storage, NestedEntry>,
+    data: PhantomData<V>
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize)]
+struct NestedEntry {
+    key: Binary,
+    value: Binary
+}
+
+/// The namespace of an inner map.
+#[derive(Clone)]
+struct Prefix(Vec<u8>);
+
+impl<
+    K1: Key,
+    K2: Key,
+    V: FadromaSerialize + FadromaDeserialize,
+    N: Namespace
+> NestedMap<K1, K2, V, N> {
+    /// Creates an instance for the given namespace. The outer key
+    /// is prefixed by its length so that inner maps never overlap.
+    /// The following namespaces are reserved by `NestedMap`:
+    ///  * N + len(K1) + K1 + "key" + K2
+    ///  * N + len(K1) + K1 + "index"
+    ///  * N + len(K1) + K1 + n - where n is a number
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            outer_data: PhantomData,
+            inner_data: PhantomData,
+            value_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Inserts the value in the inner map of `outer`.
+    /// Returns `true` if there was no value under `inner` before.
+    pub fn insert(
+        &self,
+        storage: &mut dyn Storage,
+        outer: impl Into<K1>,
+        inner: impl Into<K2>,
+        value: &V
+    ) -> StdResult<bool> {
+        let prefix = Self::prefix(outer.into());
+        let inner = Self::inner_key(inner.into());
+        let index_key = Self::index_key(&prefix, &inner);
+
+        let entry = NestedEntry {
+            key: Binary(inner),
+            value: Binary(serialize(value)?)
+        };
+
+        let mut iterable = IterableStorage::new(prefix);
+
+        match super::load::<u64>(storage, &index_key)? {
+            Some(index) => {
+                iterable.set(storage, index, &entry)?;
+
+                Ok(false)
+            },
+            None => {
+                let index = iterable.push(storage, &entry)?;
+                super::save(storage, index_key, &index)?;
+
+                Ok(true)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(
+        &self,
+        storage: &dyn Storage,
+        outer: impl Into<K1>,
+        inner: impl Into<K2>
+    ) -> StdResult<Option<V>> {
+        let prefix = Self::prefix(outer.into());
+        let index_key = Self::index_key(&prefix, &Self::inner_key(inner.into()));
+
+        let Some(index) = super::load::<u64>(storage, index_key)? else {
+            return Ok(None);
+        };
+
+        let entry: Option<NestedEntry> = IterableStorage::new(prefix).get(storage, index)?;
+
+        match entry {
+            Some(entry) => Ok(Some(deserialize(&entry.value.0)?)),
+            None => Ok(None)
+        }
+    }
+
+    #[inline]
+    pub fn get_or_error(
+        &self,
+        storage: &dyn Storage,
+        outer: impl Into<K1>,
+        inner: impl Into<K2>
+    ) -> StdResult<V> {
+        let result = self.get(storage, outer, inner)?;
+
+        result.ok_or_else(|| not_found_error::<V>())
+    }
+
+    #[inline]
+    pub fn contains(
+        &self,
+        storage: &dyn Storage,
+        outer: impl Into<K1>,
+        inner: impl Into<K2>
+    ) -> bool {
+        let prefix = Self::prefix(outer.into());
+
+        storage.get(&Self::index_key(&prefix, &Self::inner_key(inner.into()))).is_some()
+    }
+
+    /// Removes the value under `inner` from the inner map of `outer`.
+    /// Returns `true` if there was a value.
+    pub fn remove(
+        &self,
+        storage: &mut dyn Storage,
+        outer: impl Into<K1>,
+        inner: impl Into<K2>
+    ) -> StdResult<bool> {
+        let prefix = Self::prefix(outer.into());
+        let index_key = Self::index_key(&prefix, &Self::inner_key(inner.into()));
+
+        let Some(index) = super::load::<u64>(storage, &index_key)? else {
+            return Ok(false);
+        };
+
+        storage.remove(&index_key);
+
+        let mut iterable = IterableStorage::<NestedEntry, _>::new(prefix.clone());
+
+        if let Some(swapped) = iterable.swap_remove(storage, index)? {
+            super::save(storage, Self::index_key(&prefix, &swapped.key.0), &index)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Removes every entry in the inner map of `outer`.
+    /// Returns the number of entries removed. Every entry has to be removed
+    /// from storage, so use [`NestedMap::clear_batch`] for inner maps that
+    /// may be too large to clear in a single transaction.
+    #[inline]
+    pub fn clear(
+        &self,
+        storage: &mut dyn Storage,
+        outer: impl Into<K1>
+    ) -> StdResult<u64> {
+        let (removed, _) = Self::clear_prefix(storage, Self::prefix(outer.into()), u64::MAX)?;
+
+        Ok(removed)
+    }
+
+    /// Removes at most `limit` entries from the inner map of `outer`. Returns the number
+    /// of entries that are left so that it can be called again in subsequent transactions
+    /// until it returns 0. Returns an error if `limit` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use fadroma::{
+    /// #     cosmwasm_std::{StdResult, testing::mock_dependencies},
+    /// #     storage::{TypedKey, nested::NestedMap}
+    /// # };
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// fadroma::namespace!(VotesNs, b"votes");
+    /// const VOTES: NestedMap<TypedKey<u64>, TypedKey<u64>, bool, VotesNs> = NestedMap::new();
+    ///
+    /// for voter in 0..10 {
+    ///     VOTES.insert(storage, &1, &voter, &true)?;
+    /// }
+    ///
+    /// // Each call could be made in a separate transaction.
+    /// assert_eq!(VOTES.clear_batch(storage, &1, 4)?, 6);
+    /// assert_eq!(VOTES.clear_batch(storage, &1, 4)?, 2);
+    /// assert_eq!(VOTES.clear_batch(storage, &1, 4)?, 0);
+    ///
+    /// assert!(VOTES.is_empty(storage, &1)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn clear_batch(
+        &self,
+        storage: &mut dyn Storage,
+        outer: impl Into<K1>,
+        limit: u64
+    ) -> StdResult<u64> {
+        let (_, remaining) = Self::clear_prefix(storage, Self::prefix(outer.into()), limit)?;
+
+        Ok(remaining)
+    }
+
+    /// Returns the number of entries in the inner map of `outer`.
+    #[inline]
+    pub fn len(
+        &self,
+        storage: &dyn Storage,
+        outer: impl Into<K1>
+    ) -> StdResult<u64> {
+        IterableStorage::<NestedEntry, _>::new(Self::prefix(outer.into())).len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty(
+        &self,
+        storage: &dyn Storage,
+        outer: impl Into<K1>
+    ) -> StdResult<bool> {
+        Ok(self.len(storage, outer)? == 0)
+    }
+
+    /// Returns an iterator over the entries in the inner map of `outer`.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        outer: impl Into<K1>
+    ) -> StdResult<NestedIter<'storage, V>> {
+        let iterable = IterableStorage::<NestedEntry, _>::new(Self::prefix(outer.into()));
+
+        Ok(NestedIter {
+            inner: iterable.iter(storage)?,
+            data: PhantomData
+        })
+    }
+
+    /// Returns the number of entries removed and the number of entries left.
+    fn clear_prefix(
+        storage: &mut dyn Storage,
+        prefix: Prefix,
+        limit: u64
+    ) -> StdResult<(u64, u64)> {
+        if limit == 0 {
+            return Err(StdError::generic_err("NestedMap: limit must be bigger than 0."));
+        }
+
+        let mut iterable = IterableStorage::<NestedEntry, _>::new(prefix.clone());
+        let len = iterable.len(storage)?;
+
+        for index in len.saturating_sub(limit)..len {
+            if let Some(entry) = iterable.get(storage, index)? {
+                storage.remove(&Self::index_key(&prefix, &entry.key.0));
+            }
+        }
+
+        // Removes the entries and only saves the new length once.
+        let remaining = iterable.truncate_batch(storage, 0, limit)?;
+
+        Ok((len - remaining, remaining))
+    }
+
+    #[inline]
+    fn prefix(outer: K1) -> Prefix {
+        let mut prefix = Vec::with_capacity(N::NAMESPACE.len() + 4 + outer.size());
+        prefix.extend_from_slice(N::NAMESPACE);
+        prefix.extend_from_slice(&(outer.size() as u32).to_be_bytes());
+        outer.write_segments(&mut prefix);
+
+        Prefix(prefix)
+    }
+
+    #[inline]
+    fn inner_key(inner: K2) -> Vec<u8> {
+        let mut key = Vec::with_capacity(inner.size());
+        inner.write_segments(&mut key);
+
+        key
+    }
+
+    #[inline]
+    fn index_key(prefix: &Prefix, inner: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(prefix.0.len() + KEY_NS.len() + inner.len());
+        key.extend_from_slice(&prefix.0);
+        key.extend_from_slice(KEY_NS);
+        key.extend_from_slice(inner);
+
+        key
+    }
+}
+
+impl Key for Prefix {
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    fn write_segments(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.0);
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> NestedIter<'storage, V> {
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.inner.len()
+    }
+
+    #[inline]
+    fn decode(entry: StdResult<NestedEntry>) -> StdResult<(Vec<u8>, V)> {
+        let entry = entry?;
+
+        Ok((entry.key.0, deserialize(&entry.value.0)?))
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> Iterator for NestedIter<'storage, V> {
+    type Item = StdResult<(Vec<u8>, V)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(Self::decode)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(Self::decode)
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> DoubleEndedIterator for NestedIter<'storage, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(Self::decode)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth_back(n).map(Self::decode)
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> ExactSizeIterator for NestedIter<'storage, V> { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    type TestMap = NestedMap<TypedKey<'static, &'static str>, TypedKey<'static, u8>, u64, TestNs>;
+
+    #[test]
+    fn inner_maps_are_independent() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let map = TestMap::new();
+
+        for i in 0..10u8 {
+            assert!(map.insert(storage, &"a", &i, &(i as u64)).unwrap());
+            assert!(map.insert(storage, &"ab", &i, &(i as u64 * 10)).unwrap());
+        }
+
+        assert!(!map.insert(storage, &"a", &3, &33).unwrap());
+        assert_eq!(map.get(storage, &"a", &3).unwrap(), Some(33));
+        assert_eq!(map.len(storage, &"a").unwrap(), 10);
+
+        assert!(map.remove(storage, &"a", &0).unwrap());
+        assert!(!map.remove(storage, &"a", &0).unwrap());
+        assert!(!map.contains(storage, &"a", &0));
+        // The last entry was swapped in place of the removed one.
+        assert_eq!(map.get(storage, &"a", &9).unwrap(), Some(9));
+        assert_eq!(map.iter(storage, &"a").unwrap().next().unwrap().unwrap(), (vec![9], 9));
+
+        assert_eq!(map.clear(storage, &"ab").unwrap(), 10);
+        assert!(map.is_empty(storage, &"ab").unwrap());
+        assert_eq!(map.iter(storage, &"ab").unwrap().count(), 0);
+
+        for i in 0..10u8 {
+            assert!(!map.contains(storage, &"ab", &i));
+        }
+
+        let mut values = map.iter(storage, &"a").unwrap()
+            .map(|x| x.unwrap().1)
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, [1, 2, 4, 5, 6, 7, 8, 9, 33]);
+
+        assert!(map.clear_batch(storage, &"a", 0).is_err());
+        assert_eq!(map.clear_batch(storage, &"a", 4).unwrap(), 5);
+        assert_eq!(map.len(storage, &"a").unwrap(), 5);
+
+        let remaining = map.iter(storage, &"a").unwrap()
+            .map(|x| x.unwrap().0[0])
+            .collect::<Vec<_>>();
+
+        for i in 0..10u8 {
+            assert_eq!(map.contains(storage, &"a", &i), remaining.contains(&i));
+        }
+
+        assert_eq!(map.clear_batch(storage, &"a", 10).unwrap(), 0);
+        assert!(map.is_empty(storage, &"a").unwrap());
+        assert!(!map.contains(storage, &"a", &remaining[0]));
+
+        assert!(map.insert(storage, &"ab", &1, &1).unwrap());
+        assert_eq!(map.len(storage, &"ab").unwrap(), 1);
+    }
+}
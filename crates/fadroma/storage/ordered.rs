@@ -0,0 +1,499 @@
+//! Storage types that keep their keys sorted and support range queries.
+
+use std::marker::PhantomData;
+
+use crate::{
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult}
+};
+use super::{
+    Namespace, Key, Segment,
+    btree::{BTree, Keys, segment_bytes},
+    not_found_error
+};
+
+pub use super::btree::{Order, Bound};
+
+const KEY_VALUE: &[u8] = b"value";
+
+/// A key-value storage type that keeps its entries sorted by the bytes
+/// that their [`Key`] writes. In addition to the usual operations, it
+/// supports iterating over `(key, value)` pairs within a range of keys
+/// in both directions, prefix scans and cursor based pagination.
+///
+/// The order of the keys is kept in a B+ tree laid out across storage
+/// keys so it works without `Storage::range` which is unavailable on
+/// Secret Network. Getting a value by its key is a single storage read.
+///
+/// Keys are compared byte by byte. Segments such as numbers and canonical
+/// addresses have a fixed size, so they are sorted as expected. Be aware
+/// that a variable sized leading segment (like a [`String`]) also matches
+/// prefix scans for longer strings that begin with it.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::mock_dependencies},
+///     storage::{TypedKey2, ordered::{OrderedMap, Bound, Order}}
+/// };
+///
+/// fadroma::namespace!(OrdersNs, b"orders");
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// // Orders by price and id.
+/// let orders = OrderedMap::<TypedKey2<u64, u32>, String, OrdersNs>::new();
+///
+/// orders.insert(storage, (&300, &1), &"c".into())?;
+/// orders.insert(storage, (&100, &2), &"a".into())?;
+/// orders.insert(storage, (&200, &3), &"b".into())?;
+/// orders.insert(storage, (&200, &4), &"d".into())?;
+///
+/// // All orders with a price between 150 and 300.
+/// let values = orders.range(
+///     storage,
+///     Bound::inclusive(&150u64),
+///     Bound::exclusive(&300u64),
+///     Order::Ascending
+/// )?
+///     .map(|x| x.map(|(_, value)| value))
+///     .collect::<StdResult<Vec<String>>>()?;
+///
+/// assert_eq!(values, ["b", "d"]);
+///
+/// // All orders at a price of 200, highest id first.
+/// let values = orders.prefix(storage, &200u64, Order::Descending)?
+///     .map(|x| x.map(|(_, value)| value))
+///     .collect::<StdResult<Vec<String>>>()?;
+///
+/// assert_eq!(values, ["d", "b"]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct OrderedMap<
+    K: Key,
+    V: FadromaSerialize + FadromaDeserialize,
+    N: Namespace
+> {
+    key_data: PhantomData<K>,
+    value_data: PhantomData<V>,
+    ns_data: PhantomData<N>
+}
+
+/// Iterator over the `(key, value)` pairs of an [`OrderedMap`] within a range.
+/// The keys are the bytes written by the [`Key`] of each entry.
+pub struct Range<'storage, V: FadromaDeserialize> {
+    storage: &'storage dyn Storage,
+    keys: Keys<'storage>,
+    ns: &'static [u8],
+    data: PhantomData<V>
+}
+
+/// A page of entries returned by [`OrderedMap::page`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Page<V> {
+    pub entries: Vec<(Vec<u8>, V)>,
+    /// The cursor to pass in order to get the next page
+    /// or [`None`] if this is the last one.
+    pub next: Option<Vec<u8>>
+}
+
+impl<
+    K: Key,
+    V: FadromaSerialize + FadromaDeserialize,
+    N: Namespace
+> OrderedMap<K, V, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `OrderedMap`:
+    ///  * `ns` + "meta"
+    ///  * `ns` + "node" + n - where n is a number
+    ///  * `ns` + "value" + key
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            key_data: PhantomData,
+            value_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Inserts or overwrites the value under the given key.
+    /// Returns `true` if the key didn't exist before.
+    pub fn insert(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        value: &V
+    ) -> StdResult<bool> {
+        self.insert_raw(storage, &key_bytes(key.into()), value)
+    }
+
+    #[inline]
+    pub fn get(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<Option<V>> {
+        self.get_raw(storage, &key_bytes(key.into()))
+    }
+
+    #[inline]
+    pub fn get_or_error(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<V> {
+        let result = self.get(storage, key)?;
+
+        result.ok_or_else(|| not_found_error::<V>())
+    }
+
+    #[inline]
+    pub fn contains(&self, storage: &dyn Storage, key: impl Into<K>) -> bool {
+        storage.get(&Self::value_key(&key_bytes(key.into()))).is_some()
+    }
+
+    /// Removes the entry under the given key.
+    /// Returns `true` if the key existed.
+    pub fn remove(&self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<bool> {
+        self.remove_raw(storage, &key_bytes(key.into()))
+    }
+
+    /// Returns the number of entries currently stored.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        self.tree().len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns the number of keys that are smaller than the given one.
+    /// The key itself doesn't need to be present in the map.
+    #[inline]
+    pub fn rank(&self, storage: &dyn Storage, key: impl Into<K>) -> StdResult<u64> {
+        self.tree().rank(storage, &key_bytes(key.into()))
+    }
+
+    /// Returns the entry at the `n`-th position in ascending order.
+    pub fn nth(&self, storage: &dyn Storage, n: u64) -> StdResult<Option<(Vec<u8>, V)>> {
+        let Some(key) = self.tree().nth(storage, n)? else {
+            return Ok(None);
+        };
+
+        let value = self.get_raw(storage, &key)?
+            .ok_or_else(|| not_found_error::<V>())?;
+
+        Ok(Some((key, value)))
+    }
+
+    /// Iterates over all entries in the given order.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        order: Order
+    ) -> StdResult<Range<'storage, V>> {
+        self.range(storage, Bound::Unbounded, Bound::Unbounded, order)
+    }
+
+    /// Iterates over the entries with keys between `min` and `max` in the given order.
+    pub fn range<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        min: Bound,
+        max: Bound,
+        order: Order
+    ) -> StdResult<Range<'storage, V>> {
+        Ok(Range {
+            storage,
+            keys: self.tree().keys(storage, min, max, order)?,
+            ns: N::NAMESPACE,
+            data: PhantomData
+        })
+    }
+
+    /// Iterates over the entries with keys that begin with the bytes written
+    /// by `prefix`. Pass one or more leading segments of the key to get all
+    /// entries that share them, for example the first segment of a [`super::TypedKey2`]
+    /// or a [`super::TypedKey2`] made of the first two segments of a [`super::TypedKey3`].
+    #[inline]
+    pub fn prefix<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        prefix: &(impl Segment + ?Sized),
+        order: Order
+    ) -> StdResult<Range<'storage, V>> {
+        let (min, max) = Bound::prefix(segment_bytes(prefix));
+
+        self.range(storage, min, max, order)
+    }
+
+    /// Returns up to `limit` entries that come after the `cursor` in the
+    /// given order. Pass [`None`] to get the first page and [`Page::next`]
+    /// to get the ones after that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use fadroma::{
+    /// #     cosmwasm_std::{StdResult, testing::mock_dependencies},
+    /// #     storage::{TypedKey, ordered::{OrderedMap, Order}}
+    /// # };
+    /// # fadroma::namespace!(NumbersNs, b"numbers");
+    /// # fn main() -> StdResult<()> {
+    /// # let mut deps = mock_dependencies();
+    /// # let storage = deps.as_mut().storage;
+    /// let numbers = OrderedMap::<TypedKey<u8>, u8, NumbersNs>::new();
+    ///
+    /// for i in 0..5 {
+    ///     numbers.insert(storage, &i, &i)?;
+    /// }
+    ///
+    /// let page = numbers.page(storage, None, 3, Order::Ascending)?;
+    /// assert_eq!(page.entries.iter().map(|x| x.1).collect::<Vec<_>>(), [0, 1, 2]);
+    ///
+    /// let page = numbers.page(storage, page.next, 3, Order::Ascending)?;
+    /// assert_eq!(page.entries.iter().map(|x| x.1).collect::<Vec<_>>(), [3, 4]);
+    /// assert_eq!(page.next, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn page(
+        &self,
+        storage: &dyn Storage,
+        cursor: Option<Vec<u8>>,
+        limit: usize,
+        order: Order
+    ) -> StdResult<Page<V>> {
+        let start = cursor.map(Bound::Exclusive).unwrap_or(Bound::Unbounded);
+
+        let mut iter = match order {
+            Order::Ascending => self.range(storage, start, Bound::Unbounded, order)?,
+            Order::Descending => self.range(storage, Bound::Unbounded, start, order)?
+        };
+
+        let entries = iter.by_ref()
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let next = match entries.last() {
+            Some((key, _)) if iter.keys.next().is_some() => Some(key.clone()),
+            _ => None
+        };
+
+        Ok(Page { entries, next })
+    }
+
+    pub(crate) fn insert_raw(
+        &self,
+        storage: &mut dyn Storage,
+        key: &[u8],
+        value: &V
+    ) -> StdResult<bool> {
+        let is_new = self.tree().insert(storage, key)?;
+        super::save(storage, Self::value_key(key), value)?;
+
+        Ok(is_new)
+    }
+
+    #[inline]
+    pub(crate) fn get_raw(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<V>> {
+        super::load(storage, Self::value_key(key))
+    }
+
+    pub(crate) fn remove_raw(&self, storage: &mut dyn Storage, key: &[u8]) -> StdResult<bool> {
+        let existed = self.tree().remove(storage, key)?;
+
+        if existed {
+            super::remove(storage, Self::value_key(key));
+        }
+
+        Ok(existed)
+    }
+
+    #[inline]
+    fn tree(&self) -> BTree<'static> {
+        BTree::new(N::NAMESPACE)
+    }
+
+    #[inline]
+    fn value_key(key: &[u8]) -> Vec<u8> {
+        value_key(N::NAMESPACE, key)
+    }
+}
+
+impl<
+    K: Key,
+    V: FadromaSerialize + FadromaDeserialize + Default,
+    N: Namespace
+> OrderedMap<K, V, N> {
+    #[inline]
+    pub fn get_or_default(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<K>,
+    ) -> StdResult<V> {
+        let result: Option<V> = self.get(storage, key)?;
+
+        Ok(result.unwrap_or_default())
+    }
+}
+
+impl<'storage, V: FadromaDeserialize> Iterator for Range<'storage, V> {
+    type Item = StdResult<(Vec<u8>, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = match self.keys.next()? {
+            Ok(key) => key,
+            Err(err) => return Some(Err(err))
+        };
+
+        let value = super::load(self.storage, value_key(self.ns, &key))
+            .and_then(|x| x.ok_or_else(|| not_found_error::<V>()));
+
+        Some(value.map(|value| (key, value)))
+    }
+}
+
+#[inline]
+pub(crate) fn key_bytes(key: impl Key) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(key.size());
+    key.write_segments(&mut bytes);
+
+    bytes
+}
+
+#[inline]
+pub(crate) fn value_key(ns: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(ns.len() + KEY_VALUE.len() + key.len());
+    result.extend_from_slice(ns);
+    result.extend_from_slice(KEY_VALUE);
+    result.extend_from_slice(key);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::{TypedKey, TypedKey2},
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    fn values<V: FadromaDeserialize>(range: Range<V>) -> Vec<V> {
+        range.map(|x| x.unwrap().1).collect()
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let map = OrderedMap::<TypedKey<String>, u8, TestNs>::new();
+
+        let keys = ["b", "a", "d", "c"].map(|x| x.to_string());
+
+        for (i, key) in keys.iter().enumerate() {
+            assert!(map.insert(storage, key, &(i as u8)).unwrap());
+        }
+
+        assert!(!map.insert(storage, &keys[0], &10).unwrap());
+        assert_eq!(map.len(storage).unwrap(), 4);
+        assert_eq!(map.get(storage, &keys[0]).unwrap(), Some(10));
+        assert!(map.contains(storage, &keys[1]));
+
+        let entries = map.iter(storage, Order::Ascending).unwrap()
+            .map(|x| x.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(entries, vec![
+            (b"a".to_vec(), 1),
+            (b"b".to_vec(), 10),
+            (b"c".to_vec(), 3),
+            (b"d".to_vec(), 2)
+        ]);
+
+        assert!(map.remove(storage, &keys[1]).unwrap());
+        assert!(!map.remove(storage, &keys[1]).unwrap());
+        assert!(!map.contains(storage, &keys[1]));
+        assert_eq!(map.get(storage, &keys[1]).unwrap(), None);
+        assert_eq!(map.len(storage).unwrap(), 3);
+
+        assert_eq!(values(map.iter(storage, Order::Descending).unwrap()), [2, 3, 10]);
+
+        assert_eq!(map.rank(storage, &keys[3]).unwrap(), 1);
+        assert_eq!(map.rank(storage, &keys[1]).unwrap(), 0);
+        assert_eq!(map.nth(storage, 2).unwrap(), Some((b"d".to_vec(), 2)));
+        assert_eq!(map.nth(storage, 3).unwrap(), None);
+    }
+
+    #[test]
+    fn ranges_and_prefixes() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let map = OrderedMap::<TypedKey2<u32, u32>, u32, TestNs>::new();
+
+        for a in 0..50u32 {
+            for b in 0..5u32 {
+                map.insert(storage, (&a, &b), &(a * 10 + b)).unwrap();
+            }
+        }
+
+        let range = map.range(
+            storage,
+            Bound::exclusive(&TypedKey2::from((&10u32, &3u32))),
+            Bound::inclusive(&11u32),
+            Order::Ascending
+        ).unwrap();
+        assert_eq!(values(range), [104]);
+
+        let range = map.range(
+            storage,
+            Bound::inclusive(&48u32),
+            Bound::Unbounded,
+            Order::Descending
+        ).unwrap();
+        assert_eq!(values(range), [494, 493, 492, 491, 490, 484, 483, 482, 481, 480]);
+
+        assert_eq!(values(map.prefix(storage, &7u32, Order::Ascending).unwrap()), [70, 71, 72, 73, 74]);
+        assert_eq!(values(map.prefix(storage, &50u32, Order::Ascending).unwrap()), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn pagination() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let map = OrderedMap::<TypedKey<u16>, u16, TestNs>::new();
+
+        for i in 0..100u16 {
+            map.insert(storage, &i, &i).unwrap();
+        }
+
+        for order in [Order::Ascending, Order::Descending] {
+            let mut cursor = None;
+            let mut all = vec![];
+
+            loop {
+                let page = map.page(storage, cursor, 30, order).unwrap();
+                assert!(page.entries.len() <= 30);
+
+                all.extend(page.entries.into_iter().map(|x| x.1));
+
+                if page.next.is_none() {
+                    break;
+                }
+
+                cursor = page.next;
+            }
+
+            let mut expected = (0..100).collect::<Vec<u16>>();
+
+            if order == Order::Descending {
+                expected.reverse();
+            }
+
+            assert_eq!(all, expected);
+        }
+
+        let page = map.page(storage, None, 100, Order::Ascending).unwrap();
+        assert_eq!(page.entries.len(), 100);
+        assert_eq!(page.next, None);
+    }
+}
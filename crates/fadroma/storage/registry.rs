@@ -0,0 +1,482 @@
+//! Detection of [`Namespace`]s whose keys may collide.
+//!
+//! The [`crate::assert_namespaces`] macro checks a list of namespaces at
+//! compile time and fails the build if any of them is a prefix of another.
+//! This is conservative since not every prefix leads to a collision (a
+//! [`super::SingleItem`] only ever uses its exact namespace as a key).
+//! The [`Registry`] is meant to be used in tests and takes into account
+//! how each namespace is used in order to report only actual collisions
+//! together with the reserved key of the storage type that causes them.
+//!
+//! # Registration
+//!
+//! Declare the namespaces of a module with [`crate::namespaces`] instead of
+//! separate [`crate::namespace`] invocations. Every namespace declared with
+//! it is checked against the others at compile time and registered in a
+//! generated constant which can be passed to [`Registry::register_all`],
+//! so that a namespace can't be added without being checked.
+//!
+//! Namespaces declared in different modules or crates still have to be brought
+//! together by hand - by passing each module's constant to the same [`Registry`].
+//! Collecting every invocation of a macro across a crate and its dependencies
+//! requires a linker based registry such as `inventory` or `linkme`. Those don't
+//! work when targeting `wasm32` and would add code to every contract. Keep a single
+//! [`Registry`] test per contract which includes the namespaces of all of its modules
+//! and of the libraries it depends on (such as the SNIP-20 implementation in this crate).
+
+use std::{any, fmt};
+
+use super::Namespace;
+
+/// Fail the build if any of the given [`Namespace`]s is equal
+/// to or a prefix of another one. See [`crate::storage::registry`].
+///
+/// # Examples
+///
+/// ```
+/// fadroma::namespace!(ConfigNs, b"config");
+/// fadroma::namespace!(BalancesNs, b"balances");
+///
+/// fadroma::assert_namespaces!(ConfigNs, BalancesNs);
+/// ```
+///
+/// ```compile_fail
+/// fadroma::namespace!(BalancesNs, b"balances");
+/// fadroma::namespace!(BalancesIndexNs, b"balancesindex");
+///
+/// fadroma::assert_namespaces!(BalancesNs, BalancesIndexNs);
+/// ```
+#[macro_export]
+macro_rules! assert_namespaces {
+    (@pairs $head:ty) => {};
+    (@pairs $head:ty, $($tail:ty),+) => {
+        $(
+            if $crate::storage::registry::overlaps(
+                <$head as $crate::storage::Namespace>::NAMESPACE,
+                <$tail as $crate::storage::Namespace>::NAMESPACE
+            ) {
+                panic!(concat!(
+                    "Namespaces ",
+                    stringify!($head),
+                    " and ",
+                    stringify!($tail),
+                    " overlap."
+                ));
+            }
+        )+
+
+        $crate::assert_namespaces!(@pairs $($tail),+);
+    };
+    ($($ns:ty),+ $(,)?) => {
+        const _: () = {
+            $crate::assert_namespaces!(@pairs $($ns),+);
+        };
+    };
+}
+
+/// Declares [`Namespace`]s like [`crate::namespace`], fails the build if any of
+/// them overlap like [`crate::assert_namespaces`] and registers all of them in a
+/// constant slice of [`Registered`] namespaces, together with the [`Layout`] of
+/// the storage type that uses each one, for use with [`Registry::register_all`].
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::storage::registry::Registry;
+///
+/// fadroma::namespaces! {
+///     pub NAMESPACES = [
+///         pub ConfigNs: b"config" => SINGLE_ITEM,
+///         pub BalancesNs: b"balances" => MAP
+///     ];
+/// }
+///
+/// assert_eq!(NAMESPACES.len(), 2);
+///
+/// Registry::new()
+///     .register_all(NAMESPACES)
+///     .assert_no_collisions();
+/// ```
+///
+/// ```compile_fail
+/// fadroma::namespaces! {
+///     NAMESPACES = [
+///         BalancesNs: b"balances" => MAP,
+///         BalancesIndexNs: b"balancesindex" => SINGLE_ITEM
+///     ];
+/// }
+/// ```
+#[macro_export]
+macro_rules! namespaces {
+    (
+        $(#[$meta:meta])*
+        $visibility:vis $list:ident = [
+            $($ns_visibility:vis $name:ident: $bytes:literal => $layout:ident),+ $(,)?
+        ];
+    ) => {
+        $($crate::namespace!($ns_visibility $name, $bytes);)+
+
+        $crate::assert_namespaces!($($name),+);
+
+        $(#[$meta])*
+        $visibility const $list: &[$crate::storage::registry::Registered] = &[
+            $(
+                $crate::storage::registry::Registered {
+                    name: stringify!($name),
+                    bytes: <$name as $crate::storage::Namespace>::NAMESPACE,
+                    layout: $crate::storage::registry::Layout::$layout
+                }
+            ),+
+        ];
+    };
+}
+
+/// Returns `true` if one of the namespaces is equal to or a prefix of the other.
+pub const fn overlaps(a: &[u8], b: &[u8]) -> bool {
+    let len = if a.len() < b.len() { a.len() } else { b.len() };
+    let mut i = 0;
+
+    while i < len {
+        if a[i] != b[i] {
+            return false;
+        }
+
+        i += 1;
+    }
+
+    true
+}
+
+/// Describes which keys a storage type writes under its namespace.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layout {
+    /// Only the namespace itself is used as a key.
+    Exact,
+    /// The namespace is a prefix to other keys. The given
+    /// suffixes are reserved by the storage type for its own use.
+    Prefix(&'static [&'static [u8]])
+}
+
+impl Layout {
+    /// [`super::SingleItem`]
+    pub const SINGLE_ITEM: Self = Self::Exact;
+    /// [`super::ItemSpace`] and other types with a user provided key.
+    pub const ITEM_SPACE: Self = Self::Prefix(&[]);
+    /// [`super::iterable::IterableStorage`] which also uses `ns + n`.
+    pub const ITERABLE: Self = Self::Prefix(&[b"index"]);
+    /// [`super::map::Map`] and [`super::map::InsertOnlyMap`] which also use `ns + n`.
+    pub const MAP: Self = Self::Prefix(&[b"key", b"index"]);
+    /// [`super::set::Set`] which also uses `ns + n`.
+    pub const SET: Self = Self::Prefix(&[b"key", b"index"]);
+    /// [`super::nested::NestedMap`] which prefixes all of its keys with the outer key.
+    pub const NESTED_MAP: Self = Self::Prefix(&[]);
+    /// [`super::ordered::OrderedMap`]
+    pub const ORDERED_MAP: Self = Self::Prefix(&[b"meta", b"node", b"value"]);
+    /// [`super::indexed::IndexedMap`]
+    pub const INDEXED_MAP: Self = Self::Prefix(&[b"meta", b"node", b"value", b"idx"]);
+    /// [`super::deque::Deque`] and [`super::deque::RingBuffer`] which also use `ns + n`.
+    pub const DEQUE: Self = Self::Prefix(&[b"meta"]);
+    /// [`super::sorted::SortedSet`]
+    pub const SORTED_SET: Self = Self::Prefix(&[b"meta", b"node", b"member"]);
+    /// [`super::counter::RewardPerShare`]
+    pub const REWARD_PER_SHARE: Self = Self::Prefix(&[b"pool", b"account"]);
+    /// [`super::bitmap::Bitmap`]
+    pub const BITMAP: Self = Self::Prefix(&[b"word", b"count"]);
+    /// [`super::merkle::MerkleLog`]
+    #[cfg(feature = "crypto")]
+    pub const MERKLE_LOG: Self = Self::Prefix(&[b"meta", b"entry", b"node"]);
+    /// [`super::expiring::ExpiringMap`]
+    pub const EXPIRING_MAP: Self = Self::Prefix(&[b"value", b"height", b"time"]);
+    /// [`super::snapshot::SnapshotItem`] and [`super::snapshot::SnapshotItemSpace`]
+    pub const SNAPSHOT: Self = Self::Prefix(&[b"value", b"meta", b"height", b"cp"]);
+}
+
+/// Collects namespaces together with how they are used
+/// and reports the ones whose keys may collide.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::storage::registry::{Registry, Layout, CollisionKind};
+///
+/// fadroma::namespace!(ConfigNs, b"config");
+/// fadroma::namespace!(BalancesNs, b"balances");
+/// fadroma::namespace!(BalancesIndexNs, b"balancesindex");
+///
+/// let mut registry = Registry::new();
+/// registry
+///     .register::<ConfigNs>(Layout::SINGLE_ITEM)
+///     .register::<BalancesNs>(Layout::ITERABLE)
+///     .register::<BalancesIndexNs>(Layout::SINGLE_ITEM);
+///
+/// let collisions = registry.collisions();
+/// assert_eq!(collisions.len(), 1);
+/// assert_eq!(collisions[0].kind, CollisionKind::Reserved(b"index"));
+/// ```
+#[derive(Default, Debug)]
+pub struct Registry {
+    entries: Vec<Registered>
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Registered {
+    pub name: &'static str,
+    pub bytes: &'static [u8],
+    pub layout: Layout
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Collision {
+    /// The namespace whose keys include the other namespace.
+    pub outer: Registered,
+    pub inner: Registered,
+    pub kind: CollisionKind
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CollisionKind {
+    /// Both namespaces are the same.
+    Duplicate,
+    /// The keys of the inner namespace start with one
+    /// of the given reserved suffixes of the outer one.
+    Reserved(&'static [u8]),
+    /// The keys of the inner namespace may be equal
+    /// to the user provided keys of the outer one.
+    Prefix
+}
+
+impl Registry {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn register<N: Namespace>(&mut self, layout: Layout) -> &mut Self {
+        self.register_bytes(any::type_name::<N>(), N::NAMESPACE, layout)
+    }
+
+    pub fn register_bytes(
+        &mut self,
+        name: &'static str,
+        bytes: &'static [u8],
+        layout: Layout
+    ) -> &mut Self {
+        self.entries.push(Registered { name, bytes, layout });
+
+        self
+    }
+
+    /// Registers all of the given namespaces, usually
+    /// the ones declared by [`crate::namespaces`].
+    #[inline]
+    pub fn register_all(&mut self, namespaces: &[Registered]) -> &mut Self {
+        self.entries.extend_from_slice(namespaces);
+
+        self
+    }
+
+    /// Returns all pairs of namespaces whose keys may collide.
+    pub fn collisions(&self) -> Vec<Collision> {
+        let mut result = vec![];
+
+        for (i, a) in self.entries.iter().enumerate() {
+            for b in &self.entries[i + 1..] {
+                let (outer, inner) = if a.bytes.len() <= b.bytes.len() {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+
+                if let Some(kind) = collision(outer, inner) {
+                    result.push(Collision {
+                        outer: *outer,
+                        inner: *inner,
+                        kind
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Panics with a description of every collision if there are any.
+    pub fn assert_no_collisions(&self) {
+        let collisions = self.collisions();
+
+        if !collisions.is_empty() {
+            let report = collisions.iter()
+                .map(|x| format!("  * {}", x))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            panic!("Found {} namespace collision(s):\n{}", collisions.len(), report);
+        }
+    }
+}
+
+fn collision(outer: &Registered, inner: &Registered) -> Option<CollisionKind> {
+    if outer.bytes == inner.bytes {
+        return Some(CollisionKind::Duplicate);
+    }
+
+    if !inner.bytes.starts_with(outer.bytes) {
+        return None;
+    }
+
+    match outer.layout {
+        // The only key is shorter than any key of the inner namespace.
+        Layout::Exact => None,
+        Layout::Prefix(reserved) => {
+            let rest = &inner.bytes[outer.bytes.len()..];
+
+            let kind = reserved.iter()
+                .find(|suffix| rest.starts_with(suffix) || suffix.starts_with(rest))
+                .map_or(CollisionKind::Prefix, |suffix| CollisionKind::Reserved(suffix));
+
+            Some(kind)
+        }
+    }
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outer = String::from_utf8_lossy(self.outer.bytes);
+        let inner = String::from_utf8_lossy(self.inner.bytes);
+
+        match self.kind {
+            CollisionKind::Duplicate => write!(
+                f,
+                "{} and {} both use the namespace \"{}\"",
+                self.outer.name,
+                self.inner.name,
+                outer
+            ),
+            CollisionKind::Reserved(suffix) => write!(
+                f,
+                "{} (\"{}\") overlaps with the key \"{}\" + \"{}\" reserved by {}",
+                self.inner.name,
+                inner,
+                outer,
+                String::from_utf8_lossy(suffix),
+                self.outer.name
+            ),
+            CollisionKind::Prefix => write!(
+                f,
+                "{} (\"{}\") is prefixed by {} (\"{}\") and may collide with its keys",
+                self.inner.name,
+                inner,
+                self.outer.name,
+                outer
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace;
+
+    namespace!(ANs, b"abc");
+    namespace!(BNs, b"abcdef");
+    namespace!(CNs, b"abcindex1");
+    namespace!(DNs, b"abc");
+    namespace!(ENs, b"xyz");
+
+    crate::assert_namespaces!(ANs, ENs);
+
+    #[test]
+    fn overlapping_bytes() {
+        assert!(overlaps(b"abc", b"abc"));
+        assert!(overlaps(b"abc", b"abcd"));
+        assert!(overlaps(b"abcd", b"abc"));
+        assert!(overlaps(b"", b"abc"));
+        assert!(!overlaps(b"abd", b"abc"));
+    }
+
+    #[test]
+    fn collisions() {
+        let mut registry = Registry::new();
+        registry
+            .register::<ANs>(Layout::ITERABLE)
+            .register::<BNs>(Layout::SINGLE_ITEM)
+            .register::<CNs>(Layout::ITEM_SPACE)
+            .register::<ENs>(Layout::MAP);
+
+        let collisions = registry.collisions();
+        assert_eq!(collisions.len(), 2);
+
+        assert_eq!(collisions[0].outer.bytes, ANs::NAMESPACE);
+        assert_eq!(collisions[0].inner.bytes, BNs::NAMESPACE);
+        assert_eq!(collisions[0].kind, CollisionKind::Prefix);
+
+        assert_eq!(collisions[1].inner.bytes, CNs::NAMESPACE);
+        assert_eq!(collisions[1].kind, CollisionKind::Reserved(b"index"));
+
+        // A single item can't collide with longer namespaces.
+        let mut registry = Registry::new();
+        registry
+            .register::<ANs>(Layout::SINGLE_ITEM)
+            .register::<BNs>(Layout::MAP)
+            .register::<ENs>(Layout::SINGLE_ITEM);
+
+        registry.assert_no_collisions();
+
+        registry.register::<DNs>(Layout::SINGLE_ITEM);
+        assert_eq!(registry.collisions()[0].kind, CollisionKind::Duplicate);
+    }
+
+    #[test]
+    #[should_panic(expected = "Found 1 namespace collision(s):")]
+    fn assert_no_collisions() {
+        let mut registry = Registry::new();
+        registry
+            .register::<ANs>(Layout::ITEM_SPACE)
+            .register::<DNs>(Layout::ITEM_SPACE);
+
+        registry.assert_no_collisions();
+    }
+
+    crate::namespaces! {
+        NAMESPACES = [
+            pub(crate) ConfigNs: b"config" => SINGLE_ITEM,
+            OffersNs: b"offers" => INDEXED_MAP,
+        ];
+    }
+
+    #[test]
+    fn declared_namespaces() {
+        assert_eq!(ConfigNs::NAMESPACE, b"config");
+        assert_eq!(NAMESPACES[1], Registered {
+            name: "OffersNs",
+            bytes: b"offers",
+            layout: Layout::INDEXED_MAP
+        });
+
+        let mut registry = Registry::new();
+        registry
+            .register_all(NAMESPACES)
+            .register::<ANs>(Layout::ITEM_SPACE);
+
+        registry.assert_no_collisions();
+
+        registry.register_bytes("OffersMetaNs", b"offersmeta", Layout::SINGLE_ITEM);
+        assert_eq!(registry.collisions()[0].kind, CollisionKind::Reserved(b"meta"));
+    }
+
+    #[test]
+    fn fadroma_namespaces() {
+        use crate::{admin, killswitch, storage::migration};
+
+        let mut registry = Registry::new();
+        registry
+            .register::<admin::AdminNs>(Layout::SINGLE_ITEM)
+            .register::<admin::PendingAdminNs>(Layout::SINGLE_ITEM)
+            .register::<killswitch::KillswitchNs>(Layout::SINGLE_ITEM)
+            .register::<migration::MigrationNs>(Layout::SINGLE_ITEM);
+
+        registry.assert_no_collisions();
+    }
+}
@@ -0,0 +1,241 @@
+use std::marker::PhantomData;
+
+use crate::cosmwasm_std::{Storage, Binary, StdResult};
+use super::{
+    Namespace, Key, StaticKey,
+    iterable::{IterableStorage, Iter}
+};
+
+const KEY_NS: StaticKey = StaticKey(b"key");
+
+/// A storage type that holds a set of unique keys. Inserting, removing
+/// and checking whether a key is a member are constant time operations
+/// and the members can be iterated over. Removing a member swaps the last
+/// one in its place, just like [`super::map::Map`] does, so the iteration
+/// order is the insertion order only as long as nothing is removed.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{CanonicalAddr, StdResult, testing::mock_dependencies},
+///     storage::{TypedKey, set::Set}
+/// };
+///
+/// fadroma::namespace!(MintersNs, b"minters");
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let mut minters = Set::<TypedKey<CanonicalAddr>, MintersNs>::new();
+///
+/// let alice = CanonicalAddr::from(b"alice".as_slice());
+/// let bob = CanonicalAddr::from(b"bob".as_slice());
+///
+/// assert!(minters.insert(storage, &alice)?);
+/// assert!(minters.insert(storage, &bob)?);
+/// assert!(!minters.insert(storage, &alice)?);
+///
+/// assert!(minters.contains(storage, &alice));
+/// assert_eq!(minters.len(storage)?, 2);
+///
+/// assert!(minters.remove(storage, &alice)?);
+/// assert!(!minters.contains(storage, &alice));
+///
+/// let members = minters.iter(storage)?.collect::<StdResult<Vec<_>>>()?;
+/// assert_eq!(members, [b"bob".to_vec()]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Set<K: Key, N: Namespace> {
+    iterable: IterableStorage<Binary, StaticKey>,
+    key_data: PhantomData<K>,
+    ns_data: PhantomData<N>
+}
+
+/// Iterator over the members of [`Set`].
+/// Yields the bytes written by the [`Key`] of each member.
+pub struct SetIter<'storage> {
+    inner: Iter<'storage, Binary>
+}
+
+impl<K: Key, N: Namespace> Set<K, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `Set`:
+    ///  * N + "key" + K,
+    ///  * N + "index"
+    ///  * N + n - where n is a number
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            iterable: IterableStorage::new(StaticKey(N::NAMESPACE)),
+            key_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Adds the key to the set.
+    /// Returns `true` if it wasn't a member already.
+    pub fn insert(&mut self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<bool> {
+        let member = Self::member(key.into());
+        let index_key = Self::index_key(&member);
+
+        if storage.get(&index_key).is_some() {
+            return Ok(false);
+        }
+
+        let index = self.iterable.push(storage, &Binary(member))?;
+        super::save(storage, index_key, &index)?;
+
+        Ok(true)
+    }
+
+    /// Removes the key from the set.
+    /// Returns `true` if it was a member.
+    pub fn remove(&mut self, storage: &mut dyn Storage, key: impl Into<K>) -> StdResult<bool> {
+        let index_key = Self::index_key(&Self::member(key.into()));
+
+        let Some(index) = super::load::<u64>(storage, &index_key)? else {
+            return Ok(false);
+        };
+
+        storage.remove(&index_key);
+
+        if let Some(swapped) = self.iterable.swap_remove(storage, index)? {
+            super::save(storage, Self::index_key(&swapped.0), &index)?;
+        }
+
+        Ok(true)
+    }
+
+    #[inline]
+    pub fn contains(&self, storage: &dyn Storage, key: impl Into<K>) -> bool {
+        storage.get(&Self::index_key(&Self::member(key.into()))).is_some()
+    }
+
+    /// Returns the number of members.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        self.iterable.len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns an iterator over all members.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage
+    ) -> StdResult<SetIter<'storage>> {
+        Ok(SetIter {
+            inner: self.iterable.iter(storage)?
+        })
+    }
+
+    #[inline]
+    fn member(key: K) -> Vec<u8> {
+        let mut member = Vec::with_capacity(key.size());
+        key.write_segments(&mut member);
+
+        member
+    }
+
+    #[inline]
+    fn index_key(member: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(N::NAMESPACE.len() + KEY_NS.size() + member.len());
+        key.extend_from_slice(N::NAMESPACE);
+        key.extend_from_slice(KEY_NS.0);
+        key.extend_from_slice(member);
+
+        key
+    }
+}
+
+impl<'storage> SetIter<'storage> {
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.inner.len()
+    }
+}
+
+impl<'storage> Iterator for SetIter<'storage> {
+    type Item = StdResult<Vec<u8>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|x| x.map(|member| member.0))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|x| x.map(|member| member.0))
+    }
+}
+
+impl<'storage> DoubleEndedIterator for SetIter<'storage> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|x| x.map(|member| member.0))
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth_back(n).map(|x| x.map(|member| member.0))
+    }
+}
+
+impl<'storage> ExactSizeIterator for SetIter<'storage> { }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    #[test]
+    fn set_insert_remove() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let mut set = Set::<TypedKey<u16>, TestNs>::new();
+        let mut model = BTreeSet::new();
+        let keys = (0..200u16).map(|i| (i * 7) % 50).collect::<Vec<_>>();
+
+        for (i, key) in keys.iter().enumerate() {
+            if i % 3 == 0 {
+                assert_eq!(set.remove(storage, key).unwrap(), model.remove(key));
+            } else {
+                assert_eq!(set.insert(storage, key).unwrap(), model.insert(*key));
+            }
+
+            assert_eq!(set.contains(storage, key), model.contains(key));
+        }
+
+        assert_eq!(set.len(storage).unwrap(), model.len() as u64);
+
+        let members = set.iter(storage).unwrap()
+            .map(|x| u16::from_be_bytes(x.unwrap().try_into().unwrap()))
+            .collect::<BTreeSet<u16>>();
+        assert_eq!(members, model);
+
+        for key in &model {
+            assert!(set.remove(storage, key).unwrap());
+        }
+
+        assert!(set.is_empty(storage).unwrap());
+        assert_eq!(set.iter(storage).unwrap().next(), None);
+    }
+}
@@ -0,0 +1,650 @@
+use std::marker::PhantomData;
+
+use crate::{
+    self as fadroma,
+    bin_serde::{FadromaSerialize, FadromaDeserialize},
+    cosmwasm_std::{Storage, StdResult, StdError}
+};
+use super::{Key, Namespace, not_found_error};
+
+const KEY_VALUE: &[u8] = b"value";
+const KEY_META: &[u8] = b"meta";
+const KEY_HEIGHT: &[u8] = b"height";
+const KEY_CHECKPOINT: &[u8] = b"cp";
+
+/// Determines when [`SnapshotItem`] and [`SnapshotItemSpace`]
+/// record the value in their history.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strategy {
+    /// Record a checkpoint on every change. Multiple changes at the
+    /// same height replace each other so that only the value at the
+    /// end of the block is kept.
+    EveryBlock,
+    /// Only record a checkpoint when explicitly requested by calling
+    /// `checkpoint`. Changes in between are not visible in the history.
+    Selected
+}
+
+/// Stores a single item like [`super::SingleItem`] and additionally
+/// keeps its history by block height so that the value it had at the
+/// end of a past block can be loaded with [`SnapshotItem::load_at`].
+/// Lookups are a binary search over the recorded checkpoints.
+///
+/// If a retention window is set with [`SnapshotItem::with_retention`],
+/// checkpoints that are older than it are pruned on every write. The most
+/// recent checkpoint before the window is always kept so that every height
+/// inside the window can still be answered.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::mock_dependencies},
+///     storage::snapshot::{SnapshotItem, Strategy}
+/// };
+///
+/// fadroma::namespace!(SupplyNs, b"supply");
+/// const SUPPLY: SnapshotItem<u128, SupplyNs> = SnapshotItem::new(Strategy::EveryBlock);
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// SUPPLY.save(storage, 10, &1000)?;
+/// SUPPLY.save(storage, 20, &1500)?;
+/// SUPPLY.save(storage, 20, &1700)?;
+///
+/// assert_eq!(SUPPLY.load_at(storage, 5)?, None);
+/// assert_eq!(SUPPLY.load_at(storage, 15)?, Some(1000));
+/// assert_eq!(SUPPLY.load_at(storage, 20)?, Some(1700));
+/// assert_eq!(SUPPLY.load(storage)?, Some(1700));
+/// # Ok(())
+/// # }
+/// ```
+pub struct SnapshotItem<T: FadromaSerialize + FadromaDeserialize, N: Namespace> {
+    strategy: Strategy,
+    retention: Option<u64>,
+    namespace_data: PhantomData<N>,
+    item_data: PhantomData<T>
+}
+
+/// Stores many items like [`super::ItemSpace`] and keeps the history
+/// of each one by block height in the same way as [`SnapshotItem`].
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{CanonicalAddr, StdResult, testing::mock_dependencies},
+///     storage::{TypedKey, snapshot::{SnapshotItemSpace, Strategy}}
+/// };
+///
+/// fadroma::namespace!(StakesNs, b"stakes");
+/// const STAKES: SnapshotItemSpace<u128, StakesNs, TypedKey<CanonicalAddr>> =
+///     SnapshotItemSpace::new(Strategy::EveryBlock).with_retention(100);
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// let alice = CanonicalAddr::from(b"alice".as_slice());
+///
+/// STAKES.save(storage, &alice, 10, &50)?;
+/// STAKES.remove(storage, &alice, 30)?;
+///
+/// assert_eq!(STAKES.load_at(storage, &alice, 29)?, Some(50));
+/// assert_eq!(STAKES.load_at(storage, &alice, 30)?, None);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SnapshotItemSpace<T: FadromaSerialize + FadromaDeserialize, N: Namespace, K: Key> {
+    strategy: Strategy,
+    retention: Option<u64>,
+    namespace_data: PhantomData<N>,
+    item_data: PhantomData<T>,
+    key_data: PhantomData<K>
+}
+
+#[derive(FadromaSerialize, FadromaDeserialize, Clone, Copy, Default)]
+struct CheckpointsMeta {
+    /// Index of the oldest checkpoint that hasn't been pruned.
+    start: u64,
+    /// One past the index of the latest checkpoint.
+    end: u64
+}
+
+/// The history of a single value. The checkpoint heights and values
+/// are stored separately so that the binary search only loads heights.
+struct Checkpoints {
+    name: &'static str,
+    ns: &'static [u8],
+    /// Empty for [`SnapshotItem`] and the length prefixed key for
+    /// [`SnapshotItemSpace`] so that the checkpoint index that follows
+    /// it can't be confused with the bytes of a longer key.
+    base: Vec<u8>,
+    retention: Option<u64>
+}
+
+impl<T: FadromaSerialize + FadromaDeserialize, N: Namespace> SnapshotItem<T, N> {
+    /// Creates an instance with the given [`Strategy`] that keeps all checkpoints.
+    /// The following namespaces are reserved by `SnapshotItem`:
+    ///  * N + "value"
+    ///  * N + "meta"
+    ///  * N + "height" + n - where n is a number
+    ///  * N + "cp" + n - where n is a number
+    #[inline]
+    pub const fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            retention: None,
+            namespace_data: PhantomData,
+            item_data: PhantomData
+        }
+    }
+
+    /// Prune checkpoints that are older than the given number of blocks.
+    #[inline]
+    pub const fn with_retention(self, blocks: u64) -> Self {
+        Self {
+            retention: Some(blocks),
+            ..self
+        }
+    }
+
+    #[inline]
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Saves the item as the current value at the given `height`.
+    #[inline]
+    pub fn save(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64,
+        item: &T
+    ) -> StdResult<()> {
+        self.checkpoints().save(storage, self.strategy, height, Some(item))
+    }
+
+    /// Removes the current value at the given `height`. The history is kept
+    /// and loading at this height or later will return `None`.
+    #[inline]
+    pub fn remove(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64
+    ) -> StdResult<()> {
+        self.checkpoints().save::<T>(storage, self.strategy, height, None)
+    }
+
+    #[inline]
+    pub fn load(
+        &self,
+        storage: &dyn Storage
+    ) -> StdResult<Option<T>> {
+        self.checkpoints().load(storage)
+    }
+
+    #[inline]
+    pub fn load_or_error(
+        &self,
+        storage: &dyn Storage
+    ) -> StdResult<T> {
+        let result: Option<T> = self.load(storage)?;
+
+        result.ok_or_else(|| not_found_error::<T>())
+    }
+
+    /// Loads the value that the item had at the end of block `height`.
+    /// Returns an error if the checkpoints for that height have been pruned.
+    #[inline]
+    pub fn load_at(
+        &self,
+        storage: &dyn Storage,
+        height: u64
+    ) -> StdResult<Option<T>> {
+        self.checkpoints().load_at(storage, height)
+    }
+
+    /// Records the current value as a checkpoint at the given `height`.
+    /// This is what [`Strategy::Selected`] relies on but it can be
+    /// called regardless of the strategy.
+    #[inline]
+    pub fn checkpoint(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64
+    ) -> StdResult<()> {
+        self.checkpoints().checkpoint::<T>(storage, height)
+    }
+
+    /// Removes the checkpoints that are outside of the retention window
+    /// relative to the given `height`. Does nothing if no window is set.
+    /// Returns the number of checkpoints removed.
+    #[inline]
+    pub fn prune(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64
+    ) -> StdResult<u64> {
+        self.checkpoints().prune(storage, height)
+    }
+
+    #[inline]
+    fn checkpoints(&self) -> Checkpoints {
+        Checkpoints {
+            name: "SnapshotItem",
+            ns: N::NAMESPACE,
+            base: vec![],
+            retention: self.retention
+        }
+    }
+}
+
+impl<T: FadromaSerialize + FadromaDeserialize, N: Namespace, K: Key> SnapshotItemSpace<T, N, K> {
+    /// Creates an instance with the given [`Strategy`] that keeps all checkpoints.
+    /// The following namespaces are reserved by `SnapshotItemSpace`:
+    ///  * N + "value" + len(K) + K
+    ///  * N + "meta" + len(K) + K
+    ///  * N + "height" + len(K) + K + n - where n is a number
+    ///  * N + "cp" + len(K) + K + n - where n is a number
+    #[inline]
+    pub const fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            retention: None,
+            namespace_data: PhantomData,
+            item_data: PhantomData,
+            key_data: PhantomData
+        }
+    }
+
+    /// Prune checkpoints that are older than the given number of blocks.
+    #[inline]
+    pub const fn with_retention(self, blocks: u64) -> Self {
+        Self {
+            retention: Some(blocks),
+            ..self
+        }
+    }
+
+    #[inline]
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Saves the item under `key` as the current value at the given `height`.
+    #[inline]
+    pub fn save(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        height: u64,
+        item: &T
+    ) -> StdResult<()> {
+        self.checkpoints(key).save(storage, self.strategy, height, Some(item))
+    }
+
+    /// Removes the current value under `key` at the given `height`. The
+    /// history is kept and loading at this height or later will return `None`.
+    #[inline]
+    pub fn remove(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        height: u64
+    ) -> StdResult<()> {
+        self.checkpoints(key).save::<T>(storage, self.strategy, height, None)
+    }
+
+    #[inline]
+    pub fn load(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<K>
+    ) -> StdResult<Option<T>> {
+        self.checkpoints(key).load(storage)
+    }
+
+    #[inline]
+    pub fn load_or_error(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<K>
+    ) -> StdResult<T> {
+        let result: Option<T> = self.load(storage, key)?;
+
+        result.ok_or_else(|| not_found_error::<T>())
+    }
+
+    /// Loads the value that the item under `key` had at the end of block `height`.
+    /// Returns an error if the checkpoints for that height have been pruned.
+    #[inline]
+    pub fn load_at(
+        &self,
+        storage: &dyn Storage,
+        key: impl Into<K>,
+        height: u64
+    ) -> StdResult<Option<T>> {
+        self.checkpoints(key).load_at(storage, height)
+    }
+
+    /// Records the current value under `key` as a checkpoint at the given
+    /// `height`. This is what [`Strategy::Selected`] relies on but it can be
+    /// called regardless of the strategy.
+    #[inline]
+    pub fn checkpoint(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        height: u64
+    ) -> StdResult<()> {
+        self.checkpoints(key).checkpoint::<T>(storage, height)
+    }
+
+    /// Removes the checkpoints of the item under `key` that are outside of
+    /// the retention window relative to the given `height`. Does nothing if
+    /// no window is set. Returns the number of checkpoints removed.
+    #[inline]
+    pub fn prune(
+        &self,
+        storage: &mut dyn Storage,
+        key: impl Into<K>,
+        height: u64
+    ) -> StdResult<u64> {
+        self.checkpoints(key).prune(storage, height)
+    }
+
+    #[inline]
+    fn checkpoints(&self, key: impl Into<K>) -> Checkpoints {
+        let key = key.into();
+
+        let mut base = Vec::with_capacity(4 + key.size());
+        base.extend_from_slice(&(key.size() as u32).to_be_bytes());
+        key.write_segments(&mut base);
+
+        Checkpoints {
+            name: "SnapshotItemSpace",
+            ns: N::NAMESPACE,
+            base,
+            retention: self.retention
+        }
+    }
+}
+
+impl Checkpoints {
+    fn save<T: FadromaSerialize + FadromaDeserialize>(
+        &self,
+        storage: &mut dyn Storage,
+        strategy: Strategy,
+        height: u64,
+        item: Option<&T>
+    ) -> StdResult<()> {
+        let key = self.key(KEY_VALUE);
+
+        match item {
+            Some(item) => super::save(storage, &key, item)?,
+            None => storage.remove(&key)
+        }
+
+        match strategy {
+            Strategy::EveryBlock => self.record(storage, height, item),
+            Strategy::Selected => Ok(())
+        }
+    }
+
+    #[inline]
+    fn load<T: FadromaDeserialize>(&self, storage: &dyn Storage) -> StdResult<Option<T>> {
+        super::load(storage, self.key(KEY_VALUE))
+    }
+
+    fn load_at<T: FadromaDeserialize>(
+        &self,
+        storage: &dyn Storage,
+        height: u64
+    ) -> StdResult<Option<T>> {
+        let meta = self.meta(storage)?;
+
+        // Find the first checkpoint that is after the given height.
+        // The one before it holds the value at that height.
+        let mut low = meta.start;
+        let mut high = meta.end;
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+
+            if self.height(storage, mid)? <= height {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        if low > meta.start {
+            super::load(storage, self.index_key(KEY_CHECKPOINT, low - 1))
+        } else if meta.start > 0 {
+            Err(StdError::generic_err(format!(
+                "{}: the value at height {} has been pruned.",
+                self.name,
+                height
+            )))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn checkpoint<T: FadromaSerialize + FadromaDeserialize>(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64
+    ) -> StdResult<()> {
+        let item: Option<T> = self.load(storage)?;
+
+        self.record(storage, height, item.as_ref())
+    }
+
+    fn record<T: FadromaSerialize>(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64,
+        item: Option<&T>
+    ) -> StdResult<()> {
+        let mut meta = self.meta(storage)?;
+
+        let index = if meta.end > meta.start {
+            let last = self.height(storage, meta.end - 1)?;
+
+            if height < last {
+                return Err(StdError::generic_err(format!(
+                    "{}: cannot record a checkpoint at height {} which is before the last one at height {}.",
+                    self.name,
+                    height,
+                    last
+                )));
+            }
+
+            if height == last {
+                meta.end - 1
+            } else {
+                meta.end
+            }
+        } else {
+            meta.end
+        };
+
+        if index == meta.end {
+            super::save(storage, self.index_key(KEY_HEIGHT, index), &height)?;
+            meta.end += 1;
+
+            super::save(storage, self.key(KEY_META), &meta)?;
+        }
+
+        let key = self.index_key(KEY_CHECKPOINT, index);
+
+        match item {
+            Some(item) => super::save(storage, key, item)?,
+            None => storage.remove(&key)
+        }
+
+        self.prune(storage, height)?;
+
+        Ok(())
+    }
+
+    fn prune(&self, storage: &mut dyn Storage, height: u64) -> StdResult<u64> {
+        let Some(retention) = self.retention else {
+            return Ok(0);
+        };
+
+        let cutoff = height.saturating_sub(retention);
+        let mut meta = self.meta(storage)?;
+        let mut removed = 0;
+
+        // Keep the latest checkpoint at or before the cutoff
+        // since it holds the value at the start of the window.
+        while meta.end - meta.start > 1 && self.height(storage, meta.start + 1)? <= cutoff {
+            storage.remove(&self.index_key(KEY_HEIGHT, meta.start));
+            storage.remove(&self.index_key(KEY_CHECKPOINT, meta.start));
+
+            meta.start += 1;
+            removed += 1;
+        }
+
+        if removed > 0 {
+            super::save(storage, self.key(KEY_META), &meta)?;
+        }
+
+        Ok(removed)
+    }
+
+    #[inline]
+    fn meta(&self, storage: &dyn Storage) -> StdResult<CheckpointsMeta> {
+        let meta: Option<CheckpointsMeta> = super::load(storage, self.key(KEY_META))?;
+
+        Ok(meta.unwrap_or_default())
+    }
+
+    #[inline]
+    fn height(&self, storage: &dyn Storage, index: u64) -> StdResult<u64> {
+        let height: Option<u64> = super::load(storage, self.index_key(KEY_HEIGHT, index))?;
+
+        height.ok_or_else(not_found_error::<u64>)
+    }
+
+    #[inline]
+    fn key(&self, kind: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.ns.len() + kind.len() + self.base.len());
+        key.extend_from_slice(self.ns);
+        key.extend_from_slice(kind);
+        key.extend_from_slice(&self.base);
+
+        key
+    }
+
+    #[inline]
+    fn index_key(&self, kind: &[u8], index: u64) -> Vec<u8> {
+        let mut key = self.key(kind);
+        key.extend_from_slice(&index.to_be_bytes());
+
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    #[test]
+    fn every_block_history() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let item = SnapshotItem::<u64, TestNs>::new(Strategy::EveryBlock);
+
+        for height in (10..100).step_by(10) {
+            item.save(storage, height, &(height * 2)).unwrap();
+            item.save(storage, height, &(height * 3)).unwrap();
+        }
+
+        assert_eq!(item.checkpoints().meta(storage).unwrap().end, 9);
+        assert_eq!(item.load_at(storage, 0).unwrap(), None);
+        assert_eq!(item.load_at(storage, 9).unwrap(), None);
+
+        for height in 10..100 {
+            assert_eq!(item.load_at(storage, height).unwrap(), Some(height / 10 * 30));
+        }
+
+        item.remove(storage, 100).unwrap();
+        assert_eq!(item.load(storage).unwrap(), None);
+        assert_eq!(item.load_at(storage, 99).unwrap(), Some(270));
+        assert_eq!(item.load_at(storage, 100).unwrap(), None);
+        assert_eq!(item.load_at(storage, u64::MAX).unwrap(), None);
+
+        let err = item.save(storage, 50, &1).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("SnapshotItem: cannot record a checkpoint at height 50 which is before the last one at height 100.")
+        );
+    }
+
+    #[test]
+    fn selected_history() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let item = SnapshotItem::<u64, TestNs>::new(Strategy::Selected);
+
+        item.save(storage, 1, &1).unwrap();
+        item.save(storage, 2, &2).unwrap();
+        assert_eq!(item.load_at(storage, 2).unwrap(), None);
+
+        item.checkpoint(storage, 2).unwrap();
+        item.save(storage, 3, &3).unwrap();
+        item.save(storage, 4, &4).unwrap();
+
+        assert_eq!(item.load(storage).unwrap(), Some(4));
+        assert_eq!(item.load_at(storage, 1).unwrap(), None);
+        assert_eq!(item.load_at(storage, 4).unwrap(), Some(2));
+
+        item.checkpoint(storage, 5).unwrap();
+        assert_eq!(item.load_at(storage, 4).unwrap(), Some(2));
+        assert_eq!(item.load_at(storage, 5).unwrap(), Some(4));
+    }
+
+    #[test]
+    fn pruning() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+        let space = SnapshotItemSpace::<u64, TestNs, TypedKey<u8>>::new(Strategy::EveryBlock)
+            .with_retention(20);
+
+        for height in 1..=50 {
+            space.save(storage, &1, height, &height).unwrap();
+        }
+
+        space.save(storage, &2, 50, &7).unwrap();
+
+        // Heights 30 to 50 are inside the window.
+        let meta = space.checkpoints(&1).meta(storage).unwrap();
+        assert_eq!(meta.end - meta.start, 21);
+
+        for height in 30..=50 {
+            assert_eq!(space.load_at(storage, &1, height).unwrap(), Some(height));
+        }
+
+        let err = space.load_at(storage, &1, 29).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("SnapshotItemSpace: the value at height 29 has been pruned.")
+        );
+
+        assert_eq!(space.load_at(storage, &2, 49).unwrap(), None);
+        assert_eq!(space.load_at(storage, &2, 50).unwrap(), Some(7));
+
+        // Nothing changed for a while so only the latest checkpoint is kept.
+        assert_eq!(space.prune(storage, &1, 1000).unwrap(), 20);
+        assert_eq!(space.load_at(storage, &1, 980).unwrap(), Some(50));
+        assert_eq!(space.prune(storage, &1, 2000).unwrap(), 0);
+    }
+}
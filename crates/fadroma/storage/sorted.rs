@@ -0,0 +1,409 @@
+use std::marker::PhantomData;
+
+use crate::cosmwasm_std::{Storage, Binary, StdResult, StdError};
+use super::{
+    Namespace, Key, Segment,
+    btree::{BTree, Keys, segment_bytes, encode, after, split_encoded}
+};
+
+pub use super::btree::{Order, Bound};
+
+const KEY_MEMBER: &[u8] = b"member";
+
+/// A set of unique members where each one has a score and the members are
+/// kept sorted by it. Members with the same score are sorted by their key.
+/// Inserting, removing and finding the rank of a member as well as getting
+/// the member at a given rank take logarithmic time since the entries are
+/// stored in a B+ tree that is laid out across storage keys. This makes it
+/// suitable for order books, auction bids, leaderboards and priority queues.
+///
+/// The score can be any [`Segment`] and is compared by the bytes it writes.
+/// For integers these are in big endian, so they are sorted by value.
+///
+/// # Examples
+///
+/// ```
+/// use fadroma::{
+///     cosmwasm_std::{StdResult, testing::mock_dependencies},
+///     storage::{TypedKey, sorted::{SortedSet, Bound, Order}}
+/// };
+///
+/// fadroma::namespace!(BidsNs, b"bids");
+/// const BIDS: SortedSet<TypedKey<&str>, BidsNs> = SortedSet::new();
+///
+/// # fn main() -> StdResult<()> {
+/// # let mut deps = mock_dependencies();
+/// # let storage = deps.as_mut().storage;
+/// BIDS.insert(storage, &"alice", &300u128)?;
+/// BIDS.insert(storage, &"bob", &100u128)?;
+/// BIDS.insert(storage, &"carol", &200u128)?;
+///
+/// // The highest bid.
+/// let (score, member) = BIDS.iter(storage, Order::Descending)?.next().unwrap()?;
+/// assert_eq!(score, 300u128.to_be_bytes());
+/// assert_eq!(member, b"alice");
+///
+/// // The first bid above 100.
+/// let (_, member) = BIDS.range(
+///     storage,
+///     Bound::exclusive(&100u128),
+///     Bound::Unbounded,
+///     Order::Ascending
+/// )?.next().unwrap()?;
+/// assert_eq!(member, b"carol");
+///
+/// assert_eq!(BIDS.rank(storage, &"bob", Order::Descending)?, Some(2));
+/// # Ok(())
+/// # }
+/// ```
+pub struct SortedSet<K: Key, N: Namespace> {
+    key_data: PhantomData<K>,
+    ns_data: PhantomData<N>
+}
+
+/// Iterator over the entries of a [`SortedSet`] in score order.
+/// Yields the bytes written by the score and the member key.
+pub struct SortedIter<'storage> {
+    inner: Keys<'storage>
+}
+
+impl<K: Key, N: Namespace> SortedSet<K, N> {
+    /// Creates an instance for the given namespace.
+    /// The following namespaces are reserved by `SortedSet`:
+    ///  * N + "meta"
+    ///  * N + "node" + n - where n is a number
+    ///  * N + "member" + K
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            key_data: PhantomData,
+            ns_data: PhantomData
+        }
+    }
+
+    /// Inserts the member with the given score or updates its score if
+    /// it is already in the set. Returns `true` if it is a new member.
+    pub fn insert(
+        &self,
+        storage: &mut dyn Storage,
+        member: impl Into<K>,
+        score: &(impl Segment + ?Sized)
+    ) -> StdResult<bool> {
+        let member = Self::member_bytes(member.into());
+        let score = segment_bytes(score);
+        let tree = BTree::new(N::NAMESPACE);
+
+        let previous = self.score_raw(storage, &member)?;
+
+        if let Some(previous) = &previous {
+            if *previous == score {
+                return Ok(false);
+            }
+
+            tree.remove(storage, &entry_key(previous, &member))?;
+        }
+
+        tree.insert(storage, &entry_key(&score, &member))?;
+        super::save(storage, Self::member_key(&member), &Binary(score))?;
+
+        Ok(previous.is_none())
+    }
+
+    /// Removes the member from the set. Returns `true` if it was in the set.
+    pub fn remove(
+        &self,
+        storage: &mut dyn Storage,
+        member: impl Into<K>
+    ) -> StdResult<bool> {
+        let member = Self::member_bytes(member.into());
+
+        let Some(score) = self.score_raw(storage, &member)? else {
+            return Ok(false);
+        };
+
+        BTree::new(N::NAMESPACE).remove(storage, &entry_key(&score, &member))?;
+        storage.remove(&Self::member_key(&member));
+
+        Ok(true)
+    }
+
+    /// Returns the bytes written by the score of the member.
+    #[inline]
+    pub fn score(
+        &self,
+        storage: &dyn Storage,
+        member: impl Into<K>
+    ) -> StdResult<Option<Vec<u8>>> {
+        self.score_raw(storage, &Self::member_bytes(member.into()))
+    }
+
+    #[inline]
+    pub fn contains(&self, storage: &dyn Storage, member: impl Into<K>) -> bool {
+        storage.get(&Self::member_key(&Self::member_bytes(member.into()))).is_some()
+    }
+
+    /// Returns the number of members.
+    #[inline]
+    pub fn len(&self, storage: &dyn Storage) -> StdResult<u64> {
+        BTree::new(N::NAMESPACE).len(storage)
+    }
+
+    #[inline]
+    pub fn is_empty(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.len(storage)? == 0)
+    }
+
+    /// Returns the position of the member when the set is sorted in the given order.
+    pub fn rank(
+        &self,
+        storage: &dyn Storage,
+        member: impl Into<K>,
+        order: Order
+    ) -> StdResult<Option<u64>> {
+        let member = Self::member_bytes(member.into());
+
+        let Some(score) = self.score_raw(storage, &member)? else {
+            return Ok(None);
+        };
+
+        let tree = BTree::new(N::NAMESPACE);
+        let rank = tree.rank(storage, &entry_key(&score, &member))?;
+
+        match order {
+            Order::Ascending => Ok(Some(rank)),
+            Order::Descending => Ok(Some(tree.len(storage)? - 1 - rank))
+        }
+    }
+
+    /// Returns the score and member at position `n` when the set is sorted in the given order.
+    pub fn nth(
+        &self,
+        storage: &dyn Storage,
+        n: u64,
+        order: Order
+    ) -> StdResult<Option<(Vec<u8>, Vec<u8>)>> {
+        let tree = BTree::new(N::NAMESPACE);
+
+        let n = match order {
+            Order::Ascending => n,
+            Order::Descending => {
+                let len = tree.len(storage)?;
+
+                match n.checked_add(1).and_then(|x| len.checked_sub(x)) {
+                    Some(n) => n,
+                    None => return Ok(None)
+                }
+            }
+        };
+
+        match tree.nth(storage, n)? {
+            Some(key) => Ok(Some(decode_entry(&key)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns up to `n` entries from the start of the set sorted in the given order.
+    #[inline]
+    pub fn top(
+        &self,
+        storage: &dyn Storage,
+        n: usize,
+        order: Order
+    ) -> StdResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.iter(storage, order)?.take(n).collect()
+    }
+
+    /// Returns an iterator over all entries in the given order.
+    #[inline]
+    pub fn iter<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        order: Order
+    ) -> StdResult<SortedIter<'storage>> {
+        self.range(storage, Bound::Unbounded, Bound::Unbounded, order)
+    }
+
+    /// Returns an iterator over the entries whose score is within the given bounds.
+    pub fn range<'storage>(
+        &self,
+        storage: &'storage dyn Storage,
+        min: Bound,
+        max: Bound,
+        order: Order
+    ) -> StdResult<SortedIter<'storage>> {
+        let min = match min {
+            Bound::Inclusive(score) => Bound::Inclusive(encode(&score)),
+            Bound::Exclusive(score) => Bound::Inclusive(after(encode(&score))),
+            Bound::Unbounded => Bound::Unbounded
+        };
+
+        let max = match max {
+            Bound::Inclusive(score) => Bound::Exclusive(after(encode(&score))),
+            Bound::Exclusive(score) => Bound::Exclusive(encode(&score)),
+            Bound::Unbounded => Bound::Unbounded
+        };
+
+        Ok(SortedIter {
+            inner: BTree::new(N::NAMESPACE).keys(storage, min, max, order)?
+        })
+    }
+
+    #[inline]
+    fn score_raw(&self, storage: &dyn Storage, member: &[u8]) -> StdResult<Option<Vec<u8>>> {
+        let score: Option<Binary> = super::load(storage, Self::member_key(member))?;
+
+        Ok(score.map(|x| x.0))
+    }
+
+    #[inline]
+    fn member_bytes(member: K) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(member.size());
+        member.write_segments(&mut bytes);
+
+        bytes
+    }
+
+    #[inline]
+    fn member_key(member: &[u8]) -> Vec<u8> {
+        [N::NAMESPACE, KEY_MEMBER, member].concat()
+    }
+}
+
+impl<'storage> Iterator for SortedIter<'storage> {
+    type Item = StdResult<(Vec<u8>, Vec<u8>)>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|x| decode_entry(&x?))
+    }
+}
+
+/// The encoded score followed by the member so that entries
+/// are sorted by score first and then by member.
+#[inline]
+fn entry_key(score: &[u8], member: &[u8]) -> Vec<u8> {
+    let mut key = encode(score);
+    key.extend_from_slice(member);
+
+    key
+}
+
+#[inline]
+fn decode_entry(key: &[u8]) -> StdResult<(Vec<u8>, Vec<u8>)> {
+    match split_encoded(key) {
+        Some((score, member)) => Ok((score, member.to_vec())),
+        None => Err(StdError::generic_err("SortedSet: invalid entry."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        cosmwasm_std::testing::mock_dependencies,
+        storage::TypedKey,
+        namespace
+    };
+
+    namespace!(TestNs, b"test");
+
+    const SET: SortedSet<TypedKey<u16>, TestNs> = SortedSet::new();
+
+    fn decode(entry: (Vec<u8>, Vec<u8>)) -> (u32, u16) {
+        (
+            u32::from_be_bytes(entry.0.try_into().unwrap()),
+            u16::from_be_bytes(entry.1.try_into().unwrap())
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_sorted_set(ops in prop::collection::vec((0u16..64, 0u32..32, any::<bool>()), 1..300)) {
+            let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+            let mut model = BTreeSet::<(u32, u16)>::new();
+
+            for (member, score, insert) in ops {
+                let previous = model.iter().find(|x| x.1 == member).copied();
+
+                if insert {
+                    if let Some(previous) = previous {
+                        model.remove(&previous);
+                    }
+
+                    model.insert((score, member));
+                    prop_assert_eq!(SET.insert(storage, &member, &score).unwrap(), previous.is_none());
+                } else {
+                    if let Some(previous) = previous {
+                        model.remove(&previous);
+                    }
+
+                    prop_assert_eq!(SET.remove(storage, &member).unwrap(), previous.is_some());
+                }
+            }
+
+            prop_assert_eq!(SET.len(storage).unwrap(), model.len() as u64);
+
+            let ascending = SET.iter(storage, Order::Ascending).unwrap()
+                .map(|x| decode(x.unwrap()))
+                .collect::<Vec<_>>();
+            prop_assert_eq!(&ascending, &model.iter().copied().collect::<Vec<_>>());
+
+            for (rank, (score, member)) in model.iter().enumerate() {
+                prop_assert_eq!(SET.rank(storage, member, Order::Ascending).unwrap(), Some(rank as u64));
+                prop_assert_eq!(
+                    SET.rank(storage, member, Order::Descending).unwrap(),
+                    Some((model.len() - 1 - rank) as u64)
+                );
+                prop_assert_eq!(
+                    SET.nth(storage, rank as u64, Order::Ascending).unwrap().map(decode),
+                    Some((*score, *member))
+                );
+            }
+
+            let above = SET.range(storage, Bound::exclusive(&16u32), Bound::Unbounded, Order::Descending).unwrap()
+                .map(|x| decode(x.unwrap()))
+                .collect::<Vec<_>>();
+            prop_assert_eq!(above, model.iter().rev().filter(|x| x.0 > 16).copied().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn top_and_nth() {
+        let storage = &mut mock_dependencies().storage as &mut dyn Storage;
+
+        for member in 0..10u16 {
+            SET.insert(storage, &member, &(member as u32 % 3)).unwrap();
+        }
+
+        assert!(!SET.insert(storage, &9, &0u32).unwrap());
+        assert_eq!(SET.score(storage, &9).unwrap(), Some(0u32.to_be_bytes().to_vec()));
+        assert!(SET.contains(storage, &9));
+
+        let top = SET.top(storage, 3, Order::Descending).unwrap()
+            .into_iter()
+            .map(decode)
+            .collect::<Vec<_>>();
+        assert_eq!(top, [(2, 8), (2, 5), (2, 2)]);
+
+        assert_eq!(SET.nth(storage, 0, Order::Descending).unwrap().map(decode), Some((2, 8)));
+        assert_eq!(SET.nth(storage, 9, Order::Descending).unwrap().map(decode), Some((0, 0)));
+        assert_eq!(SET.nth(storage, 10, Order::Descending).unwrap(), None);
+        assert_eq!(SET.nth(storage, u64::MAX, Order::Descending).unwrap(), None);
+
+        let ones = SET.range(storage, Bound::inclusive(&1u32), Bound::inclusive(&1u32), Order::Ascending).unwrap()
+            .map(|x| decode(x.unwrap()).1)
+            .collect::<Vec<_>>();
+        assert_eq!(ones, [1, 4, 7]);
+
+        for member in 0..10u16 {
+            assert!(SET.remove(storage, &member).unwrap());
+        }
+
+        assert!(SET.is_empty(storage).unwrap());
+        assert_eq!(SET.rank(storage, &1, Order::Ascending).unwrap(), None);
+    }
+}